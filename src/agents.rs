@@ -1,5 +1,7 @@
 pub mod fetcher;
 pub mod notifier;
 pub mod player;
+pub mod queue;
 pub mod repo;
+pub mod tabs;
 pub mod updater;
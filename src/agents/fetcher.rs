@@ -1,7 +1,7 @@
 use super::notifier;
 use crate::{objects::JsError, utils};
 use chrono::{DateTime, FixedOffset};
-use js_sys::ArrayBuffer;
+use js_sys::{ArrayBuffer, Function, Promise, Reflect, Uint8Array};
 use podcast_player_common::{channel_val::ChannelVal, item_val::ItemVal, FeedVal};
 use serde::de::DeserializeOwned;
 use std::collections::{HashMap, HashSet};
@@ -9,6 +9,7 @@ use uuid::Uuid;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::ConnectionType;
+use yew::Callback;
 use yew_agent::{Agent, AgentLink, Context, Dispatched, Dispatcher, HandlerId};
 
 #[derive(Debug)]
@@ -29,8 +30,11 @@ pub enum Response {
     PullFeedVals(Result<Vec<FeedVal>, JsError>),
     PullChannelVals(Result<Vec<ChannelVal>, JsError>),
     PullItemVals(Result<Vec<ItemVal>, JsError>),
-    PullDownload(Uuid, Result<ArrayBuffer, JsError>),
+    /// Content type reported by the server and first bytes of the completed download
+    PullDownload(Uuid, Result<(Option<String>, Vec<u8>), JsError>),
     PullDownloadStarted(Uuid),
+    /// Chunk (item id, index, data) of a running download; sent to all subscribers
+    DownloadChunk(Uuid, u32, Uint8Array),
 }
 
 #[derive(Debug)]
@@ -40,7 +44,8 @@ pub enum Message {
     PullFeedVals(HandlerId, Result<Vec<FeedVal>, JsError>),
     PullChannelVals(HandlerId, Result<Vec<ChannelVal>, JsError>),
     PullItemVals(HandlerId, Result<Vec<ItemVal>, JsError>),
-    PullDownload(HandlerId, Uuid, Result<ArrayBuffer, JsError>),
+    PullDownload(HandlerId, Uuid, Result<(Option<String>, Vec<u8>), JsError>),
+    DownloadChunk(Uuid, u32, Uint8Array),
 }
 
 pub struct Fetcher {
@@ -49,6 +54,8 @@ pub struct Fetcher {
     notifier: Dispatcher<notifier::Notifier>,
 }
 
+const CHUNK_SIZE: u32 = 1 << 20;
+
 enum HttpMethod {
     Get,
     Post,
@@ -70,6 +77,16 @@ impl Fetcher {
             Message::PullDownload(handler_id, item_id, res) => self
                 .link
                 .respond(handler_id, Response::PullDownload(item_id, res)),
+            Message::DownloadChunk(item_id, index, data) => {
+                for subscriber in &self.subscribers {
+                    if subscriber.is_respondable() {
+                        self.link.respond(
+                            *subscriber,
+                            Response::DownloadChunk(item_id, index, data.clone()),
+                        );
+                    }
+                }
+            }
             // Message::ReceiveBinary(handler_id, uuid, res) => {
             //     self.link.respond(handler_id, Response::Binary(uuid, res));
             // }
@@ -148,9 +165,12 @@ impl Fetcher {
             }
             Request::PullDownload(item_id) => {
                 let url = format!("/api/items/{}/stream", item_id);
+                let on_chunk = self
+                    .link
+                    .callback(move |(index, data)| Message::DownloadChunk(item_id, index, data));
 
                 self.link.send_future(async move {
-                    Message::PullDownload(id, item_id, fetch_binary(&url, None).await)
+                    Message::PullDownload(id, item_id, fetch_chunked(&url, None, on_chunk).await)
                 });
                 self.link
                     .respond(id, Response::PullDownloadStarted(item_id));
@@ -274,18 +294,63 @@ async fn fetch(
 }
 
 // https://github.com/yewstack/yew/blob/v0.18/examples/futures/src/main.rs
-async fn fetch_binary(
+// The response is handed to `on_chunk` in chunks of about CHUNK_SIZE bytes as it arrives.
+// Once the response is complete, its content type and first bytes (e.g., for sniffing the type) are returned.
+async fn fetch_chunked(
     url: &str,
     headers: Option<HashMap<String, String>>,
-) -> Result<ArrayBuffer, JsError> {
-    Ok(ArrayBuffer::from(
-        JsFuture::from(
-            fetch(url, HttpMethod::Get, headers, None)
-                .await?
-                .array_buffer()?,
-        )
-        .await?,
-    ))
+    on_chunk: Callback<(u32, Uint8Array)>,
+) -> Result<(Option<String>, Vec<u8>), JsError> {
+    let resp = fetch(url, HttpMethod::Get, headers, None).await?;
+    let content_type = resp.headers().get("content-type")?;
+    let body = resp.body().ok_or("response without body")?;
+    let reader = Reflect::get(&body, &"getReader".into())?
+        .dyn_into::<Function>()?
+        .call0(&body)?;
+    let read = Reflect::get(&reader, &"read".into())?.dyn_into::<Function>()?;
+    let mut head = Vec::new();
+    let mut parts = Vec::new();
+    let mut length = 0;
+    let mut index = 0;
+
+    loop {
+        let result = JsFuture::from(read.call0(&reader)?.dyn_into::<Promise>()?).await?;
+        let done = Reflect::get(&result, &"done".into())?
+            .as_bool()
+            .unwrap_or(true);
+
+        if !done {
+            let part: Uint8Array = Reflect::get(&result, &"value".into())?.dyn_into()?;
+            let missing = utils::SNIFF_LENGTH.saturating_sub(head.len() as u32);
+
+            head.extend(part.subarray(0, missing.min(part.length())).to_vec());
+            length += part.length();
+            parts.push(part);
+        }
+
+        if length >= CHUNK_SIZE || (done && length > 0) {
+            on_chunk.emit((index, concat(&parts, length)));
+            index += 1;
+            parts.clear();
+            length = 0;
+        }
+
+        if done {
+            return Ok((content_type, head));
+        }
+    }
+}
+
+fn concat(parts: &[Uint8Array], length: u32) -> Uint8Array {
+    let res = Uint8Array::new_with_length(length);
+    let mut offset = 0;
+
+    for part in parts {
+        res.set(part, offset);
+        offset += part.length();
+    }
+
+    res
 }
 
 async fn fetch_text(
@@ -1,25 +1,97 @@
+mod audio_chain;
+mod media_error;
+mod media_session;
+mod progressive;
+mod sleep_timer;
 mod task;
+mod transition;
 
-use super::{notifier, repo};
-use crate::objects::{Item, JsError};
-use podcast_player_common::Channel;
+use super::{
+    fetcher, notifier, queue, repo,
+    tabs::{self, TabMessage},
+};
+use crate::{
+    objects::{
+        AudioProcessing, Bookmark, Chapter, IntroOutro, Item, ItemChapters, JsError,
+        ListeningSession, PlayerConfig, PlayerState, SkipIntervals,
+    },
+    utils,
+};
+use audio_chain::AudioChain;
+use chrono::Local;
+use js_sys::Uint8Array;
+use media_session::{Action, MediaSession};
+use podcast_player_common::{item_meta::DownloadStatus, Channel};
+use progressive::Progressive;
+use sleep_timer::SleepTimer;
+pub use sleep_timer::SleepTimerMode;
 use std::collections::HashSet;
 use task::*;
+use transition::{Crossfade, NextSource};
+use uuid::Uuid;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::{closure::Closure, JsValue};
-use web_sys::{self, Event, MediaSource};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{self, Blob, Event, HtmlAudioElement, MediaSourceReadyState};
 use yew_agent::{Agent, AgentLink, Bridge, Bridged, Context, Dispatched, Dispatcher, HandlerId};
 
 // TODO: check play events
 
+/// Lowest playback rate selectable for a channel.
+pub const MIN_PLAYBACK_RATE: f64 = 0.5;
+/// Highest playback rate selectable for a channel.
+pub const MAX_PLAYBACK_RATE: f64 = 2.5;
+/// Time (in seconds) before the transition to the next queued item at which the item is preloaded.
+const PRELOAD_LEAD: f64 = 30.0;
+
+/// Where the data of a source is obtained from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SourceMode {
+    /// the downloaded enclosure
+    Local,
+    /// the enclosure streamed from the server
+    Stream,
+}
+
+impl SourceMode {
+    /// Plays the enclosure locally, if it is (being) downloaded, and streams it otherwise.
+    pub fn for_item(item: &Item) -> Self {
+        match item.get_download_status() {
+            DownloadStatus::Ok | DownloadStatus::InProgress => Self::Local,
+            _ => Self::Stream,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Request {
-    SetSource(Item),
+    /// Sets the source; it starts at the given position (in seconds), if any, and at the stored playback time otherwise
+    SetSource(Item, SourceMode, Option<f64>),
     SetCurrentTime(f64),
+    SkipForward,
+    SkipBackward,
+    /// Sets (`None` resets) the skip lengths of the channel of the current source
+    SetSkipIntervals(Option<SkipIntervals>),
+    /// Sets the audio processing of the channel of the current source
+    SetAudioProcessing(AudioProcessing),
+    /// Sets the intro and outro lengths of the channel of the current source
+    SetIntroOutro(IntroOutro),
     SetVolume(f64),
     SetPlaybackRate(f64),
+    /// Changes the playback rate of the channel of the current source by the given amount
+    ChangePlaybackRate(f64),
+    /// Seeks to the given fraction (between 0 and 1) of the current source
+    SeekToFraction(f64),
+    /// Starts (`None` cancels) the sleep timer
+    SetSleepTimer(Option<SleepTimerMode>),
+    /// Adds the given number of seconds to the sleep timer
+    ExtendSleepTimer(f64),
     Play,
     Pause,
+    /// Pauses, if playing, and plays otherwise
+    TogglePlayback,
+    /// Bookmarks the current time of the current source with the given note
+    AddBookmark(String),
     GetStatus,
 }
 
@@ -28,35 +100,112 @@ pub enum Response {
     Playing,
     Paused,
     SourceSet(Item, Channel, f64),
+    TimeUpdate(f64),
+    DurationChange(f64),
+    Buffering(bool),
+    /// Chapters of the current source (empty, if it has none or they are not known yet)
+    Chapters(Vec<Chapter>),
+    /// Mode and remaining time (in seconds) of the sleep timer
+    SleepTimer(Option<(SleepTimerMode, f64)>),
     End,
     Status(Option<(Item, Channel, f64, bool)>),
+    /// The current source (or the one being set) cannot be played
+    Error(JsError),
 }
 
 #[derive(Debug)]
 pub enum Message {
     RepoMessage(repo::Response),
+    FetcherMessage(fetcher::Response),
+    QueueMessage(queue::Response),
+    TabsMessage(tabs::Response),
+    MediaSessionAction(Action),
+    SleepTimerTick(Event),
+    SilenceTick(Event),
+    CrossfadeTick(Event),
+    ChaptersRead(Uuid, Result<Vec<Chapter>, JsError>),
     SourceOpened(Event),
-    SourceBufferUpdate(Event),
+    SourceBufferUpdateEnd(Event),
+    SourceBufferError(Event),
+    ChunkRead(Uuid, u32, Result<JsValue, JsValue>),
+    EnclosureRestRead(Uuid, Result<JsValue, JsValue>),
+    OnDurationChange(Event),
     StartedPlaying(Result<JsValue, JsValue>),
     OnPlay(Event),
     OnPause(Event),
     OnTimeupdate(Event),
+    OnSeeked(Event),
     OnEnd(Event),
+    OnLoadedMetadata(Event),
+    OnWaiting(Event),
+    OnCanPlay(Event),
+    OnError(Event),
+    OnStalled(Event),
+    OnVisibilityChange(Event),
+    OnPageHide(Event),
 }
 
 pub struct Player {
     link: AgentLink<Self>,
     subscribers: HashSet<HandlerId>,
     repo: Box<dyn Bridge<repo::Repo>>,
-    audio_element: web_sys::HtmlAudioElement,
-    media_source: MediaSource,
+    fetcher: Box<dyn Bridge<fetcher::Fetcher>>,
+    queue: Box<dyn Bridge<queue::Queue>>,
+    tabs: Box<dyn Bridge<tabs::Tabs>>,
+    media_session: MediaSession,
+    sleep_timer: Option<SleepTimer>,
+    chapters: Option<(Uuid, Vec<Chapter>)>,
+    /// source of the chapters being loaded (item and enclosure)
+    chapter_source: Option<(Item, Option<Blob>)>,
+    sleep_timer_interval: Option<i32>,
+    sleep_timer_closure: Closure<dyn Fn(web_sys::Event)>,
+    /// created, when audio processing is enabled for the first time
+    audio_chain: Option<AudioChain>,
+    silence_interval: Option<i32>,
+    silence_closure: Closure<dyn Fn(web_sys::Event)>,
+    audio_element: HtmlAudioElement,
+    /// items in the queue (kept up to date through the queue broadcasts)
+    queued: Vec<Item>,
+    /// next queued item, preloaded shortly before the current source ends
+    next_source: Option<NextSource>,
+    /// previous source, while it fades out
+    crossfade: Option<Crossfade>,
+    crossfade_interval: Option<i32>,
+    crossfade_closure: Closure<dyn Fn(web_sys::Event)>,
+    object_url: Option<String>,
+    progressive: Option<Progressive>,
     mediasource_opened_closure: Closure<dyn Fn(web_sys::Event)>,
     sourcebuffer_update_closure: Closure<dyn Fn(web_sys::Event)>,
-    _on_play_closure: Closure<dyn Fn(web_sys::Event)>,
-    _on_pause_closure: Closure<dyn Fn(web_sys::Event)>,
-    _on_end_closure: Closure<dyn Fn(web_sys::Event)>,
+    sourcebuffer_error_closure: Closure<dyn Fn(web_sys::Event)>,
+    on_durationchange_closure: Closure<dyn Fn(web_sys::Event)>,
+    on_play_closure: Closure<dyn Fn(web_sys::Event)>,
+    on_pause_closure: Closure<dyn Fn(web_sys::Event)>,
+    on_end_closure: Closure<dyn Fn(web_sys::Event)>,
+    on_loadedmetadata_closure: Closure<dyn Fn(web_sys::Event)>,
+    on_waiting_closure: Closure<dyn Fn(web_sys::Event)>,
+    on_canplay_closure: Closure<dyn Fn(web_sys::Event)>,
+    on_error_closure: Closure<dyn Fn(web_sys::Event)>,
+    on_stalled_closure: Closure<dyn Fn(web_sys::Event)>,
     on_timeupdate_closure: Closure<dyn Fn(web_sys::Event)>,
+    on_seeked_closure: Closure<dyn Fn(web_sys::Event)>,
+    _on_visibilitychange_closure: Closure<dyn Fn(web_sys::Event)>,
+    _on_pagehide_closure: Closure<dyn Fn(web_sys::Event)>,
     source: Option<(Item, Channel)>,
+    config: PlayerConfig,
+    last_position_save: f64,
+    /// time (ms since the epoch) the playback was paused
+    paused_at: Option<f64>,
+    /// listening session in progress
+    session: Option<ListeningSession>,
+    /// the play count of the current source was incremented (on reaching the completion threshold)
+    marked_played: bool,
+    /// the failure of the current source was reported (an error of the source buffer is followed by one of the audio element)
+    failure_reported: bool,
+    /// the source stored before the last reload is being restored (status requests wait for it)
+    restoring: bool,
+    /// stored source, while its item is being loaded
+    restored_state: Option<PlayerState>,
+    buffering: bool,
     notifier: Dispatcher<notifier::Notifier>,
     tasks: Vec<Task>,
 }
@@ -88,12 +237,788 @@ impl Player {
         }
     }
 
+    /// Starts a listening session, if playing and none is in progress.
+    fn start_session(&mut self) {
+        if self.session.is_some() || self.audio_element.paused() {
+            return;
+        }
+
+        if let Some(source) = &self.source {
+            self.session = Some(ListeningSession {
+                id: Uuid::new_v4(),
+                item_id: source.0.get_id(),
+                channel_id: source.1.val.id,
+                start_position: self.audio_element.current_time(),
+                end_position: self.audio_element.current_time(),
+                started_at: Local::now().into(),
+                ended_at: Local::now().into(),
+                playback_rate: source.1.meta.playback_rate,
+            });
+        }
+    }
+
+    /// Ends the listening session in progress and adds it to the history; sessions without progress are dropped.
+    fn finish_session(&mut self) {
+        if let Some(mut session) = self.session.take() {
+            session.end_position = self.audio_element.current_time();
+            session.ended_at = Local::now().into();
+
+            if session.played() > 0.0 {
+                self.repo.send(repo::Request::AddListeningSession(session));
+            }
+        }
+    }
+
+    /// Seeks to the given time; while a progressive source is downloading, only the buffered range can be reached.
+    fn seek(&mut self, time: f64) {
+        self.finish_session();
+
+        let time = match &self.progressive {
+            Some(progressive) if !progressive.download_complete => time.min(self.buffered_end()),
+            _ => time,
+        };
+
+        self.tasks
+            .insert(0, Task::SetCurrentTime(SetCurrentTimeTask::new(time)))
+    }
+
+    /// Seeks relative to the current time, staying within the source.
+    fn skip(&mut self, offset: f64) {
+        let duration = self.audio_element.duration();
+        let mut time = (self.audio_element.current_time() + offset).max(0.0);
+
+        if duration.is_finite() {
+            time = time.min(duration);
+        }
+
+        self.seek(time);
+    }
+
+    /// Persists the current playback position of the source.
+    fn save_position(&mut self) {
+        if let Some(source) = &mut self.source {
+            source
+                .0
+                .set_playback_time(Some(self.audio_element.current_time()));
+            self.repo.send(repo::Request::UpdateItem(source.0.clone()));
+            self.last_position_save = js_sys::Date::now();
+        }
+
+        self.save_state();
+    }
+
+    /// Stores the current source, so it can be restored after a reload.
+    fn save_state(&mut self) {
+        if let Some(source) = &self.source {
+            self.repo
+                .send(repo::Request::GetPlayerState(Some(PlayerState {
+                    item_id: source.0.get_id(),
+                    channel_id: source.1.val.id,
+                    position: self.audio_element.current_time(),
+                    playing: !self.audio_element.paused(),
+                    paused_at: self.paused_at,
+                })));
+        }
+    }
+
+    /// Sets the stored source (paused at the stored position), unless a source was set in the meantime.
+    fn restore_state(&mut self, mut item: Item, state: PlayerState) {
+        let source_requested = self
+            .tasks
+            .iter()
+            .any(|task| matches!(task, Task::SetSource(_)));
+
+        if self.source.is_some() || source_requested {
+            return;
+        }
+
+        item.set_playback_time(Some(state.position));
+        let mut task = SetSourceTask::new(item.clone(), SourceMode::for_item(&item));
+
+        task.set_paused_at(state.paused_at);
+        // the restored source precedes the status requests waiting for it
+        self.tasks.push(Task::SetSource(task));
+    }
+
+    /// Appends the next chunk of a progressive source, if the source buffer is ready for it.
+    /// Once all chunks of the completed download are appended, the stream is ended.
+    fn append_chunks(&mut self) -> Result<(), JsError> {
+        let mut appended = Ok(());
+
+        if let Some(progressive) = &mut self.progressive {
+            if let Some(source_buffer) = &progressive.source_buffer {
+                if source_buffer.updating() {
+                    return Ok(());
+                }
+
+                match progressive.next_chunk() {
+                    Some(chunk) => {
+                        appended = source_buffer.append_buffer_with_array_buffer_view(&chunk)
+                    }
+                    None => {
+                        if progressive.is_exhausted()
+                            && progressive.media_source.ready_state() == MediaSourceReadyState::Open
+                        {
+                            progressive.media_source.end_of_stream()?;
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Err(e) = appended {
+            self.playback_failed(
+                &format!("the downloaded data could not be appended ({:?})", e),
+                true,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Aborts setting the source (if in progress) and reports the error; a corrupt enclosure stored locally is marked as failed, so it can be downloaded again.
+    fn playback_failed(&mut self, reason: &str, corrupt: bool) {
+        if self.failure_reported {
+            return;
+        }
+
+        let pending = match self.tasks.last() {
+            Some(Task::SetSource(task)) => Some(task.get_item().clone()),
+            _ => None,
+        };
+
+        if pending.is_some() {
+            self.tasks.pop();
+        }
+
+        let item = pending.or_else(|| self.source.as_ref().map(|source| source.0.clone()));
+        let error = match &item {
+            Some(item) => JsError::from_str(&format!(
+                "could not play \"{}\": {}",
+                item.get_title(),
+                reason
+            )),
+            None => JsError::from_str(&format!("could not play: {}", reason)),
+        };
+        // the data of a progressive source is only stored as enclosure, once its download completed
+        let stored = match &self.progressive {
+            Some(progressive) => progressive.download_complete,
+            None => self.object_url.is_some(),
+        };
+
+        self.failure_reported = true;
+        self.progressive = None;
+        self.finish_session();
+
+        if let (Some(item), true, true) = (item, corrupt, stored) {
+            self.repo
+                .send(repo::Request::SetEnclosureError(item, reason.to_string()));
+        }
+        if self.buffering {
+            self.buffering = false;
+            self.send_response(Response::Buffering(false));
+        }
+
+        self.notifier
+            .send(notifier::Request::NotifyError(error.clone()));
+        self.send_response(Response::Error(error));
+    }
+
+    /// Describes the error of the audio element; returns whether the data is corrupt (as opposed to, e.g., a network error).
+    fn media_error(&self) -> (String, bool) {
+        let error = match self.audio_element.error() {
+            Some(error) => error,
+            None => return (String::from("unknown error"), false),
+        };
+
+        media_error::describe(error.code(), &error.message())
+    }
+
+    /// End of the buffered range; seeking beyond it is not possible while a progressive source is downloading.
+    fn buffered_end(&self) -> f64 {
+        let buffered = self.audio_element.buffered();
+
+        match buffered.length() {
+            0 => 0.0,
+            length => buffered.end(length - 1).unwrap_or(0.0),
+        }
+    }
+
     fn send_response(&self, response: Response) {
+        if let Err(e) = self.update_media_session(&response) {
+            log::warn!("could not update media session: {:?}", e);
+        }
+
         for handler_id in &self.subscribers {
             self.link.respond(*handler_id, response.clone())
         }
     }
 
+    /// Mirrors the state sent to the subscribers in the media session.
+    fn update_media_session(&self, response: &Response) -> Result<(), JsError> {
+        match response {
+            Response::SourceSet(item, channel, _) => {
+                self.media_session.set_metadata(item, channel)?;
+                self.media_session.set_playback_state("paused")
+            }
+            Response::Playing => self.media_session.set_playback_state("playing"),
+            Response::Paused | Response::End => self.media_session.set_playback_state("paused"),
+            Response::TimeUpdate(_) | Response::DurationChange(_) => {
+                self.media_session.set_position_state(
+                    self.audio_element.duration(),
+                    self.audio_element.playback_rate(),
+                    self.audio_element.current_time(),
+                )
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Starts playing; rewinds first, if configured and the playback was paused for long.
+    fn play(&mut self) {
+        let now = js_sys::Date::now();
+
+        if let Some(rewind) = self
+            .paused_at
+            .take()
+            .and_then(|paused_at| self.config.resume_rewind((now - paused_at) / 1000.0))
+        {
+            self.skip(-rewind);
+        }
+
+        // the volume may have been faded out by the sleep timer
+        if let Some(volume) = self.channel_volume() {
+            self.audio_element.set_volume(volume);
+        }
+        if let Err(e) = self.apply_audio_processing() {
+            self.notifier.send(notifier::Request::NotifyError(e));
+        }
+
+        self.tasks.insert(0, Task::Play(PlayTask::new()))
+    }
+
+    /// Applies the audio processing of the channel of the current source.
+    /// The audio chain is created on first use (i.e., when starting to play, as browsers only allow audio contexts after a user interaction); silence is checked ten times a second, while skipping it is enabled.
+    fn apply_audio_processing(&mut self) -> Result<(), JsError> {
+        let processing = match &self.source {
+            Some(source) => self.config.audio_processing(&source.1.val.id),
+            None => return Ok(()),
+        };
+        let window = web_sys::window().ok_or("could not obtain window")?;
+
+        if self.audio_chain.is_none() && (processing.normalize || processing.skip_silence) {
+            self.audio_chain = Some(AudioChain::new(&self.audio_element)?);
+        }
+
+        match (processing.skip_silence, self.silence_interval) {
+            (true, None) => {
+                self.silence_interval =
+                    Some(window.set_interval_with_callback_and_timeout_and_arguments(
+                        self.silence_closure.as_ref().unchecked_ref(),
+                        100,
+                        &js_sys::Array::new(),
+                    )?);
+            }
+            (false, Some(handle)) => {
+                window.clear_interval_with_handle(handle);
+                self.silence_interval = None;
+
+                if let Some(source) = &self.source {
+                    self.audio_element
+                        .set_playback_rate(source.1.meta.playback_rate);
+                }
+            }
+            (_, _) => {}
+        }
+
+        if let Some(audio_chain) = &mut self.audio_chain {
+            audio_chain.set_processing(processing)?;
+            audio_chain.resume()?;
+        }
+
+        Ok(())
+    }
+
+    /// Speeds up the playback during silence.
+    fn silence_tick(&mut self) {
+        let playing = !self.audio_element.paused();
+
+        if let (Some(audio_chain), Some(source)) = (&mut self.audio_chain, &self.source) {
+            let rate = audio_chain.playback_rate(
+                js_sys::Date::now(),
+                source.1.meta.playback_rate,
+                playing,
+            );
+
+            if rate != self.audio_element.playback_rate() {
+                self.audio_element.set_playback_rate(rate);
+            }
+        }
+    }
+
+    /// Loads the chapters of a new source.
+    /// Stored chapters are used, if available.
+    /// Otherwise, they are read from the ID3 tag of the enclosure or, if it has none, from the chapters file linked by the item.
+    fn load_chapters(&mut self, item: &Item, data: Option<Blob>) {
+        self.chapters = None;
+        self.send_response(Response::Chapters(Vec::new()));
+        self.chapter_source = Some((item.clone(), data));
+        self.repo.send(repo::Request::GetChapters(item.get_id()));
+    }
+
+    /// Continues loading the chapters, if the stored ones are not available.
+    fn read_chapters(&mut self, item_id: Uuid) {
+        match &self.chapter_source {
+            Some((item, Some(data))) if item.get_id() == item_id => {
+                let data = data.clone();
+
+                self.link.send_future(async move {
+                    Message::ChaptersRead(item_id, read_id3_chapters(data).await)
+                });
+            }
+            _ => self.fetch_chapters(item_id),
+        }
+    }
+
+    fn fetch_chapters(&mut self, item_id: Uuid) {
+        if let Some((item, _)) = &self.chapter_source {
+            if item.get_id() == item_id {
+                match utils::announced_chapters_url(item) {
+                    Some(url) => self.fetcher.send(fetcher::Request::FetchText(item_id, url)),
+                    None => self.chapter_source = None,
+                }
+            }
+        }
+    }
+
+    /// Publishes the chapters of the current source.
+    fn set_chapters(&mut self, item_id: Uuid, chapters: Vec<Chapter>) {
+        self.chapter_source = None;
+        self.send_response(Response::Chapters(chapters.clone()));
+        self.chapters = Some((item_id, chapters));
+    }
+
+    /// Stores and publishes the chapters read for the current source.
+    fn store_chapters(&mut self, item_id: Uuid, chapters: Vec<Chapter>) {
+        self.repo.send(repo::Request::UpdateChapters(ItemChapters {
+            id: item_id,
+            chapters: chapters.clone(),
+        }));
+        self.set_chapters(item_id, chapters);
+    }
+
+    fn channel_volume(&self) -> Option<f64> {
+        self.source.as_ref().map(|source| source.1.meta.volume)
+    }
+
+    /// Real time (in seconds) until the end of the current episode.
+    fn episode_remaining(&self) -> f64 {
+        let duration = self.audio_element.duration();
+
+        match duration.is_finite() {
+            true => {
+                (duration - self.audio_element.current_time()).max(0.0)
+                    / self.audio_element.playback_rate()
+            }
+            false => f64::INFINITY,
+        }
+    }
+
+    /// Starts, replaces or cancels (`None`) the sleep timer; the timer is checked every second while it is set.
+    fn set_sleep_timer(&mut self, mode: Option<SleepTimerMode>) -> Result<(), JsError> {
+        let window = web_sys::window().ok_or("could not obtain window")?;
+
+        match mode {
+            Some(mode) => {
+                self.sleep_timer = Some(SleepTimer::new(mode, js_sys::Date::now()));
+
+                if self.sleep_timer_interval.is_none() {
+                    self.sleep_timer_interval =
+                        Some(window.set_interval_with_callback_and_timeout_and_arguments(
+                            self.sleep_timer_closure.as_ref().unchecked_ref(),
+                            1_000,
+                            &js_sys::Array::new(),
+                        )?);
+                }
+            }
+            None => {
+                self.sleep_timer = None;
+
+                if let Some(handle) = self.sleep_timer_interval.take() {
+                    window.clear_interval_with_handle(handle);
+                }
+            }
+        }
+
+        self.send_sleep_timer();
+        Ok(())
+    }
+
+    fn send_sleep_timer(&self) {
+        let state = self
+            .sleep_timer
+            .as_ref()
+            .map(|timer| (timer.mode(), timer.remaining(self.episode_remaining())));
+
+        self.send_response(Response::SleepTimer(state));
+    }
+
+    /// Counts down the sleep timer, fades out the volume towards its end and stops the playback when it expires.
+    /// The end of the episode is handled by the end task.
+    fn sleep_timer_tick(&mut self) -> Result<(), JsError> {
+        let episode_remaining = self.episode_remaining();
+        let playing = !self.audio_element.paused();
+
+        if let Some(timer) = &mut self.sleep_timer {
+            timer.tick(js_sys::Date::now(), playing);
+
+            let expired = matches!(timer.mode(), SleepTimerMode::Duration(_))
+                && timer.remaining(episode_remaining) <= 0.0;
+            let fade = timer.fade(episode_remaining);
+
+            if expired {
+                self.tasks.insert(0, Task::Pause(PauseTask::new()));
+                return self.set_sleep_timer(None);
+            }
+            if let Some(volume) = self.channel_volume() {
+                self.audio_element.set_volume(volume * fade);
+            }
+        }
+
+        self.send_sleep_timer();
+        Ok(())
+    }
+
+    /// Counts the current source as played, once the completion threshold is reached; the position is kept until the end.
+    fn check_completion(&mut self) {
+        let duration = self.audio_element.duration();
+        let current_time = self.audio_element.current_time();
+
+        if self.marked_played || !duration.is_finite() {
+            return;
+        }
+
+        if let Some(source) = &mut self.source {
+            if self
+                .config
+                .completion_threshold
+                .is_reached(current_time, duration)
+            {
+                source.0.increment_play_count();
+                source.0.set_playback_time(Some(current_time));
+                self.repo.send(repo::Request::UpdateItem(source.0.clone()));
+                self.marked_played = true;
+            }
+        }
+    }
+
+    /// Whether the playback reached the outro of the channel of the current source.
+    fn outro_reached(&self) -> bool {
+        let intro_outro = match &self.source {
+            Some(source) => self.config.intro_outro(&source.1.val.id),
+            None => return false,
+        };
+
+        !self.audio_element.paused()
+            && intro_outro.outro_reached(
+                self.audio_element.current_time(),
+                self.audio_element.duration(),
+            )
+            && !self.tasks.iter().any(|task| matches!(task, Task::End(_)))
+    }
+
+    /// Skip length of the channel of the current source (forward or backward).
+    fn skip_interval(&self, forward: bool) -> Option<f64> {
+        let source = self.source.as_ref()?;
+        let intervals = self.config.skip_intervals(&source.1.val.id);
+
+        Some(match forward {
+            true => intervals.forward,
+            false => intervals.backward,
+        })
+    }
+
+    /// Event listeners of the audio element of the current source; the time update listener is only added while playing.
+    fn audio_listeners(&self) -> [(&'static str, &Closure<dyn Fn(web_sys::Event)>); 11] {
+        [
+            ("play", &self.on_play_closure),
+            ("pause", &self.on_pause_closure),
+            ("ended", &self.on_end_closure),
+            ("waiting", &self.on_waiting_closure),
+            ("canplay", &self.on_canplay_closure),
+            // "playing" follows "waiting" when playback resumes after buffering
+            ("playing", &self.on_canplay_closure),
+            ("error", &self.on_error_closure),
+            ("stalled", &self.on_stalled_closure),
+            // completes seeking, also while paused
+            ("seeked", &self.on_seeked_closure),
+            ("durationchange", &self.on_durationchange_closure),
+            ("loadedmetadata", &self.on_loadedmetadata_closure),
+        ]
+    }
+
+    /// Moves the event listeners to the given audio element and makes it the one of the current source; returns the previous element.
+    fn replace_audio_element(
+        &mut self,
+        audio_element: HtmlAudioElement,
+    ) -> Result<HtmlAudioElement, JsError> {
+        for (event, closure) in self.audio_listeners() {
+            self.audio_element
+                .remove_event_listener_with_callback(event, closure.as_ref().unchecked_ref())?;
+            audio_element
+                .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())?;
+        }
+        self.audio_element.remove_event_listener_with_callback(
+            "timeupdate",
+            self.on_timeupdate_closure.as_ref().unchecked_ref(),
+        )?;
+
+        Ok(std::mem::replace(&mut self.audio_element, audio_element))
+    }
+
+    /// Whether the playback stops at the end of the current source (instead of continuing with the next queued item).
+    fn stops_at_end(&self) -> bool {
+        matches!(
+            self.sleep_timer.as_ref().map(|timer| timer.mode()),
+            Some(SleepTimerMode::EndOfEpisode)
+        )
+    }
+
+    /// Real time (in seconds) until the current source ends, i.e., until its end or the outro of its channel.
+    fn transition_remaining(&self) -> f64 {
+        let outro = match &self.source {
+            Some(source) => self.config.intro_outro(&source.1.val.id).outro,
+            None => 0.0,
+        };
+
+        (self.episode_remaining() - outro / self.audio_element.playback_rate()).max(0.0)
+    }
+
+    /// First queued item other than the current source (i.e., the one the queue advances to).
+    fn upcoming(&self) -> Option<&Item> {
+        let source_id = self.source.as_ref().map(|source| source.0.get_id());
+
+        self.queued
+            .iter()
+            .find(|item| Some(item.get_id()) != source_id)
+    }
+
+    /// Preloads the upcoming item into a second audio element, once the transition is near.
+    fn preload_next(&mut self) -> Result<(), JsError> {
+        if self.next_source.is_some()
+            || self.source.is_none()
+            || self.audio_element.paused()
+            || self.stops_at_end()
+            || self.transition_remaining() > PRELOAD_LEAD + self.config.crossfade
+        {
+            return Ok(());
+        }
+
+        let item = match self.upcoming() {
+            // enclosures being downloaded are played progressively once the current source ended
+            Some(item) if !matches!(item.get_download_status(), DownloadStatus::InProgress) => {
+                item.clone()
+            }
+            _ => return Ok(()),
+        };
+        let start = item
+            .get_playback_time()
+            .unwrap_or_else(|| self.config.intro_outro(item.get_channel_id()).intro);
+        let mut next_source = NextSource::new(item.clone(), start)?;
+
+        match SourceMode::for_item(&item) {
+            SourceMode::Local => self.repo.send(repo::Request::GetEnclosure(item.get_id())),
+            SourceMode::Stream => next_source.stream(),
+        }
+
+        self.repo
+            .send(repo::Request::GetChannel(*item.get_channel_id()));
+        self.next_source = Some(next_source);
+        Ok(())
+    }
+
+    /// Discards the preloaded item, if it is no longer the upcoming one (e.g., after the queue changed).
+    fn check_next_source(&mut self) -> Result<(), JsError> {
+        let upcoming_id = self.upcoming().map(|item| item.get_id());
+
+        match self.next_source.take() {
+            Some(next_source) if Some(next_source.item.get_id()) != upcoming_id => {
+                next_source.release()
+            }
+            next_source => {
+                self.next_source = next_source;
+                Ok(())
+            }
+        }
+    }
+
+    /// Discards the preloaded item.
+    fn release_next_source(&mut self) -> Result<(), JsError> {
+        match self.next_source.take() {
+            Some(next_source) => next_source.release(),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the crossfade into the preloaded item is due.
+    fn crossfade_reached(&self) -> bool {
+        self.config.crossfade > 0.0
+            && !self.audio_element.paused()
+            && !self.stops_at_end()
+            && matches!(&self.next_source, Some(next_source) if next_source.is_ready())
+            && self.transition_remaining() <= self.config.crossfade
+            && !self.tasks.iter().any(|task| matches!(task, Task::End(_)))
+    }
+
+    /// Continues with the next queued item after the current source ended.
+    /// A preloaded item takes over right away; otherwise, the item returned by the queue is set as source.
+    fn advance(&mut self, finished_id: Uuid) -> Result<(), JsError> {
+        match self.next_source.take() {
+            Some(next_source) if next_source.is_usable() => self.switch_to(next_source)?,
+            Some(next_source) => next_source.release()?,
+            None => {}
+        }
+
+        // the queue responds with the preloaded item, which is ignored, as it is the source already
+        self.queue.send(queue::Request::Advance(finished_id));
+        Ok(())
+    }
+
+    /// Makes the preloaded item the current source and starts playing it.
+    /// If the previous source is still playing (i.e., the crossfade started before its end), it fades out while the next one fades in.
+    fn switch_to(&mut self, next_source: NextSource) -> Result<(), JsError> {
+        let NextSource {
+            mut item,
+            channel,
+            audio_element,
+            object_url,
+            data,
+            ..
+        } = next_source;
+        let channel = channel.ok_or("channel not set")?;
+        let crossfade = self.config.crossfade > 0.0
+            && !self.audio_element.paused()
+            && !self.audio_element.ended();
+
+        self.finish_session();
+        self.finish_crossfade()?;
+
+        if let Some(source) = self.source.take() {
+            self.repo.send(repo::Request::Unsubscribe(repo::Query::Item(
+                source.0.get_id(),
+            )));
+            self.repo
+                .send(repo::Request::Unsubscribe(repo::Query::Channel(
+                    source.1.val.id,
+                )));
+        }
+        if data.is_none() {
+            self.download_streamed(&mut item);
+        }
+
+        let previous_element = self.replace_audio_element(audio_element)?;
+        let previous_url = std::mem::replace(&mut self.object_url, object_url);
+        // the audio chain is bound to the previous element; a new one is created for the next element, if needed
+        let previous_chain = self.audio_chain.take();
+
+        match crossfade {
+            true => {
+                let window = web_sys::window().ok_or("could not obtain window")?;
+
+                self.crossfade = Some(Crossfade::new(
+                    previous_element,
+                    previous_url,
+                    previous_chain,
+                    js_sys::Date::now(),
+                    self.config.crossfade,
+                ));
+                self.crossfade_interval =
+                    Some(window.set_interval_with_callback_and_timeout_and_arguments(
+                        self.crossfade_closure.as_ref().unchecked_ref(),
+                        100,
+                        &js_sys::Array::new(),
+                    )?);
+            }
+            false => {
+                transition::release_element(&previous_element, previous_url.as_deref())?;
+
+                if let Some(audio_chain) = previous_chain {
+                    audio_chain.close()?;
+                }
+            }
+        }
+
+        self.progressive = None;
+        self.failure_reported = false;
+        self.marked_played = false;
+        self.paused_at = None;
+        self.source = Some((item.clone(), channel.clone()));
+        self.save_state();
+        self.load_chapters(&item, data);
+        self.repo
+            .send(repo::Request::Subscribe(repo::Query::Item(item.get_id())));
+        self.repo
+            .send(repo::Request::Subscribe(repo::Query::Channel(
+                channel.val.id,
+            )));
+        self.send_response(Response::SourceSet(
+            item,
+            channel,
+            self.audio_element.duration(),
+        ));
+        self.play();
+
+        if crossfade {
+            self.audio_element.set_volume(0.0);
+        }
+
+        Ok(())
+    }
+
+    /// Fades the previous source out and the current one in.
+    fn crossfade_tick(&mut self) -> Result<(), JsError> {
+        let progress = match &self.crossfade {
+            Some(crossfade) => crossfade.tick(js_sys::Date::now()),
+            None => return Ok(()),
+        };
+
+        if let Some(volume) = self.channel_volume() {
+            self.audio_element.set_volume(volume * progress);
+        }
+        if progress >= 1.0 {
+            self.finish_crossfade()?;
+        }
+
+        Ok(())
+    }
+
+    /// Stops the previous source of a crossfade in progress.
+    fn finish_crossfade(&mut self) -> Result<(), JsError> {
+        if let Some(handle) = self.crossfade_interval.take() {
+            web_sys::window()
+                .ok_or("could not obtain window")?
+                .clear_interval_with_handle(handle);
+        }
+
+        match self.crossfade.take() {
+            Some(crossfade) => crossfade.release(),
+            None => Ok(()),
+        }
+    }
+
+    /// Requests the download of a streamed item, if streamed episodes are to be downloaded.
+    fn download_streamed(&mut self, item: &mut Item) {
+        if self.config.download_streamed_episodes
+            && matches!(
+                item.get_download_status(),
+                DownloadStatus::NotRequested | DownloadStatus::Error
+            )
+        {
+            item.set_download_status(DownloadStatus::Pending);
+            self.repo.send(repo::Request::UpdateItem(item.clone()));
+        }
+    }
+
     fn process_update(&mut self, msg: Message) -> Result<(), JsError> {
         match msg {
             Message::StartedPlaying(res) => {
@@ -106,41 +1031,223 @@ impl Player {
                 }
             }
             Message::RepoMessage(msg) => match msg {
-                repo::Response::Enclosure(array_buffer) => {
-                    if let Some(mut task) = self.tasks.last_mut() {
-                        match &mut task {
-                            Task::SetSource(task) => task.set_data(array_buffer),
-                            _ => {}
+                repo::Response::Enclosure(item_id, blob) => {
+                    match (self.tasks.last_mut(), &mut self.progressive) {
+                        // the rest of a completed progressive source
+                        (_, Some(progressive))
+                            if progressive.item_id == item_id && progressive.awaits_enclosure() =>
+                        {
+                            let rest = blob.slice_with_f64(progressive.read_enclosure())?;
+
+                            progressive.pending_requests =
+                                progressive.pending_requests.saturating_sub(1);
+                            progressive.pending_reads += 1;
+                            self.link.send_future(async move {
+                                Message::EnclosureRestRead(
+                                    item_id,
+                                    JsFuture::from(rest.array_buffer()).await,
+                                )
+                            });
+                        }
+                        (Some(Task::SetSource(task)), _) if task.get_item().get_id() == item_id => {
+                            task.set_data(blob)
+                        }
+                        _ => {
+                            if let Some(next_source) = &mut self.next_source {
+                                if next_source.item.get_id() == item_id {
+                                    next_source.set_data(blob)?;
+                                }
+                            }
                         }
                     }
                 }
-                repo::Response::UpdatedItem(item) => {
-                    if let Some(source) = &self.source {
-                        if source.0.get_id() == item.get_id() {
-                            self.source = Some((item, source.1.clone()));
+                repo::Response::PlayerConfig(config) => {
+                    self.config = config.unwrap_or_default();
+
+                    if !self.audio_element.paused() {
+                        self.apply_audio_processing()?;
+                    }
+                }
+                repo::Response::Channel(channel) => {
+                    if let Some(next_source) = &mut self.next_source {
+                        if next_source.channel.is_none()
+                            && next_source.item.get_channel_id() == &channel.val.id
+                        {
+                            next_source.set_channel(channel.clone());
+                        }
+                    }
+                    if let Some(Task::SetSource(task)) = self.tasks.last_mut() {
+                        if task.get_item().get_channel_id() == &channel.val.id {
+                            task.set_channel(channel);
                         }
                     }
                 }
-                repo::Response::UpdatedChannel(channel) => match &self.source {
-                    Some(source) => {
-                        if source.1.val.id == channel.val.id {
-                            self.audio_element
-                                .set_playback_rate(channel.meta.playback_rate);
-                            self.audio_element.set_volume(channel.meta.volume);
-                            self.source = Some((source.0.clone(), channel));
+                repo::Response::Chapters(item_id, chapters) => {
+                    let pending =
+                        matches!(&self.chapter_source, Some((item, _)) if item.get_id() == item_id);
+
+                    if pending {
+                        match chapters {
+                            Some(chapters) => self.set_chapters(item_id, chapters),
+                            None => self.read_chapters(item_id),
                         }
                     }
-                    None => {
-                        if let Some(mut task) = self.tasks.last_mut() {
-                            match &mut task {
-                                Task::SetSource(task) => task.set_channel(channel),
-                                _ => {}
+                }
+                repo::Response::EnclosureChunks(item_id, chunks) => {
+                    if let Some(progressive) = &mut self.progressive {
+                        if progressive.item_id == item_id {
+                            progressive.pending_requests =
+                                progressive.pending_requests.saturating_sub(1);
+
+                            for (index, chunk) in chunks.into_iter().enumerate() {
+                                progressive.pending_reads += 1;
+                                self.link.send_future(async move {
+                                    Message::ChunkRead(
+                                        item_id,
+                                        index as u32,
+                                        JsFuture::from(chunk.array_buffer()).await,
+                                    )
+                                });
                             }
                         }
                     }
-                },
+
+                    // without chunks to read, the stream may be complete
+                    self.append_chunks()?;
+                }
+                repo::Response::PlayerState(state) => {
+                    if self.restoring {
+                        match state {
+                            Some(state) if self.source.is_none() => {
+                                self.repo.send(repo::Request::Subscribe(repo::Query::Item(
+                                    state.item_id,
+                                )));
+                                self.restored_state = Some(state);
+                            }
+                            _ => self.restoring = false,
+                        }
+                    }
+                }
+                repo::Response::ItemsChanged(repo::Query::Item(item_id), diffs) => {
+                    if let Some(state) = self.restored_state.take() {
+                        if state.item_id == item_id {
+                            // the subscription only served to load the item
+                            self.repo
+                                .send(repo::Request::Unsubscribe(repo::Query::Item(item_id)));
+                            self.restoring = false;
+
+                            if let Some(repo::Diff::Added(item) | repo::Diff::Updated(item)) =
+                                diffs.first()
+                            {
+                                self.restore_state(item.clone(), state);
+                            }
+
+                            return Ok(());
+                        }
+
+                        self.restored_state = Some(state);
+                    }
+
+                    for diff in diffs {
+                        if let repo::Diff::Added(item) | repo::Diff::Updated(item) = diff {
+                            let download_completed = match &mut self.progressive {
+                                Some(progressive)
+                                    if progressive.item_id == item.get_id()
+                                        && !progressive.download_complete
+                                        && matches!(
+                                            item.get_download_status(),
+                                            DownloadStatus::Ok
+                                        ) =>
+                                {
+                                    progressive.download_complete = true;
+                                    true
+                                }
+                                _ => false,
+                            };
+
+                            if download_completed {
+                                let restarted = match self.tasks.last_mut() {
+                                    Some(Task::SetSource(task)) => {
+                                        task.download_completed(item.clone())
+                                    }
+                                    _ => false,
+                                };
+
+                                // the chunks are combined into the enclosure once the download is complete (and those downloaded by the leader tab are never received)
+                                if let (false, Some(progressive)) =
+                                    (restarted, &mut self.progressive)
+                                {
+                                    progressive.pending_requests += 1;
+                                    self.repo.send(repo::Request::GetEnclosure(item.get_id()));
+                                }
+                                self.append_chunks()?;
+                            }
+                            if let Some(source) = &self.source {
+                                if source.0.get_id() == item.get_id() {
+                                    self.source = Some((item, source.1.clone()));
+                                }
+                            }
+                        }
+                    }
+                }
+                repo::Response::ChannelsChanged(repo::Query::Channel(_), diffs) => {
+                    for diff in diffs {
+                        if let repo::Diff::Added(channel) | repo::Diff::Updated(channel) = diff {
+                            if let Some(source) = &self.source {
+                                if source.1.val.id == channel.val.id {
+                                    self.audio_element
+                                        .set_playback_rate(channel.meta.playback_rate);
+                                    self.audio_element.set_volume(channel.meta.volume);
+                                    self.source = Some((source.0.clone(), channel));
+                                }
+                            }
+                        }
+                    }
+                }
                 _ => {}
             },
+            Message::FetcherMessage(fetcher::Response::DownloadChunk(item_id, index, data)) => {
+                if let Some(progressive) = &mut self.progressive {
+                    if progressive.item_id == item_id {
+                        progressive.add_chunk(index, data);
+                        self.append_chunks()?;
+                    }
+                }
+            }
+            Message::FetcherMessage(fetcher::Response::Text(item_id, res)) => {
+                if matches!(&self.chapter_source, Some((item, _)) if item.get_id() == item_id) {
+                    self.chapter_source = None;
+
+                    let chapters = utils::parse_json_chapters(&res?).map_err(|e| {
+                        JsError::from_str(&format!("could not parse chapters: {}", e))
+                    })?;
+
+                    self.store_chapters(item_id, chapters);
+                }
+            }
+            Message::FetcherMessage(_) => {}
+            Message::ChaptersRead(item_id, res) => match res? {
+                chapters if chapters.is_empty() => self.fetch_chapters(item_id),
+                chapters => self.store_chapters(item_id, chapters),
+            },
+            Message::ChunkRead(item_id, index, res) => {
+                if let Some(progressive) = &mut self.progressive {
+                    if progressive.item_id == item_id {
+                        progressive.pending_reads = progressive.pending_reads.saturating_sub(1);
+                        progressive.add_chunk(index, Uint8Array::new(&res?));
+                        self.append_chunks()?;
+                    }
+                }
+            }
+            Message::EnclosureRestRead(item_id, res) => {
+                if let Some(progressive) = &mut self.progressive {
+                    if progressive.item_id == item_id {
+                        progressive.pending_reads = progressive.pending_reads.saturating_sub(1);
+                        progressive.add_rest(Uint8Array::new(&res?));
+                        self.append_chunks()?;
+                    }
+                }
+            }
             Message::SourceOpened(_e) => {
                 if let Some(mut task) = self.tasks.last_mut() {
                     match &mut task {
@@ -149,21 +1256,110 @@ impl Player {
                     }
                 }
             }
-            Message::SourceBufferUpdate(_e) => {
-                self.media_source.end_of_stream()?;
+            Message::SourceBufferUpdateEnd(_e) => self.append_chunks()?,
+            Message::SourceBufferError(_e) => self.playback_failed(
+                "the downloaded data could not be appended (it may be corrupt)",
+                true,
+            ),
+            Message::MediaSessionAction(action) => match action {
+                Action::Play => self.play(),
+                Action::Pause => self.tasks.insert(0, Task::Pause(PauseTask::new())),
+                Action::SeekBackward(offset) => {
+                    if let Some(offset) = offset.or_else(|| self.skip_interval(false)) {
+                        self.skip(-offset);
+                    }
+                }
+                Action::SeekForward(offset) => {
+                    if let Some(offset) = offset.or_else(|| self.skip_interval(true)) {
+                        self.skip(offset);
+                    }
+                }
+                Action::SeekTo(time) => self.seek(time),
+                Action::NextTrack => {
+                    if let Some(source) = &self.source {
+                        self.save_position();
+                        self.queue.send(queue::Request::Advance(source.0.get_id()));
+                    }
+                }
+            },
+            Message::QueueMessage(queue::Response::Queue(items)) => {
+                self.queued = items;
+                self.check_next_source()?;
+            }
+            Message::QueueMessage(queue::Response::Next(Some(item))) => {
+                // the preloaded item already took over
+                if matches!(&self.source, Some(source) if source.0.get_id() == item.get_id()) {
+                    return Ok(());
+                }
+
+                let mode = SourceMode::for_item(&item);
+
+                self.tasks.insert(0, Task::Pause(PauseTask::new()));
+                self.tasks
+                    .insert(0, Task::SetSource(SetSourceTask::new(item, mode)));
+                self.play();
+            }
+            Message::QueueMessage(queue::Response::Next(None)) => {}
+            // only one tab plays at a time
+            Message::TabsMessage(tabs::Response::Received(TabMessage::Playing)) => {
+                if !self.audio_element.paused() {
+                    self.tasks.insert(0, Task::Pause(PauseTask::new()));
+                }
+            }
+            Message::TabsMessage(_) => {}
+            Message::SleepTimerTick(_e) => self.sleep_timer_tick()?,
+            Message::SilenceTick(_e) => self.silence_tick(),
+            Message::CrossfadeTick(_e) => self.crossfade_tick()?,
+            Message::OnDurationChange(_e) => {
+                self.send_response(Response::DurationChange(self.audio_element.duration()));
+            }
+            Message::OnLoadedMetadata(_e) => {
                 if let Some(mut task) = self.tasks.last_mut() {
                     match &mut task {
-                        Task::SetSource(task) => task.buffer_updated(),
+                        Task::SetSource(task) => task.metadata_loaded(),
                         _ => {}
                     }
                 }
             }
+            Message::OnWaiting(_e) => {
+                if !self.buffering {
+                    self.buffering = true;
+                    self.send_response(Response::Buffering(true));
+                }
+            }
+            Message::OnCanPlay(_e) => {
+                if self.buffering {
+                    self.buffering = false;
+                    self.send_response(Response::Buffering(false));
+                }
+            }
+            Message::OnError(_e) => {
+                let (reason, corrupt) = self.media_error();
+
+                self.playback_failed(&reason, corrupt);
+            }
+            Message::OnStalled(_e) => {
+                // local data does not stall; the browser keeps trying to load streams
+                if self.object_url.is_none() && !self.audio_element.src().is_empty() {
+                    self.send_response(Response::Error(JsError::from_str(
+                        "the playback stalled: no data is being received from the server",
+                    )));
+                }
+            }
             Message::OnEnd(_e) => {
+                self.finish_session();
                 self.tasks.insert(0, Task::End(EndTask::new()));
             }
             Message::OnPause(_e) => {
                 let mut task_required = false;
 
+                self.finish_session();
+                self.finish_crossfade()?;
+
+                if !self.audio_element.ended() {
+                    self.paused_at = Some(js_sys::Date::now());
+                }
+
                 if let Some(mut task) = self.tasks.last_mut() {
                     match &mut task {
                         Task::Pause(task) => {
@@ -185,6 +1381,10 @@ impl Player {
             Message::OnPlay(_e) => {
                 let mut task_required = false;
 
+                self.save_state();
+                self.tabs
+                    .send(tabs::Request::Broadcast(TabMessage::Playing));
+
                 if let Some(mut task) = self.tasks.last_mut() {
                     match &mut task {
                         Task::Play(task) => {
@@ -203,25 +1403,60 @@ impl Player {
                     self.tasks.insert(0, Task::Play(task));
                 }
             }
+            Message::OnSeeked(_e) => {
+                if let Some(Task::SetCurrentTime(task)) = self.tasks.last_mut() {
+                    task.time_set();
+                }
+            }
             Message::OnTimeupdate(_e) => {
-                let mut task_required = false;
+                // time updates are also sent while seeking
+                let seeked = matches!(self.tasks.last(), Some(Task::SetCurrentTime(_)));
 
-                if let Some(mut task) = self.tasks.last_mut() {
-                    match &mut task {
-                        Task::SetCurrentTime(task) => {
-                            task.time_set();
-                        }
-                        _ => task_required = true,
-                    }
-                } else {
-                    task_required = true;
+                // saving the position on every time update would write the item several times a second
+                if !seeked
+                    && (js_sys::Date::now() - self.last_position_save
+                        >= self.config.position_save_interval * 1000.0)
+                {
+                    self.save_position();
                 }
 
-                if task_required {
-                    let mut task = SetCurrentTimeTask::new(self.audio_element.current_time());
+                if !seeked {
+                    self.start_session();
+                    self.check_completion();
+                }
 
-                    task.time_set();
-                    self.tasks.insert(0, Task::SetCurrentTime(task));
+                self.send_response(Response::TimeUpdate(self.audio_element.current_time()));
+
+                if !seeked {
+                    // the current source ends without pausing, while the next one fades in
+                    if self.crossfade_reached() {
+                        self.finish_session();
+                        self.tasks.insert(0, Task::End(EndTask::new()));
+                    }
+                    // the outro is skipped by ending the episode early
+                    if self.outro_reached() {
+                        self.tasks.insert(0, Task::Pause(PauseTask::new()));
+                        self.tasks.insert(0, Task::End(EndTask::new()));
+                    }
+
+                    self.preload_next()?;
+                }
+            }
+            Message::OnVisibilityChange(_e) => {
+                let hidden = web_sys::window()
+                    .and_then(|w| w.document())
+                    .ok_or("could not obtain document")?
+                    .hidden();
+
+                if hidden && !self.audio_element.paused() {
+                    self.save_position();
+                    self.finish_session();
+                }
+            }
+            Message::OnPageHide(_e) => {
+                if !self.audio_element.paused() {
+                    self.save_position();
+                    self.finish_session();
                 }
             }
         }
@@ -238,14 +1473,18 @@ impl Agent for Player {
 
     fn create(link: AgentLink<Self>) -> Self {
         let callback_repo = link.callback(Message::RepoMessage);
-        let callback_mediasource_opened = link.callback(move |e| Message::SourceOpened(e));
+        let callback_mediasource_opened = link.callback(Message::SourceOpened);
         let mediasource_opened_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
             callback_mediasource_opened.emit(event)
         }) as Box<dyn Fn(_)>);
-        let callback_sourcebuffer_update = link.callback(move |e| Message::SourceBufferUpdate(e));
+        let callback_sourcebuffer_update = link.callback(Message::SourceBufferUpdateEnd);
         let sourcebuffer_update_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
             callback_sourcebuffer_update.emit(event)
         }) as Box<dyn Fn(_)>);
+        let callback_sourcebuffer_error = link.callback(Message::SourceBufferError);
+        let sourcebuffer_error_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            callback_sourcebuffer_error.emit(event)
+        }) as Box<dyn Fn(_)>);
 
         // set audio element callbacks
         let on_play_callback = link.callback(move |e| Message::OnPlay(e));
@@ -266,42 +1505,178 @@ impl Agent for Player {
                 Box::new(move |event: web_sys::Event| on_timeupdate_callback.emit(event))
                     as Box<dyn Fn(_)>,
             );
+        let on_seeked_callback = link.callback(Message::OnSeeked);
+        let on_seeked_closure =
+            Closure::wrap(
+                Box::new(move |event: web_sys::Event| on_seeked_callback.emit(event))
+                    as Box<dyn Fn(_)>,
+            );
         let on_end_callback = link.callback(move |e| Message::OnEnd(e));
         let on_end_closure =
             Closure::wrap(
                 Box::new(move |event: web_sys::Event| on_end_callback.emit(event))
                     as Box<dyn Fn(_)>,
             );
+        let on_loadedmetadata_callback = link.callback(Message::OnLoadedMetadata);
+        let on_loadedmetadata_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            on_loadedmetadata_callback.emit(event)
+        }) as Box<dyn Fn(_)>);
+        let on_durationchange_callback = link.callback(Message::OnDurationChange);
+        let on_durationchange_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            on_durationchange_callback.emit(event)
+        }) as Box<dyn Fn(_)>);
+        let on_waiting_callback = link.callback(Message::OnWaiting);
+        let on_waiting_closure =
+            Closure::wrap(
+                Box::new(move |event: web_sys::Event| on_waiting_callback.emit(event))
+                    as Box<dyn Fn(_)>,
+            );
+        let on_canplay_callback = link.callback(Message::OnCanPlay);
+        let on_canplay_closure =
+            Closure::wrap(
+                Box::new(move |event: web_sys::Event| on_canplay_callback.emit(event))
+                    as Box<dyn Fn(_)>,
+            );
+        let on_error_callback = link.callback(Message::OnError);
+        let on_error_closure =
+            Closure::wrap(
+                Box::new(move |event: web_sys::Event| on_error_callback.emit(event))
+                    as Box<dyn Fn(_)>,
+            );
+        let on_stalled_callback = link.callback(Message::OnStalled);
+        let on_stalled_closure =
+            Closure::wrap(
+                Box::new(move |event: web_sys::Event| on_stalled_callback.emit(event))
+                    as Box<dyn Fn(_)>,
+            );
+        let on_visibilitychange_callback = link.callback(Message::OnVisibilityChange);
+        let on_visibilitychange_closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            on_visibilitychange_callback.emit(event)
+        }) as Box<dyn Fn(_)>);
+        let on_pagehide_callback = link.callback(Message::OnPageHide);
+        let on_pagehide_closure =
+            Closure::wrap(
+                Box::new(move |event: web_sys::Event| on_pagehide_callback.emit(event))
+                    as Box<dyn Fn(_)>,
+            );
         let audio_element = web_sys::HtmlAudioElement::new().unwrap();
+        let window = web_sys::window().unwrap();
 
-        audio_element
-            .add_event_listener_with_callback("play", on_play_closure.as_ref().unchecked_ref())
+        window
+            .document()
+            .unwrap()
+            .add_event_listener_with_callback(
+                "visibilitychange",
+                on_visibilitychange_closure.as_ref().unchecked_ref(),
+            )
             .unwrap();
-        audio_element
-            .add_event_listener_with_callback("pause", on_pause_closure.as_ref().unchecked_ref())
-            .unwrap();
-        audio_element
-            .add_event_listener_with_callback("ended", on_end_closure.as_ref().unchecked_ref())
+        window
+            .add_event_listener_with_callback(
+                "pagehide",
+                on_pagehide_closure.as_ref().unchecked_ref(),
+            )
             .unwrap();
+
         // not sure how one could avoid the unwraps in the object creation; on option might be
         // to wrap the media source and audio element in options; however, this increases the
         // complexity and still does not provide the required functionality
-        Self {
+        let mut repo = repo::Repo::bridge(callback_repo);
+        let fetcher = fetcher::Fetcher::bridge(link.callback(Message::FetcherMessage));
+        let mut queue = queue::Queue::bridge(link.callback(Message::QueueMessage));
+        let callback_sleep_timer = link.callback(Message::SleepTimerTick);
+        let sleep_timer_closure =
+            Closure::wrap(
+                Box::new(move |event: web_sys::Event| callback_sleep_timer.emit(event))
+                    as Box<dyn Fn(_)>,
+            );
+        let callback_silence = link.callback(Message::SilenceTick);
+        let silence_closure =
+            Closure::wrap(
+                Box::new(move |event: web_sys::Event| callback_silence.emit(event))
+                    as Box<dyn Fn(_)>,
+            );
+        let tabs = tabs::Tabs::bridge(link.callback(Message::TabsMessage));
+        let callback_crossfade = link.callback(Message::CrossfadeTick);
+        let crossfade_closure =
+            Closure::wrap(
+                Box::new(move |event: web_sys::Event| callback_crossfade.emit(event))
+                    as Box<dyn Fn(_)>,
+            );
+        let mut notifier = notifier::Notifier::dispatcher();
+        let media_session = match MediaSession::new(link.callback(Message::MediaSessionAction)) {
+            Ok(media_session) => media_session,
+            Err(e) => {
+                notifier.send(notifier::Request::NotifyError(e));
+                MediaSession::unavailable()
+            }
+        };
+
+        repo.send(repo::Request::GetPlayerConf(None));
+        repo.send(repo::Request::GetPlayerState(None));
+        queue.send(queue::Request::GetQueue);
+
+        let player = Self {
             link,
             audio_element,
             subscribers: HashSet::new(),
-            repo: repo::Repo::bridge(callback_repo),
-            media_source: MediaSource::new().unwrap(),
+            repo,
+            object_url: None,
+            progressive: None,
             mediasource_opened_closure,
             sourcebuffer_update_closure,
-            notifier: notifier::Notifier::dispatcher(),
+            sourcebuffer_error_closure,
+            on_durationchange_closure,
+            fetcher,
+            queue,
+            tabs,
+            media_session,
+            sleep_timer: None,
+            chapters: None,
+            chapter_source: None,
+            sleep_timer_interval: None,
+            sleep_timer_closure,
+            audio_chain: None,
+            silence_interval: None,
+            silence_closure,
+            queued: Vec::new(),
+            next_source: None,
+            crossfade: None,
+            crossfade_interval: None,
+            crossfade_closure,
+            notifier,
             source: None,
             tasks: Vec::new(),
-            _on_pause_closure: on_pause_closure,
-            _on_play_closure: on_play_closure,
-            _on_end_closure: on_end_closure,
-            on_timeupdate_closure: on_timeupdate_closure,
+            on_pause_closure,
+            on_play_closure,
+            on_end_closure,
+            on_loadedmetadata_closure,
+            on_waiting_closure,
+            on_canplay_closure,
+            on_error_closure,
+            on_stalled_closure,
+            on_timeupdate_closure,
+            on_seeked_closure,
+            _on_visibilitychange_closure: on_visibilitychange_closure,
+            _on_pagehide_closure: on_pagehide_closure,
+            config: PlayerConfig::default(),
+            paused_at: None,
+            session: None,
+            marked_played: false,
+            failure_reported: false,
+            restoring: true,
+            restored_state: None,
+            last_position_save: 0.0,
+            buffering: false,
+        };
+
+        for (event, closure) in player.audio_listeners() {
+            player
+                .audio_element
+                .add_event_listener_with_callback(event, closure.as_ref().unchecked_ref())
+                .unwrap();
         }
+
+        player
     }
 
     fn update(&mut self, msg: Self::Message) {
@@ -315,16 +1690,86 @@ impl Agent for Player {
 
     fn handle_input(&mut self, msg: Self::Input, _handler_id: HandlerId) {
         match msg {
-            Request::SetSource(item) => {
+            Request::SetSource(item, mode, start) => {
+                let mut task = SetSourceTask::new(item, mode);
+
+                task.set_start(start);
                 self.tasks.insert(0, Task::Pause(PauseTask::new()));
-                self.tasks
-                    .insert(0, Task::SetSource(SetSourceTask::new(item)));
+                self.tasks.insert(0, Task::SetSource(task));
             }
-            Request::Play => self.tasks.insert(0, Task::Play(PlayTask::new())),
+            Request::Play => self.play(),
             Request::Pause => self.tasks.insert(0, Task::Pause(PauseTask::new())),
-            Request::SetCurrentTime(time) => self
-                .tasks
-                .insert(0, Task::SetCurrentTime(SetCurrentTimeTask::new(time))),
+            Request::TogglePlayback => match self.audio_element.paused() {
+                true => self.play(),
+                false => self.tasks.insert(0, Task::Pause(PauseTask::new())),
+            },
+            Request::AddBookmark(note) => {
+                if let Some(source) = &self.source {
+                    self.repo.send(repo::Request::UpdateBookmark(Bookmark {
+                        id: Uuid::new_v4(),
+                        item_id: source.0.get_id(),
+                        position: self.audio_element.current_time(),
+                        note,
+                        created_at: Local::now().into(),
+                    }));
+                }
+            }
+            Request::SetCurrentTime(time) => self.seek(time),
+            Request::SeekToFraction(fraction) => {
+                let duration = self.audio_element.duration();
+
+                if self.source.is_some() && duration.is_finite() {
+                    self.seek(duration * fraction.clamp(0.0, 1.0));
+                }
+            }
+            Request::SkipForward => {
+                if let Some(offset) = self.skip_interval(true) {
+                    self.skip(offset);
+                }
+            }
+            Request::SkipBackward => {
+                if let Some(offset) = self.skip_interval(false) {
+                    self.skip(-offset);
+                }
+            }
+            Request::SetSkipIntervals(intervals) => {
+                if let Some(source) = &self.source {
+                    let mut config = self.config.clone();
+
+                    match intervals {
+                        Some(intervals) => {
+                            config
+                                .channel_skip_intervals
+                                .insert(source.1.val.id, intervals);
+                        }
+                        None => {
+                            config.channel_skip_intervals.remove(&source.1.val.id);
+                        }
+                    }
+
+                    self.repo.send(repo::Request::GetPlayerConf(Some(config)));
+                }
+            }
+            Request::SetAudioProcessing(processing) => {
+                if let Some(source) = &self.source {
+                    let mut config = self.config.clone();
+
+                    config
+                        .channel_audio_processing
+                        .insert(source.1.val.id, processing);
+                    self.repo.send(repo::Request::GetPlayerConf(Some(config)));
+                }
+            }
+            Request::SetIntroOutro(intro_outro) => {
+                if let Some(source) = &self.source {
+                    let mut config = self.config.clone();
+
+                    config
+                        .channel_intro_outro
+                        .insert(source.1.val.id, intro_outro);
+                    self.repo.send(repo::Request::GetPlayerConf(Some(config)));
+                }
+            }
             Request::SetPlaybackRate(val) => {
                 if let Some(source) = &self.source {
                     let mut channel = source.1.clone();
@@ -333,6 +1778,16 @@ impl Agent for Player {
                     self.repo.send(repo::Request::UpdateChannel(channel));
                 }
             }
+            Request::ChangePlaybackRate(delta) => {
+                if let Some(source) = &self.source {
+                    let mut channel = source.1.clone();
+                    // avoid accumulating rounding errors (e.g., 1.2000000000000002)
+                    let rate = ((channel.meta.playback_rate + delta) * 10.0).round() / 10.0;
+
+                    channel.meta.playback_rate = rate.clamp(MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE);
+                    self.repo.send(repo::Request::UpdateChannel(channel));
+                }
+            }
             Request::SetVolume(val) => {
                 if let Some(source) = &self.source {
                     let mut channel = source.1.clone();
@@ -341,7 +1796,35 @@ impl Agent for Player {
                     self.repo.send(repo::Request::UpdateChannel(channel));
                 }
             }
-            Request::GetStatus => self.tasks.insert(0, Task::Status(StatusTask::new())),
+            Request::SetSleepTimer(mode) => {
+                if self.sleep_timer.is_some() {
+                    if let Some(volume) = self.channel_volume() {
+                        self.audio_element.set_volume(volume);
+                    }
+                }
+                if let Err(e) = self.set_sleep_timer(mode) {
+                    self.notifier.send(notifier::Request::NotifyError(e));
+                }
+            }
+            Request::ExtendSleepTimer(seconds) => {
+                let episode_remaining = self.episode_remaining();
+
+                if let Some(timer) = &mut self.sleep_timer {
+                    timer.extend(seconds, episode_remaining);
+
+                    if let Some(volume) = self.channel_volume() {
+                        self.audio_element.set_volume(volume);
+                    }
+                    self.send_sleep_timer();
+                }
+            }
+            Request::GetStatus => {
+                self.send_sleep_timer();
+                if let Some((_, chapters)) = &self.chapters {
+                    self.send_response(Response::Chapters(chapters.clone()));
+                }
+                self.tasks.insert(0, Task::Status(StatusTask::new()))
+            }
         }
         self.process_tasks();
     }
@@ -354,3 +1837,16 @@ impl Agent for Player {
         self.subscribers.remove(&id);
     }
 }
+
+/// Reads the chapters from the ID3 tag at the beginning of the enclosure.
+async fn read_id3_chapters(data: Blob) -> Result<Vec<Chapter>, JsError> {
+    let header =
+        utils::read_blob(&data.slice_with_i32_and_i32(0, utils::ID3_HEADER_LENGTH as i32)?).await?;
+    let length = match utils::id3_tag_length(&header) {
+        Some(length) => length,
+        None => return Ok(Vec::new()),
+    };
+    let tag = utils::read_blob(&data.slice_with_f64_and_f64(0.0, length as f64)?).await?;
+
+    Ok(utils::parse_id3_chapters(&tag))
+}
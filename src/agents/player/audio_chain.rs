@@ -0,0 +1,168 @@
+use crate::objects::{AudioProcessing, JsError};
+use web_sys::{
+    AnalyserNode, AudioContext, AudioNode, DynamicsCompressorNode, GainNode, HtmlAudioElement,
+    MediaElementAudioSourceNode,
+};
+
+/// Root mean square of the signal below which the audio is considered silent (about -45 dBFS).
+const SILENCE_THRESHOLD: f32 = 0.0056;
+/// Time (in ms) the audio has to be silent, before the playback is sped up.
+const SILENCE_MIN_DURATION: f64 = 250.0;
+/// Factor applied to the playback rate during silence.
+const SILENCE_SPEEDUP: f64 = 2.0;
+/// Maximum playback rate supported by the browsers.
+const MAX_PLAYBACK_RATE: f64 = 4.0;
+/// Gain applied after the compressor to level the loudness.
+const MAKEUP_GAIN: f32 = 1.5;
+
+/// Whether the root mean square of the samples is below the silence threshold.
+fn is_silence(samples: &[f32]) -> bool {
+    let power = samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32;
+
+    power.sqrt() < SILENCE_THRESHOLD
+}
+
+/// Playback rate for the given rate after the audio has been silent for the given time (in ms).
+fn silence_playback_rate(rate: f64, silent_for: f64) -> f64 {
+    match silent_for >= SILENCE_MIN_DURATION {
+        true => (rate * SILENCE_SPEEDUP).min(MAX_PLAYBACK_RATE),
+        false => rate,
+    }
+}
+
+/// # Audio Chain
+///
+/// Routes the audio element through the Web Audio API: the source is either connected to the output directly or through a compressor (normalization).
+/// An analyser taps the source to detect silence.
+///
+/// Once the audio element is connected to an audio context, it cannot be played without it anymore.
+/// The chain is therefore only created, when processing is enabled for the first time.
+pub struct AudioChain {
+    context: AudioContext,
+    source: MediaElementAudioSourceNode,
+    compressor: DynamicsCompressorNode,
+    _gain: GainNode,
+    analyser: AnalyserNode,
+    processing: AudioProcessing,
+    samples: Vec<f32>,
+    /// time (ms since the epoch) the current silence started
+    silent_since: Option<f64>,
+}
+
+impl AudioChain {
+    pub fn new(audio_element: &HtmlAudioElement) -> Result<Self, JsError> {
+        let context = AudioContext::new()?;
+        let source = context.create_media_element_source(audio_element)?;
+        let compressor = context.create_dynamics_compressor()?;
+        let gain = context.create_gain()?;
+        let analyser = context.create_analyser()?;
+
+        compressor.threshold().set_value(-24.0);
+        compressor.knee().set_value(30.0);
+        compressor.ratio().set_value(4.0);
+        compressor.attack().set_value(0.003);
+        compressor.release().set_value(0.25);
+        gain.gain().set_value(MAKEUP_GAIN);
+        analyser.set_fft_size(2048);
+        compressor.connect_with_audio_node(&gain)?;
+        gain.connect_with_audio_node(&context.destination())?;
+
+        let samples = vec![0.0; analyser.fft_size() as usize];
+        let chain = Self {
+            context,
+            source,
+            compressor,
+            _gain: gain,
+            analyser,
+            processing: AudioProcessing::default(),
+            samples,
+            silent_since: None,
+        };
+
+        chain.connect()?;
+        Ok(chain)
+    }
+
+    /// Switches the processing; the source is reconnected, if the normalization changes.
+    pub fn set_processing(&mut self, processing: AudioProcessing) -> Result<(), JsError> {
+        let reconnect = processing.normalize != self.processing.normalize;
+
+        self.processing = processing;
+
+        if !self.processing.skip_silence {
+            self.silent_since = None;
+        }
+
+        match reconnect {
+            true => self.connect(),
+            false => Ok(()),
+        }
+    }
+
+    /// Resumes the audio context; browsers suspend contexts created without user interaction.
+    pub fn resume(&self) -> Result<(), JsError> {
+        let _promise = self.context.resume()?;
+
+        Ok(())
+    }
+
+    /// Closes the audio context, once the audio element connected to it is discarded.
+    pub fn close(&self) -> Result<(), JsError> {
+        let _promise = self.context.close()?;
+
+        Ok(())
+    }
+
+    /// Playback rate for the given (channel) rate; raised while the audio is silent and skipping silence is enabled.
+    pub fn playback_rate(&mut self, now: f64, rate: f64, playing: bool) -> f64 {
+        if !self.processing.skip_silence || !playing || !self.is_silent() {
+            self.silent_since = None;
+            return rate;
+        }
+
+        let silent_since = *self.silent_since.get_or_insert(now);
+
+        silence_playback_rate(rate, now - silent_since)
+    }
+
+    fn is_silent(&mut self) -> bool {
+        self.analyser.get_float_time_domain_data(&mut self.samples);
+        is_silence(&self.samples)
+    }
+
+    fn connect(&self) -> Result<(), JsError> {
+        let destination = self.context.destination();
+        let output: &AudioNode = match self.processing.normalize {
+            true => &self.compressor,
+            false => &destination,
+        };
+
+        self.source.disconnect()?;
+        self.source.connect_with_audio_node(output)?;
+        self.source.connect_with_audio_node(&self.analyser)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn silence() {
+        assert!(is_silence(&[0.0; 128]));
+        assert!(is_silence(&[0.005, -0.005, 0.001, -0.001]));
+        assert!(!is_silence(&[0.1, -0.1, 0.05, -0.05]));
+        // a single peak raises the level of the whole block
+        assert!(!is_silence(&[0.0, 0.0, 0.0, 0.5]));
+    }
+
+    #[test]
+    fn playback_rate() {
+        assert_eq!(silence_playback_rate(1.0, 0.0), 1.0);
+        assert_eq!(silence_playback_rate(1.0, SILENCE_MIN_DURATION - 1.0), 1.0);
+        assert_eq!(silence_playback_rate(1.0, SILENCE_MIN_DURATION), 2.0);
+        assert_eq!(silence_playback_rate(1.5, 1000.0), 3.0);
+        assert_eq!(silence_playback_rate(3.0, 1000.0), MAX_PLAYBACK_RATE);
+    }
+}
@@ -0,0 +1,50 @@
+use web_sys::MediaError;
+
+/// Describes an error of the audio element by its code and message; returns whether the data is corrupt (as opposed to, e.g., a network error).
+pub fn describe(code: u16, message: &str) -> (String, bool) {
+    let (description, corrupt) = match code {
+        MediaError::MEDIA_ERR_ABORTED => ("loading the data was aborted", false),
+        MediaError::MEDIA_ERR_NETWORK => ("a network error occurred", false),
+        MediaError::MEDIA_ERR_DECODE => (
+            "the data could not be decoded (it may be truncated or corrupt)",
+            true,
+        ),
+        MediaError::MEDIA_ERR_SRC_NOT_SUPPORTED => (
+            "the data is not valid audio or its format is not supported",
+            true,
+        ),
+        _ => ("unknown error", false),
+    };
+
+    match message.is_empty() {
+        true => (description.to_string(), corrupt),
+        false => (format!("{} ({})", description, message), corrupt),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corrupt() {
+        assert!(describe(MediaError::MEDIA_ERR_DECODE, "").1);
+        assert!(describe(MediaError::MEDIA_ERR_SRC_NOT_SUPPORTED, "").1);
+        assert!(!describe(MediaError::MEDIA_ERR_ABORTED, "").1);
+        assert!(!describe(MediaError::MEDIA_ERR_NETWORK, "").1);
+        assert!(!describe(0, "").1);
+    }
+
+    #[test]
+    fn message() {
+        assert_eq!(
+            describe(MediaError::MEDIA_ERR_NETWORK, "").0,
+            "a network error occurred"
+        );
+        assert_eq!(
+            describe(MediaError::MEDIA_ERR_NETWORK, "connection reset").0,
+            "a network error occurred (connection reset)"
+        );
+        assert_eq!(describe(99, "").0, "unknown error");
+    }
+}
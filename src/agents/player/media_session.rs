@@ -0,0 +1,159 @@
+use crate::objects::{Item, JsError};
+use js_sys::{Array, Function, Object, Reflect};
+use podcast_player_common::Channel;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use yew::Callback;
+
+/// Actions triggered through the media session (e.g., on the lock screen or by a headset).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Play,
+    Pause,
+    /// offset in seconds, if provided by the browser
+    SeekBackward(Option<f64>),
+    /// offset in seconds, if provided by the browser
+    SeekForward(Option<f64>),
+    SeekTo(f64),
+    NextTrack,
+}
+
+/// # Media Session
+///
+/// Wrapper around `navigator.mediaSession`.
+/// The API is accessed dynamically, as it is not available in all browsers (and unstable in web-sys); without it, all methods do nothing.
+pub struct MediaSession {
+    session: Option<JsValue>,
+    _action_closures: Vec<Closure<dyn Fn(JsValue)>>,
+}
+
+const ACTIONS: [&str; 6] = [
+    "play",
+    "pause",
+    "seekbackward",
+    "seekforward",
+    "seekto",
+    "nexttrack",
+];
+
+impl MediaSession {
+    /// A media session doing nothing.
+    pub fn unavailable() -> Self {
+        Self {
+            session: None,
+            _action_closures: Vec::new(),
+        }
+    }
+
+    pub fn new(callback: Callback<Action>) -> Result<Self, JsError> {
+        let navigator = web_sys::window()
+            .ok_or("could not obtain window")?
+            .navigator();
+        let session = Reflect::get(&navigator, &"mediaSession".into())?;
+        if session.is_undefined() || session.is_null() {
+            return Ok(Self::unavailable());
+        }
+
+        let mut action_closures = Vec::new();
+
+        let set_action_handler =
+            Reflect::get(&session, &"setActionHandler".into())?.dyn_into::<Function>()?;
+
+        for action in ACTIONS {
+            let callback = callback.clone();
+            let closure = Closure::wrap(Box::new(move |details: JsValue| {
+                if let Some(action) = parse_action(action, &details) {
+                    callback.emit(action)
+                }
+            }) as Box<dyn Fn(_)>);
+
+            // browsers throw for actions they do not support
+            if let Err(e) = set_action_handler.call2(&session, &action.into(), closure.as_ref()) {
+                log::info!("media session action \"{}\" not supported: {:?}", action, e);
+            }
+
+            action_closures.push(closure);
+        }
+
+        Ok(Self {
+            session: Some(session),
+            _action_closures: action_closures,
+        })
+    }
+
+    /// Shows the item and channel (title, channel title and artwork).
+    pub fn set_metadata(&self, item: &Item, channel: &Channel) -> Result<(), JsError> {
+        if let Some(session) = &self.session {
+            let window = web_sys::window().ok_or("could not obtain window")?;
+            let constructor =
+                Reflect::get(&window, &"MediaMetadata".into())?.dyn_into::<Function>()?;
+            let init = Object::new();
+            let artwork = Object::new();
+
+            Reflect::set(&init, &"title".into(), &item.get_title().into())?;
+            Reflect::set(&init, &"artist".into(), &channel.val.title.clone().into())?;
+            Reflect::set(&init, &"album".into(), &channel.val.title.clone().into())?;
+            Reflect::set(&artwork, &"src".into(), &channel.val.image.clone().into())?;
+            Reflect::set(&init, &"artwork".into(), &Array::of1(&artwork))?;
+
+            let metadata = Reflect::construct(&constructor, &Array::of1(&init))?;
+
+            Reflect::set(session, &"metadata".into(), &metadata)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets the playback state ("none", "paused" or "playing").
+    pub fn set_playback_state(&self, state: &str) -> Result<(), JsError> {
+        if let Some(session) = &self.session {
+            Reflect::set(session, &"playbackState".into(), &state.into())?;
+        }
+
+        Ok(())
+    }
+
+    /// Updates the position shown by the browser; ignored while the duration is not known.
+    pub fn set_position_state(
+        &self,
+        duration: f64,
+        playback_rate: f64,
+        position: f64,
+    ) -> Result<(), JsError> {
+        if let Some(session) = &self.session {
+            if !duration.is_finite() || duration <= 0.0 {
+                return Ok(());
+            }
+
+            let set_position_state = Reflect::get(session, &"setPositionState".into())?;
+
+            if let Some(set_position_state) = set_position_state.dyn_ref::<Function>() {
+                let state = Object::new();
+
+                Reflect::set(&state, &"duration".into(), &duration.into())?;
+                Reflect::set(&state, &"playbackRate".into(), &playback_rate.into())?;
+                Reflect::set(&state, &"position".into(), &position.min(duration).into())?;
+                set_position_state.call1(session, &state)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_action(action: &str, details: &JsValue) -> Option<Action> {
+    let number = |key: &str| {
+        Reflect::get(details, &key.into())
+            .ok()
+            .and_then(|value| value.as_f64())
+    };
+
+    match action {
+        "play" => Some(Action::Play),
+        "pause" => Some(Action::Pause),
+        "seekbackward" => Some(Action::SeekBackward(number("seekOffset"))),
+        "seekforward" => Some(Action::SeekForward(number("seekOffset"))),
+        "seekto" => number("seekTime").map(Action::SeekTo),
+        "nexttrack" => Some(Action::NextTrack),
+        _ => None,
+    }
+}
@@ -0,0 +1,91 @@
+use js_sys::Uint8Array;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use web_sys::{MediaSource, SourceBuffer};
+
+/// # Progressive Source
+///
+/// State of a source that is played while its enclosure is still being downloaded.
+/// Chunks stored before playback started are read from the repository; later chunks are received from the fetcher.
+/// As both may overlap, chunks are kept by index and handed out in order and only once.
+/// Once the download is complete, the rest of the data (after the chunks handed out) is read from the stored enclosure, as other tabs may have downloaded chunks that were never received.
+#[derive(Debug)]
+pub struct Progressive {
+    pub item_id: Uuid,
+    pub media_source: MediaSource,
+    pub source_buffer: Option<SourceBuffer>,
+    pub download_complete: bool,
+    /// number of stored chunks still being read
+    pub pending_reads: u32,
+    /// number of requests for the stored chunks (or the enclosure) not answered yet
+    pub pending_requests: u32,
+    next_index: u32,
+    chunks: BTreeMap<u32, Uint8Array>,
+    /// number of bytes handed out
+    handed_out: f64,
+    /// the rest is read from the enclosure; chunks are no longer accepted
+    reading_enclosure: bool,
+}
+
+impl Progressive {
+    pub fn new(item_id: Uuid, media_source: MediaSource) -> Self {
+        Self {
+            item_id,
+            media_source,
+            source_buffer: None,
+            download_complete: false,
+            pending_reads: 0,
+            pending_requests: 0,
+            next_index: 0,
+            chunks: BTreeMap::new(),
+            handed_out: 0.0,
+            reading_enclosure: false,
+        }
+    }
+
+    pub fn add_chunk(&mut self, index: u32, data: Uint8Array) {
+        if !self.reading_enclosure && index >= self.next_index {
+            self.chunks.insert(index, data);
+        }
+    }
+
+    pub fn first_chunk(&self) -> Option<&Uint8Array> {
+        self.chunks.get(&0)
+    }
+
+    /// Returns the next chunk in order, if it is available.
+    pub fn next_chunk(&mut self) -> Option<Uint8Array> {
+        let chunk = self.chunks.remove(&self.next_index)?;
+
+        self.next_index += 1;
+        self.handed_out += chunk.length() as f64;
+        Some(chunk)
+    }
+
+    /// The download is complete, but the rest has not been read from the enclosure yet.
+    pub fn awaits_enclosure(&self) -> bool {
+        self.download_complete && !self.reading_enclosure
+    }
+
+    /// Continues with the stored enclosure: chunks not handed out yet are dropped; returns the offset of the rest.
+    pub fn read_enclosure(&mut self) -> f64 {
+        self.reading_enclosure = true;
+        self.chunks.clear();
+        self.handed_out
+    }
+
+    /// Adds the rest of the data read from the enclosure.
+    pub fn add_rest(&mut self, data: Uint8Array) {
+        if data.length() > 0 {
+            self.chunks.insert(self.next_index, data);
+        }
+    }
+
+    /// All chunks of the completed download have been handed out.
+    pub fn is_exhausted(&self) -> bool {
+        self.download_complete
+            && self.pending_requests == 0
+            && self.pending_reads == 0
+            && self.chunks.is_empty()
+    }
+}
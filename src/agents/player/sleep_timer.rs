@@ -0,0 +1,119 @@
+/// Mode of the sleep timer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SleepTimerMode {
+    /// stop after the given number of seconds of playback
+    Duration(f64),
+    /// stop at the end of the current episode
+    EndOfEpisode,
+}
+
+/// Length (in seconds) of the fade-out before the timer stops the playback.
+pub const FADE_DURATION: f64 = 10.0;
+
+/// # Sleep Timer
+///
+/// Counts down the playback time (pauses do not count) until the playback is stopped.
+#[derive(Debug)]
+pub struct SleepTimer {
+    mode: SleepTimerMode,
+    last_tick: f64,
+}
+
+impl SleepTimer {
+    pub fn new(mode: SleepTimerMode, now: f64) -> Self {
+        Self {
+            mode,
+            last_tick: now,
+        }
+    }
+
+    pub fn mode(&self) -> SleepTimerMode {
+        self.mode
+    }
+
+    /// Counts down the time passed since the last tick, if playing.
+    pub fn tick(&mut self, now: f64, playing: bool) {
+        if let SleepTimerMode::Duration(remaining) = &mut self.mode {
+            if playing {
+                *remaining = (*remaining - (now - self.last_tick) / 1000.0).max(0.0);
+            }
+        }
+
+        self.last_tick = now;
+    }
+
+    /// Adds time; at the end of the episode, the timer continues into the next episode.
+    pub fn extend(&mut self, seconds: f64, episode_remaining: f64) {
+        self.mode = SleepTimerMode::Duration(self.remaining(episode_remaining) + seconds);
+    }
+
+    /// Remaining time in seconds; `episode_remaining` is the (real) time until the end of the current episode.
+    pub fn remaining(&self, episode_remaining: f64) -> f64 {
+        match self.mode {
+            SleepTimerMode::Duration(remaining) => remaining,
+            SleepTimerMode::EndOfEpisode => episode_remaining,
+        }
+    }
+
+    /// Factor applied to the volume to fade out the playback.
+    pub fn fade(&self, episode_remaining: f64) -> f64 {
+        (self.remaining(episode_remaining) / FADE_DURATION).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tick() {
+        let mut timer = SleepTimer::new(SleepTimerMode::Duration(60.0), 0.0);
+
+        timer.tick(10_000.0, true);
+        assert_eq!(timer.mode(), SleepTimerMode::Duration(50.0));
+        timer.tick(40_000.0, false);
+        assert_eq!(timer.mode(), SleepTimerMode::Duration(50.0));
+        timer.tick(45_000.0, true);
+        assert_eq!(timer.mode(), SleepTimerMode::Duration(45.0));
+        timer.tick(120_000.0, true);
+        assert_eq!(timer.mode(), SleepTimerMode::Duration(0.0));
+    }
+
+    #[test]
+    fn tick_end_of_episode() {
+        let mut timer = SleepTimer::new(SleepTimerMode::EndOfEpisode, 0.0);
+
+        timer.tick(10_000.0, true);
+        assert_eq!(timer.mode(), SleepTimerMode::EndOfEpisode);
+        assert_eq!(timer.remaining(25.0), 25.0);
+    }
+
+    #[test]
+    fn extend() {
+        let mut timer = SleepTimer::new(SleepTimerMode::Duration(60.0), 0.0);
+
+        timer.extend(300.0, 25.0);
+        assert_eq!(timer.mode(), SleepTimerMode::Duration(360.0));
+
+        let mut timer = SleepTimer::new(SleepTimerMode::EndOfEpisode, 0.0);
+
+        timer.extend(300.0, 25.0);
+        assert_eq!(timer.mode(), SleepTimerMode::Duration(325.0));
+    }
+
+    #[test]
+    fn fade() {
+        let timer = SleepTimer::new(SleepTimerMode::Duration(60.0), 0.0);
+
+        assert_eq!(timer.fade(0.0), 1.0);
+
+        let timer = SleepTimer::new(SleepTimerMode::Duration(FADE_DURATION / 2.0), 0.0);
+
+        assert_eq!(timer.fade(0.0), 0.5);
+
+        let timer = SleepTimer::new(SleepTimerMode::EndOfEpisode, 0.0);
+
+        assert_eq!(timer.fade(0.0), 0.0);
+        assert_eq!(timer.fade(-1.0), 0.0);
+    }
+}
@@ -1,11 +1,18 @@
 use crate::{
-    agents::{player::Response, repo},
+    agents::{
+        player::{Response, SleepTimerMode},
+        repo,
+    },
     objects::JsError,
 };
 
 /// # End Task
 ///
-/// It is assumed that this task will only be created in the on_end event handler.
+/// It is assumed that this task will only be created in the on_end event handler or when the outro of the channel is reached (after pausing).
+/// It is also created, when the crossfade into the next queued item starts (without pausing).
+/// The play count is only incremented, if it was not already when reaching the completion threshold.
+/// If the sleep timer is set to the end of the episode, the timer is cancelled and the playback is reported as paused, so it does not continue with the next episode.
+/// Otherwise, the player continues with the next queued item.
 ///
 /// ## Stages and Transitions
 ///
@@ -36,11 +43,29 @@ impl super::TaskProcessor<EndTask> for super::super::Player {
     fn process(&mut self, task: &mut EndTask) -> Result<bool, JsError> {
         match task.get_stage() {
             EndStage::Finalize => {
-                if let Some(source) = &mut self.source {
+                let source = match &mut self.source {
+                    Some(source) => source,
+                    None => return Ok(true),
+                };
+
+                if !self.marked_played {
                     source.0.increment_play_count();
-                    source.0.set_playback_time(None);
-                    self.repo.send(repo::Request::UpdateItem(source.0.clone()));
-                    self.send_response(Response::End);
+                    self.marked_played = true;
+                }
+                source.0.set_playback_time(None);
+                self.repo.send(repo::Request::UpdateItem(source.0.clone()));
+
+                let item_id = source.0.get_id();
+
+                match self.sleep_timer.as_ref().map(|timer| timer.mode()) {
+                    Some(SleepTimerMode::EndOfEpisode) => {
+                        self.set_sleep_timer(None)?;
+                        self.send_response(Response::Paused);
+                    }
+                    _ => {
+                        self.send_response(Response::End);
+                        self.advance(item_id)?;
+                    }
                 }
 
                 Ok(true)
@@ -1,6 +1,6 @@
 use wasm_bindgen::JsCast;
 
-use crate::agents::{player::Response, repo};
+use crate::agents::player::Response;
 
 #[derive(Debug)]
 pub struct PauseTask {
@@ -48,15 +48,12 @@ impl super::TaskProcessor<PauseTask> for super::super::Player {
             }
             PauseStage::WaitingForPause => Ok(false),
             PauseStage::Finalize => {
-                if let Some(source) = &mut self.source {
+                if self.source.is_some() {
                     self.audio_element.remove_event_listener_with_callback(
                         "timeupdate",
                         self.on_timeupdate_closure.as_ref().unchecked_ref(),
                     )?;
-                    source
-                        .0
-                        .set_playback_time(Some(self.audio_element.current_time()));
-                    self.repo.send(repo::Request::UpdateItem(source.0.clone()));
+                    self.save_position();
                     self.send_response(Response::Paused);
                 }
                 Ok(true)
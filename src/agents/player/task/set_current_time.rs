@@ -1,4 +1,4 @@
-use crate::agents::repo;
+use web_sys::HtmlMediaElement;
 
 #[derive(Debug)]
 pub enum SetCurrentTimeStage {
@@ -8,6 +8,9 @@ pub enum SetCurrentTimeStage {
 
 /// # Task to set the current time
 ///
+/// The task completes on the "seeked" event, which is also sent while paused.
+/// Before the metadata is loaded, no seeking takes place; the time is then the position the playback starts at.
+///
 /// ## Stages and Transitions
 /// * Init (S)
 /// * time_set (T)
@@ -45,15 +48,17 @@ impl super::TaskProcessor<SetCurrentTimeTask> for super::super::Player {
             SetCurrentTimeStage::Init => {
                 self.audio_element
                     .set_current_time(task.get_current_time().clone());
-                Ok(false)
+
+                match self.audio_element.ready_state() == HtmlMediaElement::HAVE_NOTHING {
+                    true => {
+                        self.save_position();
+                        Ok(true)
+                    }
+                    false => Ok(false),
+                }
             }
             SetCurrentTimeStage::Finalize => {
-                if let Some(source) = &mut self.source {
-                    source
-                        .0
-                        .set_playback_time(Some(self.audio_element.current_time()));
-                    self.repo.send(repo::Request::UpdateItem(source.0.clone()));
-                }
+                self.save_position();
                 Ok(true)
             }
         }
@@ -1,150 +1,290 @@
 use crate::{
-    agents::{player::Response, repo},
-    objects::Item,
+    agents::{
+        player::{Response, SourceMode},
+        repo,
+    },
+    objects::{Item, JsError},
+    utils,
 };
-use js_sys::ArrayBuffer;
+use podcast_player_common::item_meta::DownloadStatus;
 use podcast_player_common::Channel;
 use wasm_bindgen::JsCast;
-use web_sys::{MediaSource, Url};
+use web_sys::{Blob, MediaSource, Url};
 
-use super::TaskProcessor;
+use super::{super::progressive::Progressive, TaskProcessor};
 
 /// # Set Source Task
 ///
+/// Downloaded enclosures are played from a blob URL.
+/// The blob is typed with the MIME type of the enclosure; if the audio element cannot play the type, the task fails.
+/// Streamed enclosures are played directly from the server.
+/// Enclosures still being downloaded are played progressively through a media source, which is fed with the chunks of the download.
+/// If the download completes before the progressive source is ready, the task starts over with the downloaded enclosure.
+///
 /// ## Stages and Transitions
 ///
 /// * Init (S)
-/// * source_open_data_triggered (T)
-/// * WaitingForSourceOpenData (S)
-/// * source_opened && set_data (T)
-/// * SourceOpenData (S)
-/// * data_buffer_update_triggered (T)
-/// * WaitingForBufferUpdate (S)
-/// * buffer_updated (T)
+/// * WaitingForData (S) [local] | WaitingForSourceOpen (S) [progressive]
+/// * set_data (T) | source_opened (T)
+/// * DataAvailable (S) | SourceOpen (S)
+/// * WaitingForMetadata (S)
+/// * metadata_loaded (T)
+/// * WaitingForChannel (S)
+/// * set_channel (T)
 /// * Finalize (S)
 #[derive(Debug)]
 pub struct SetSourceTask {
     item: Item,
-    data: Option<ArrayBuffer>,
+    mode: SourceMode,
+    data: Option<Blob>,
     channel: Option<Channel>,
-    source_open: bool,
     stage: SetSourceStage,
-    buffer_updated: bool,
+    source_ready: bool,
+    progressive: bool,
+    /// time (ms since the epoch) the source was paused before (if restored)
+    paused_at: Option<f64>,
+    /// position to start at instead of the stored playback time
+    start: Option<f64>,
 }
 
 #[derive(Debug)]
 pub enum SetSourceStage {
     Init,
-    WaitingForSourceOpenData,
-    SourceOpenData,
-    WaitingForBufferUpdate,
+    WaitingForData,
+    DataAvailable,
+    WaitingForSourceOpen,
+    SourceOpen,
+    WaitingForMetadata,
     WaitingForChannel,
     Finalize,
 }
 
 impl SetSourceTask {
-    pub fn new(item: Item) -> Self {
+    pub fn new(item: Item, mode: SourceMode) -> Self {
         Self {
             item,
+            mode,
             data: None,
             channel: None,
-            source_open: false,
             stage: SetSourceStage::Init,
-            buffer_updated: false,
+            source_ready: false,
+            progressive: false,
+            paused_at: None,
+            start: None,
         }
     }
 
+    pub fn get_item(&self) -> &Item {
+        &self.item
+    }
+
     pub fn source_opened(&mut self) {
-        self.source_open = true;
+        if let SetSourceStage::WaitingForSourceOpen = self.stage {
+            self.stage = SetSourceStage::SourceOpen;
+        }
+    }
 
-        match (self.source_open, &self.data) {
-            (true, Some(_)) => self.stage = SetSourceStage::SourceOpenData,
-            (_, _) => self.stage = SetSourceStage::WaitingForSourceOpenData,
+    /// Restarts setting a progressive source not ready yet with the complete enclosure; returns whether it restarted.
+    pub fn download_completed(&mut self, item: Item) -> bool {
+        let restart = self.progressive && !self.source_ready;
+
+        if restart {
+            self.item = item;
+            self.progressive = false;
+            self.stage = SetSourceStage::Init;
         }
+
+        restart
+    }
+
+    pub fn set_paused_at(&mut self, paused_at: Option<f64>) {
+        self.paused_at = paused_at;
+    }
+
+    pub fn set_start(&mut self, start: Option<f64>) {
+        self.start = start;
     }
 
     pub fn set_channel(&mut self, channel: Channel) {
         self.channel = Some(channel);
 
-        if self.buffer_updated {
+        if self.source_ready {
             self.stage = SetSourceStage::Finalize;
         }
     }
 
-    pub fn set_data(&mut self, data: ArrayBuffer) {
+    pub fn set_data(&mut self, data: Blob) {
         self.data = Some(data);
-
-        match (self.source_open, &self.data) {
-            (true, Some(_)) => self.stage = SetSourceStage::SourceOpenData,
-            (_, _) => self.stage = SetSourceStage::WaitingForSourceOpenData,
-        }
+        self.stage = SetSourceStage::DataAvailable;
     }
 
-    pub fn buffer_updated(&mut self) {
-        self.buffer_updated = true;
+    pub fn metadata_loaded(&mut self) {
+        if let SetSourceStage::WaitingForMetadata = self.stage {
+            self.source_ready = true;
 
-        match &self.channel.is_some() {
-            true => self.stage = SetSourceStage::Finalize,
-            false => self.stage = SetSourceStage::WaitingForChannel,
+            match &self.channel.is_some() {
+                true => self.stage = SetSourceStage::Finalize,
+                false => self.stage = SetSourceStage::WaitingForChannel,
+            }
         }
     }
 }
 
 impl TaskProcessor<SetSourceTask> for super::super::Player {
-    fn process(&mut self, task: &mut SetSourceTask) -> Result<bool, crate::objects::JsError> {
+    fn process(&mut self, task: &mut SetSourceTask) -> Result<bool, JsError> {
         match &task.stage {
             SetSourceStage::Init => {
                 // remove source
-                self.source = None;
-                // set new media source
-                self.media_source = MediaSource::new()?;
-                self.audio_element
-                    .set_src(&Url::create_object_url_with_source(&self.media_source)?);
-                self.media_source.set_onsourceopen(Some(
-                    self.mediasource_opened_closure.as_ref().unchecked_ref(),
-                ));
-                // request data
-                self.repo
-                    .send(repo::Request::GetEnclosure(task.item.get_id()));
+                if let Some(source) = self.source.take() {
+                    self.repo.send(repo::Request::Unsubscribe(repo::Query::Item(
+                        source.0.get_id(),
+                    )));
+                    self.repo
+                        .send(repo::Request::Unsubscribe(repo::Query::Channel(
+                            source.1.val.id,
+                        )));
+                }
+                if let Some(url) = self.object_url.take() {
+                    Url::revoke_object_url(&url)?;
+                }
+                self.finish_crossfade()?;
+                self.release_next_source()?;
+                self.progressive = None;
+                self.failure_reported = false;
                 self.repo
                     .send(repo::Request::GetChannel(*task.item.get_channel_id()));
-                task.stage = SetSourceStage::WaitingForSourceOpenData;
+
+                match task.mode {
+                    SourceMode::Local
+                        if matches!(
+                            task.item.get_download_status(),
+                            DownloadStatus::InProgress
+                        ) =>
+                    {
+                        let media_source = MediaSource::new()?;
+                        let url = Url::create_object_url_with_source(&media_source)?;
+
+                        media_source.set_onsourceopen(Some(
+                            self.mediasource_opened_closure.as_ref().unchecked_ref(),
+                        ));
+                        self.audio_element.set_preload("metadata");
+                        self.audio_element.set_src(&url);
+                        self.object_url = Some(url);
+                        let mut progressive = Progressive::new(task.item.get_id(), media_source);
+
+                        progressive.pending_requests += 1;
+                        self.progressive = Some(progressive);
+                        // the subscription reveals when the download is complete
+                        self.repo.send(repo::Request::Subscribe(repo::Query::Item(
+                            task.item.get_id(),
+                        )));
+                        self.repo
+                            .send(repo::Request::GetEnclosureChunks(task.item.get_id()));
+                        task.progressive = true;
+                        task.stage = SetSourceStage::WaitingForSourceOpen;
+                    }
+                    SourceMode::Local => {
+                        self.repo
+                            .send(repo::Request::GetEnclosure(task.item.get_id()));
+                        task.stage = SetSourceStage::WaitingForData;
+                    }
+                    SourceMode::Stream => {
+                        self.download_streamed(&mut task.item);
+                        self.audio_element.set_preload("metadata");
+                        self.audio_element
+                            .set_src(&format!("/api/items/{}/stream", task.item.get_id()));
+                        task.stage = SetSourceStage::WaitingForMetadata;
+                    }
+                }
+
                 Ok(false)
             }
-            SetSourceStage::WaitingForSourceOpenData => Ok(false),
-            SetSourceStage::SourceOpenData => {
-                // clear existing source buffers
-                let sbl = self.media_source.source_buffers();
+            SetSourceStage::WaitingForData => Ok(false),
+            SetSourceStage::DataAvailable => {
+                let data = task.data.as_ref().ok_or("could not get data")?;
+                let mime_type = data.type_();
 
-                while let Some(sb) = sbl.get(0) {
-                    self.media_source.remove_source_buffer(&sb)?
+                if !mime_type.is_empty() && self.audio_element.can_play_type(&mime_type).is_empty()
+                {
+                    return Err(JsError::from_str(&format!(
+                        "cannot play \"{}\": this browser does not support the format \"{}\"",
+                        task.item.get_title(),
+                        mime_type
+                    )));
                 }
-                // create new source buffer
-                let sb = self.media_source.add_source_buffer("audio/mpeg")?;
+
+                let url = Url::create_object_url_with_blob(data)?;
                 self.audio_element.set_preload("metadata");
-                // load data
-                sb.append_buffer_with_array_buffer(
-                    task.data.as_ref().ok_or("could not get data")?,
-                )?;
-                sb.set_onupdate(Some(
+                self.audio_element.set_src(&url);
+                self.object_url = Some(url);
+                task.stage = SetSourceStage::WaitingForMetadata;
+                Ok(false)
+            }
+            SetSourceStage::WaitingForSourceOpen => Ok(false),
+            SetSourceStage::SourceOpen => {
+                let progressive = self
+                    .progressive
+                    .as_mut()
+                    .ok_or("progressive source not set")?;
+                // the type is sniffed from the first chunk, as the download does not know it yet
+                let mime_type = match progressive.first_chunk() {
+                    Some(chunk) => utils::sniff_mime_type(
+                        &chunk
+                            .subarray(0, chunk.length().min(utils::SNIFF_LENGTH))
+                            .to_vec(),
+                    )
+                    .unwrap_or("audio/mpeg"),
+                    None => return Ok(false),
+                };
+
+                if !MediaSource::is_type_supported(mime_type) {
+                    return Err(JsError::from_str(&format!(
+                        "cannot play \"{}\" while it is downloading: this browser does not support streaming the format \"{}\"",
+                        task.item.get_title(),
+                        mime_type
+                    )));
+                }
+
+                let source_buffer = progressive.media_source.add_source_buffer(mime_type)?;
+
+                source_buffer.set_onupdateend(Some(
                     self.sourcebuffer_update_closure.as_ref().unchecked_ref(),
                 ));
-                task.stage = SetSourceStage::WaitingForBufferUpdate;
+                source_buffer.set_onerror(Some(
+                    self.sourcebuffer_error_closure.as_ref().unchecked_ref(),
+                ));
+                progressive.source_buffer = Some(source_buffer);
+                self.append_chunks()?;
+                task.stage = SetSourceStage::WaitingForMetadata;
                 Ok(false)
             }
-            SetSourceStage::WaitingForBufferUpdate => Ok(false),
+            SetSourceStage::WaitingForMetadata => Ok(false),
             SetSourceStage::WaitingForChannel => Ok(false),
             SetSourceStage::Finalize => {
                 let channel = task.channel.as_ref().ok_or("channel not set")?;
                 self.audio_element
                     .set_playback_rate(channel.meta.playback_rate);
                 self.audio_element.set_volume(channel.meta.volume);
-                self.audio_element
-                    .set_current_time(match task.item.get_playback_time() {
+                let duration = self.audio_element.duration();
+                self.audio_element.set_current_time(
+                    match task.start.or(task.item.get_playback_time()) {
                         Some(current_time) => current_time,
-                        None => 0.0,
-                    });
+                        // episodes started from the beginning skip the intro of the channel
+                        None => self.config.intro_outro(&channel.val.id).start(duration),
+                    },
+                );
                 self.source = Some((task.item.clone(), channel.clone()));
+                self.marked_played = false;
+                self.save_state();
+                self.load_chapters(&task.item, task.data.clone());
+                self.paused_at = task.paused_at;
+                self.repo.send(repo::Request::Subscribe(repo::Query::Item(
+                    task.item.get_id(),
+                )));
+                self.repo
+                    .send(repo::Request::Subscribe(repo::Query::Channel(
+                        channel.val.id,
+                    )));
                 self.send_response(Response::SourceSet(
                     task.item.clone(),
                     channel.clone(),
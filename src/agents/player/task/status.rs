@@ -7,6 +7,7 @@ use crate::{
 ///
 /// This task will return the status of the player (i.e., item, channel, length, playing).
 /// If no source is set, ``None`` will be returned.
+/// While the source stored before a reload is being restored, the task waits, so the status reflects the restored source.
 ///
 /// ## Stages and Transitions
 ///
@@ -37,6 +38,10 @@ impl super::TaskProcessor<StatusTask> for super::super::Player {
     fn process(&mut self, task: &mut StatusTask) -> Result<bool, JsError> {
         match task.get_stage() {
             StatusStage::Finalize => {
+                if self.restoring {
+                    return Ok(false);
+                }
+
                 match &self.source {
                     Some(source) => {
                         self.send_response(Response::Status(Some((
@@ -0,0 +1,177 @@
+use super::audio_chain::AudioChain;
+use crate::objects::{Item, JsError};
+use podcast_player_common::Channel;
+use web_sys::{Blob, HtmlAudioElement, HtmlMediaElement, Url};
+
+/// # Next Source
+///
+/// The next queued item, loaded into a second audio element before the current source ends.
+/// When the current source ends (or its crossfade starts), the elements are swapped, so the next item starts without waiting for its data.
+/// Enclosures still being downloaded are not preloaded; they are set as source once the current one ended.
+pub struct NextSource {
+    pub item: Item,
+    pub channel: Option<Channel>,
+    pub audio_element: HtmlAudioElement,
+    pub object_url: Option<String>,
+    /// downloaded enclosure (passed on to read the chapters)
+    pub data: Option<Blob>,
+    /// position to start at (the stored playback time or the end of the intro)
+    start: f64,
+}
+
+impl NextSource {
+    pub fn new(item: Item, start: f64) -> Result<Self, JsError> {
+        let audio_element = HtmlAudioElement::new()?;
+
+        audio_element.set_preload("auto");
+
+        Ok(Self {
+            item,
+            channel: None,
+            audio_element,
+            object_url: None,
+            data: None,
+            start,
+        })
+    }
+
+    /// Loads the downloaded enclosure.
+    pub fn set_data(&mut self, data: Blob) -> Result<(), JsError> {
+        let url = Url::create_object_url_with_blob(&data)?;
+
+        self.load(&url);
+        self.object_url = Some(url);
+        self.data = Some(data);
+        Ok(())
+    }
+
+    /// Loads the enclosure streamed from the server.
+    pub fn stream(&mut self) {
+        self.load(&format!("/api/items/{}/stream", self.item.get_id()));
+    }
+
+    fn load(&self, url: &str) {
+        self.audio_element.set_src(url);
+        // before the metadata is loaded, this sets the position the playback starts at
+        self.audio_element.set_current_time(self.start);
+    }
+
+    pub fn set_channel(&mut self, channel: Channel) {
+        self.audio_element
+            .set_playback_rate(channel.meta.playback_rate);
+        self.audio_element.set_volume(channel.meta.volume);
+        self.channel = Some(channel);
+    }
+
+    /// Whether the element can take over (possibly still buffering): the channel is known and the data is loading without errors.
+    pub fn is_usable(&self) -> bool {
+        self.channel.is_some()
+            && self.audio_element.error().is_none()
+            && !self.audio_element.src().is_empty()
+    }
+
+    /// Whether enough data was loaded to start without buffering (required for a crossfade).
+    pub fn is_ready(&self) -> bool {
+        self.is_usable() && self.audio_element.ready_state() >= HtmlMediaElement::HAVE_FUTURE_DATA
+    }
+
+    /// Stops loading and releases the data.
+    pub fn release(self) -> Result<(), JsError> {
+        release_element(&self.audio_element, self.object_url.as_deref())
+    }
+}
+
+/// # Crossfade
+///
+/// The element of the previous source, fading out while the next one fades in.
+/// It keeps the audio chain it is connected to until it is released.
+pub struct Crossfade {
+    audio_element: HtmlAudioElement,
+    object_url: Option<String>,
+    audio_chain: Option<AudioChain>,
+    /// time (ms since the epoch) the crossfade started
+    started_at: f64,
+    /// length in seconds
+    length: f64,
+    /// volume of the previous source at the start
+    volume: f64,
+}
+
+impl Crossfade {
+    pub fn new(
+        audio_element: HtmlAudioElement,
+        object_url: Option<String>,
+        audio_chain: Option<AudioChain>,
+        now: f64,
+        length: f64,
+    ) -> Self {
+        let volume = audio_element.volume();
+
+        Self {
+            audio_element,
+            object_url,
+            audio_chain,
+            started_at: now,
+            length,
+            volume,
+        }
+    }
+
+    /// Fades out the previous source; returns the share (between 0 and 1) of the volume of the next source.
+    pub fn tick(&self, now: f64) -> f64 {
+        let progress = fade_progress(now - self.started_at, self.length);
+
+        self.audio_element
+            .set_volume(self.volume * (1.0 - progress));
+        progress
+    }
+
+    /// Stops the previous source and releases its data.
+    pub fn release(self) -> Result<(), JsError> {
+        if let Some(audio_chain) = &self.audio_chain {
+            audio_chain.close()?;
+        }
+
+        release_element(&self.audio_element, self.object_url.as_deref())
+    }
+}
+
+/// Share (between 0 and 1) of a crossfade of the given length (in seconds) done after the given time (in ms).
+fn fade_progress(elapsed: f64, length: f64) -> f64 {
+    match length > 0.0 {
+        true => (elapsed / (length * 1000.0)).clamp(0.0, 1.0),
+        false => 1.0,
+    }
+}
+
+/// Stops an audio element that is no longer used; the object URL of its data is revoked.
+pub fn release_element(
+    audio_element: &HtmlAudioElement,
+    object_url: Option<&str>,
+) -> Result<(), JsError> {
+    audio_element.pause()?;
+    audio_element.remove_attribute("src")?;
+    audio_element.load();
+
+    if let Some(url) = object_url {
+        Url::revoke_object_url(url)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress() {
+        assert_eq!(fade_progress(0.0, 10.0), 0.0);
+        assert_eq!(fade_progress(2_500.0, 10.0), 0.25);
+        assert_eq!(fade_progress(10_000.0, 10.0), 1.0);
+        assert_eq!(fade_progress(60_000.0, 10.0), 1.0);
+        // the clock may go backwards
+        assert_eq!(fade_progress(-1_000.0, 10.0), 0.0);
+        assert_eq!(fade_progress(0.0, 0.0), 1.0);
+    }
+}
@@ -0,0 +1,321 @@
+use super::{
+    notifier, repo,
+    tabs::{self, TabMessage},
+};
+use crate::objects::{Item, ItemQueue, JsError};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use yew_agent::{Agent, AgentLink, Bridge, Bridged, Context, Dispatched, Dispatcher, HandlerId};
+
+#[derive(Debug, Clone)]
+pub enum Request {
+    GetQueue,          // returns Queue only to requester
+    PlayNext(Item),    // returns Queue to all subscribers
+    Append(Item),      // returns Queue to all subscribers
+    Remove(Uuid),      // returns Queue to all subscribers
+    Move(Uuid, usize), // returns Queue to all subscribers
+    Clear,             // returns Queue to all subscribers
+    /// Removes the finished item and takes the next one from the queue, once it is loaded; returns Next only to requester
+    Advance(Uuid),
+}
+
+#[derive(Debug, Clone)]
+pub enum Response {
+    Queue(Vec<Item>),
+    Next(Option<Item>),
+}
+
+#[derive(Debug)]
+pub enum Message {
+    RepoMessage(repo::Response),
+    TabsMessage(tabs::Response),
+}
+
+/// # Queue
+///
+/// Keeps the list of items to be played next.
+/// The queue is persisted in the repository; the queued items are followed through item subscriptions, so their state is up to date.
+/// Requests received before the queue is loaded are processed once it is available.
+/// When advancing, the next item stays queued until it is loaded (e.g., right after a reload); queued items that no longer exist are removed.
+/// Changes are announced to the other tabs, which take over the changed queue.
+pub struct Queue {
+    link: AgentLink<Self>,
+    subscribers: HashSet<HandlerId>,
+    repo: Box<dyn Bridge<repo::Repo>>,
+    tabs: Box<dyn Bridge<tabs::Tabs>>,
+    notifier: Dispatcher<notifier::Notifier>,
+    queue: Option<ItemQueue>,
+    items: HashMap<Uuid, Item>,
+    pending: Vec<(Request, HandlerId)>,
+    /// requester of an advance waiting for the next item to be loaded
+    advancing: Option<HandlerId>,
+}
+
+impl Queue {
+    fn process_update(&mut self, msg: Message) -> Result<(), JsError> {
+        match msg {
+            Message::RepoMessage(repo::Response::Queue(queue)) => {
+                if self.queue.is_none() {
+                    let queue = queue.unwrap_or_default();
+
+                    for item_id in &queue.item_ids {
+                        self.repo
+                            .send(repo::Request::Subscribe(repo::Query::Item(*item_id)));
+                    }
+
+                    self.queue = Some(queue);
+
+                    for (request, handler_id) in std::mem::take(&mut self.pending) {
+                        self.process_handle_input(request, handler_id)?;
+                    }
+                }
+            }
+            Message::RepoMessage(repo::Response::ItemsChanged(
+                repo::Query::Item(item_id),
+                diffs,
+            )) => {
+                for diff in diffs {
+                    match diff {
+                        repo::Diff::Added(item) | repo::Diff::Updated(item) => {
+                            self.items.insert(item.get_id(), item);
+                        }
+                        repo::Diff::Removed(item_id) => {
+                            self.items.remove(&item_id);
+                        }
+                    }
+                }
+
+                let mut changed = false;
+
+                // the item does not exist (anymore)
+                if !self.items.contains_key(&item_id) {
+                    if let Some(queue) = &mut self.queue {
+                        if queue.remove(&item_id) {
+                            self.release(item_id);
+                            changed = true;
+                        }
+                    }
+                }
+
+                changed |= self.take_next();
+
+                if changed {
+                    self.store()?;
+                }
+
+                self.send_queue();
+            }
+            Message::RepoMessage(_) => {}
+            Message::TabsMessage(tabs::Response::Received(TabMessage::QueueChanged(changed))) => {
+                if let Some(queue) = &self.queue {
+                    let removed: Vec<Uuid> = queue
+                        .item_ids
+                        .iter()
+                        .filter(|item_id| !changed.contains(item_id))
+                        .cloned()
+                        .collect();
+
+                    for item_id in &changed.item_ids {
+                        if !queue.contains(item_id) {
+                            self.repo
+                                .send(repo::Request::Subscribe(repo::Query::Item(*item_id)));
+                        }
+                    }
+                    for item_id in removed {
+                        self.release(item_id);
+                    }
+
+                    self.queue = Some(changed);
+
+                    if self.take_next() {
+                        self.store()?;
+                    }
+
+                    self.send_queue();
+                }
+            }
+            Message::TabsMessage(_) => {}
+        }
+
+        Ok(())
+    }
+
+    fn process_handle_input(&mut self, msg: Request, handler_id: HandlerId) -> Result<(), JsError> {
+        let queue = match &mut self.queue {
+            Some(queue) => queue,
+            None => {
+                self.pending.push((msg, handler_id));
+                return Ok(());
+            }
+        };
+
+        match msg {
+            Request::GetQueue => {
+                let items = self.queued_items();
+
+                self.link.respond(handler_id, Response::Queue(items));
+                return Ok(());
+            }
+            Request::PlayNext(item) => {
+                if !queue.contains(&item.get_id()) {
+                    self.repo
+                        .send(repo::Request::Subscribe(repo::Query::Item(item.get_id())));
+                }
+
+                queue.play_next(item.get_id());
+                self.items.insert(item.get_id(), item);
+            }
+            Request::Append(item) => {
+                if !queue.contains(&item.get_id()) {
+                    self.repo
+                        .send(repo::Request::Subscribe(repo::Query::Item(item.get_id())));
+                }
+
+                queue.append(item.get_id());
+                self.items.insert(item.get_id(), item);
+            }
+            Request::Remove(item_id) => {
+                if queue.remove(&item_id) {
+                    self.release(item_id);
+                }
+            }
+            Request::Move(item_id, position) => queue.move_to(&item_id, position),
+            Request::Clear => {
+                for item_id in std::mem::take(&mut queue.item_ids) {
+                    self.release(item_id);
+                }
+            }
+            Request::Advance(finished_id) => {
+                if queue.remove(&finished_id) {
+                    self.release(finished_id);
+                }
+
+                // a previous advance is superseded
+                if let Some(advancing) = self.advancing.replace(handler_id) {
+                    self.link.respond(advancing, Response::Next(None));
+                }
+            }
+        }
+
+        self.take_next();
+        self.store()?;
+        self.send_queue();
+        Ok(())
+    }
+
+    /// Answers the pending advance with the first queued item, which is removed from the queue; returns whether the queue changed.
+    /// The answer is deferred, while the item is not loaded.
+    fn take_next(&mut self) -> bool {
+        let (handler_id, next_id) = match (self.advancing, &self.queue) {
+            (Some(handler_id), Some(queue)) => (handler_id, queue.item_ids.first().cloned()),
+            _ => return false,
+        };
+        let next = match next_id {
+            Some(item_id) => match self.items.get(&item_id).cloned() {
+                Some(item) => {
+                    if let Some(queue) = &mut self.queue {
+                        queue.remove(&item_id);
+                    }
+                    self.release(item_id);
+                    Some(item)
+                }
+                None => return false,
+            },
+            None => None,
+        };
+        let changed = next.is_some();
+
+        self.advancing = None;
+        self.link.respond(handler_id, Response::Next(next));
+        changed
+    }
+
+    /// Persists the queue and announces it to the other tabs.
+    fn store(&mut self) -> Result<(), JsError> {
+        let queue = self.queue.clone().ok_or("queue not loaded")?;
+
+        self.repo.send(repo::Request::UpdateQueue(queue.clone()));
+        self.tabs
+            .send(tabs::Request::Broadcast(TabMessage::QueueChanged(queue)));
+        Ok(())
+    }
+
+    /// Stops following an item that left the queue.
+    fn release(&mut self, item_id: Uuid) {
+        self.repo
+            .send(repo::Request::Unsubscribe(repo::Query::Item(item_id)));
+        self.items.remove(&item_id);
+    }
+
+    /// Queued items in order; items not loaded yet are skipped.
+    fn queued_items(&self) -> Vec<Item> {
+        match &self.queue {
+            Some(queue) => queue
+                .item_ids
+                .iter()
+                .filter_map(|item_id| self.items.get(item_id).cloned())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn send_queue(&self) {
+        let items = self.queued_items();
+
+        for handler_id in &self.subscribers {
+            if handler_id.is_respondable() {
+                self.link
+                    .respond(*handler_id, Response::Queue(items.clone()));
+            }
+        }
+    }
+}
+
+impl Agent for Queue {
+    type Reach = Context<Self>;
+    type Message = Message;
+    type Input = Request;
+    type Output = Response;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        let mut repo = repo::Repo::bridge(link.callback(Message::RepoMessage));
+
+        let tabs = tabs::Tabs::bridge(link.callback(Message::TabsMessage));
+
+        repo.send(repo::Request::GetQueue);
+
+        Self {
+            link,
+            subscribers: HashSet::new(),
+            repo,
+            tabs,
+            notifier: notifier::Notifier::dispatcher(),
+            queue: None,
+            items: HashMap::new(),
+            pending: Vec::new(),
+            advancing: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) {
+        match self.process_update(msg) {
+            Ok(_) => {}
+            Err(e) => self.notifier.send(notifier::Request::NotifyError(e)),
+        }
+    }
+
+    fn handle_input(&mut self, msg: Self::Input, id: HandlerId) {
+        match self.process_handle_input(msg, id) {
+            Ok(_) => {}
+            Err(e) => self.notifier.send(notifier::Request::NotifyError(e)),
+        }
+    }
+
+    fn connected(&mut self, id: HandlerId) {
+        self.subscribers.insert(id);
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        self.subscribers.remove(&id);
+    }
+}
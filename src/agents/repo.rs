@@ -1,30 +1,55 @@
+mod cache;
+mod query;
 mod task;
-use super::{fetcher, notifier};
+use super::{
+    fetcher, notifier,
+    tabs::{self, TabMessage},
+};
 use crate::{objects::*, utils};
-use js_sys::ArrayBuffer;
-use serde::{Deserialize, Serialize};
+use cache::*;
+pub use query::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::HashSet;
 use task::*;
 use uuid::Uuid;
 use wasm_bindgen::{closure::Closure, JsCast};
-use web_sys::{ConnectionType, IdbDatabase, IdbRequest, IdbTransaction, IdbTransactionMode};
+use web_sys::{Blob, ConnectionType, IdbDatabase, IdbRequest, IdbTransaction, IdbTransactionMode};
 use yew_agent::{Agent, AgentLink, Bridge, Bridged, Context, Dispatched, Dispatcher, HandlerId};
 
+/// Keys of the configuration values observed by other subscribers and tabs; the others (e.g., the player state, written on every position save) are only sent to the requester.
+const SHARED_CONFIGURATION: &[&str] = &["player"];
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum Request {
     GetFeeds,    // returns Feeds only to requester
     GetChannels, // returns Channels only to requester
     GetYearMonthKeysByChannelId(Uuid),
-    GetItemsByChannelIdYearMonth(Uuid, String), // returns Items only to requester
-    GetItemsByDownloadRequired,                 // returns Items only to requester
-    GetItemsByDownloadOk,                       // returns Items only to requester
-    GetEnclosure(Uuid),                         // returns Enclosure only to the requester
-    GetChannel(Uuid),
-    DeleteEnclosure(Item),  // returns UpdatedItem to all subscribers
-    UpdateChannel(Channel), // returns UpdatedChannel to all subscribers
-    UpdateItem(Item),       // returns UpdatedItem to all subscribers
+    GetItemsByDownloadRequired,      // returns Items only to requester
+    GetEnclosure(Uuid),              // returns Enclosure only to the requester
+    GetEnclosureChunks(Uuid),        // returns EnclosureChunks only to the requester
+    GetChannel(Uuid),                // returns Channel only to the requester
+    DeleteEnclosure(Item),           // returns ItemsChanged to matching queries
+    SetEnclosureError(Item, String), // returns ItemsChanged to matching queries and EnclosureMetas to all subscribers
+    UpdateChannel(Channel),          // returns ChannelsChanged to matching queries
+    UpdateItem(Item),                // returns ItemsChanged to matching queries
     GetUpdaterConf(Option<UpdaterConfig>), // returns UpdaterConfig only to requester
+    GetPlayerConf(Option<PlayerConfig>), // returns PlayerConfig to all subscribers, if written
+    GetPlayerState(Option<PlayerState>), // returns PlayerState only to requester
+    GetConsistencyReport,            // returns ConsistencyReport only to requester
+    GetQueue,                        // returns Queue only to requester
+    UpdateQueue(ItemQueue),          // returns Queue only to requester
+    GetChapters(Uuid),               // returns Chapters only to requester
+    UpdateChapters(ItemChapters),    // returns Chapters only to requester
+    GetEnclosureMetas(Vec<Uuid>),    // returns EnclosureMetas (per item) only to requester
+    GetImage(Uuid),                  // returns Image only to requester
+    GetHistory,                      // returns History only to requester
+    AddListeningSession(ListeningSession), // returns ListeningSession to all subscribers
+    GetBookmarks,                    // returns Bookmarks only to requester
+    UpdateBookmark(Bookmark),        // returns Bookmark to all subscribers
+    DeleteBookmark(Uuid),            // returns BookmarkDeleted to all subscribers
     AddFeed(String),
+    Subscribe(Query), // returns the initial result set as *Changed and diffs afterwards
+    Unsubscribe(Query),
 }
 
 #[derive(Debug, Clone)]
@@ -33,18 +58,49 @@ pub enum Response {
     Channels(Vec<Channel>),
     YearMonthKeys(Vec<String>),
     Items(Vec<Item>),
-    Enclosure(ArrayBuffer),
-    UpdatedFeed(FeedVal),
-    UpdatedChannel(Channel),
-    UpdatedItem(Item),
+    /// Enclosure of the item with the given id
+    Enclosure(Uuid, Blob),
+    EnclosureChunks(Uuid, Vec<Blob>),
+    Feed(FeedVal),
+    Channel(Channel),
+    Item(Item),
+    FeedsChanged(Query, Vec<Diff<FeedVal>>),
+    ChannelsChanged(Query, Vec<Diff<Channel>>),
+    ItemsChanged(Query, Vec<Diff<Item>>),
     UpdaterConfig(Option<UpdaterConfig>),
+    PlayerConfig(Option<PlayerConfig>),
+    PlayerState(Option<PlayerState>),
+    ConsistencyReport(Option<ConsistencyReport>),
+    Queue(Option<ItemQueue>),
+    /// Chapters of an item; `None`, if none are stored
+    Chapters(Uuid, Option<Vec<Chapter>>),
+    /// Enclosure metadata; after an enclosure was analyzed, its metadata is sent to all subscribers
+    EnclosureMetas(Vec<EnclosureMeta>),
+    /// Image (data URL) embedded in the enclosure of an item; sent to all subscribers, when extracted
+    Image(Uuid, Option<String>),
+    /// All listening sessions
+    History(Vec<ListeningSession>),
+    /// Listening session added to the history
+    ListeningSession(ListeningSession),
+    /// All bookmarks
+    Bookmarks(Vec<Bookmark>),
+    /// Bookmark added or updated
+    Bookmark(Bookmark),
+    /// Id of a deleted bookmark
+    BookmarkDeleted(Uuid),
 }
 
 pub struct Repo {
     link: AgentLink<Repo>,
     subscribers: HashSet<HandlerId>,
+    subscriptions: Subscriptions,
+    cache: Cache,
     db: Option<IdbDatabase>,
+    consistency_report: Option<ConsistencyReport>,
     fetcher: Box<dyn Bridge<fetcher::Fetcher>>,
+    tabs: Box<dyn Bridge<tabs::Tabs>>,
+    /// only the leader tab synchronizes and downloads
+    leader: bool,
     notifier: Dispatcher<notifier::Notifier>,
     tasks: Vec<Task>,
     idb_closure_error: Closure<dyn Fn(web_sys::Event)>,
@@ -62,7 +118,9 @@ pub enum Message {
     IdbRequest(Result<web_sys::Event, web_sys::Event>),
     IdbTransaction(Result<web_sys::Event, web_sys::Event>),
     FetcherMessage(fetcher::Response),
+    TabsMessage(tabs::Response),
     Interval(web_sys::Event),
+    EnclosureAnalyzed(EnclosureMeta, Result<utils::MediaMetadata, JsError>),
 }
 
 trait RepositoryTask {
@@ -86,6 +144,108 @@ trait RepositoryTask {
 }
 
 impl Repo {
+    /// Updates the cache with the given (changed) records and sends the diffs they cause to the subscribers of the affected queries.
+    /// The other tabs are told about the records, so they can do the same.
+    fn notify<T: Record + Cached>(
+        &mut self,
+        records: &[T],
+        response: fn(Query, Vec<Diff<T>>) -> Response,
+    ) {
+        self.notify_subscribers(records, response);
+        self.tabs
+            .send(tabs::Request::Broadcast(T::changed(records.to_vec())));
+    }
+
+    /// Updates the cache with the given records and sends the diffs they cause to the subscribers of the affected queries.
+    fn notify_subscribers<T: Record + Cached>(
+        &mut self,
+        records: &[T],
+        response: fn(Query, Vec<Diff<T>>) -> Response,
+    ) {
+        T::update_cache(&mut self.cache, records);
+
+        for (handler_id, query, diffs) in self.subscriptions.changes(records) {
+            if handler_id.is_respondable() {
+                self.link.respond(handler_id, response(query, diffs));
+            }
+        }
+    }
+
+    /// Answers a subscription from the cache.
+    /// Returns `false`, if the records required for the query are not cached.
+    fn subscribe_cached(&mut self, handler_id: HandlerId, query: &Query) -> bool {
+        match query {
+            Query::Channels | Query::Channel(_) => match self.cache.channels() {
+                Some(channels) => {
+                    let diffs = self
+                        .subscriptions
+                        .subscribe(handler_id, query.clone(), &channels);
+
+                    self.link
+                        .respond(handler_id, Response::ChannelsChanged(query.clone(), diffs));
+                    true
+                }
+                None => false,
+            },
+            Query::Feeds => match self.cache.feeds() {
+                Some(feeds) => {
+                    let diffs = self
+                        .subscriptions
+                        .subscribe(handler_id, query.clone(), &feeds);
+
+                    self.link
+                        .respond(handler_id, Response::FeedsChanged(query.clone(), diffs));
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Reads (`value` is `None`) or writes a configuration value.
+    /// Reads are answered from the cache, if possible; writes of shared values are sent to all subscribers and the other tabs.
+    fn process_configuration<T: Serialize + DeserializeOwned>(
+        &mut self,
+        handler_id: HandlerId,
+        key: &str,
+        value: Option<T>,
+        response: fn(Option<T>) -> Response,
+    ) -> Result<(), JsError> {
+        match (value, self.cache.configuration(key)) {
+            (None, Some(cached)) => self.link.respond(
+                handler_id,
+                response(serde_wasm_bindgen::from_value(cached)?),
+            ),
+            (value, _) => {
+                let value = value
+                    .map(|value| serde_wasm_bindgen::to_value(&value))
+                    .transpose()?;
+
+                if let Some(value) = &value {
+                    self.cache.set_configuration(key, value.clone());
+                }
+
+                let handler_id = match value.is_some() && SHARED_CONFIGURATION.contains(&key) {
+                    true => None,
+                    false => Some(handler_id),
+                };
+
+                self.tasks.insert(
+                    0,
+                    Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                        handler_id,
+                        task::put_get_with_key::Kind::Configuration,
+                        serde_wasm_bindgen::to_value(key)?,
+                        value,
+                    )),
+                )
+            }
+        }
+
+        Ok(())
+    }
+
     fn process_tasks(&mut self) {
         if let Some(mut task) = self.tasks.pop() {
             match self.process_task(&mut task) {
@@ -108,10 +268,14 @@ impl Repo {
             Task::GetAll(task) => self.process(task),
             Task::PutGetWithKey(task) => self.process(task),
             Task::StoreEnclosure(task) => self.process(task),
+            Task::StoreEnclosureChunk(task) => self.process(task),
             Task::DownloadStarted(task) => self.process(task),
             Task::DeleteEnclosure(task) => self.process(task),
+            Task::DeleteWithKey(task) => self.process(task),
             Task::SyncVal(task) => self.process(task),
             Task::GetKeys(task) => self.process(task),
+            Task::CheckConsistency(task) => self.process(task),
+            Task::GetEnclosure(task) => self.process(task),
         }
     }
 
@@ -142,11 +306,14 @@ impl Repo {
             Message::FetcherMessage(resp) => match resp {
                 fetcher::Response::PullFeedVals(feed_vals) => match feed_vals {
                     Ok(feed_vals) => {
-                        for feed_val in feed_vals {
+                        if !feed_vals.is_empty() {
                             self.tasks.insert(
                                 0,
                                 Task::SyncVal(task::sync_val::Task::new(
-                                    task::sync_val::Value::Feed(feed_val),
+                                    feed_vals
+                                        .into_iter()
+                                        .map(task::sync_val::Value::Feed)
+                                        .collect(),
                                 )),
                             );
                         }
@@ -160,11 +327,14 @@ impl Repo {
                 },
                 fetcher::Response::PullChannelVals(channel_vals) => match channel_vals {
                     Ok(channel_vals) => {
-                        for channel_val in channel_vals {
+                        if !channel_vals.is_empty() {
                             self.tasks.insert(
                                 0,
                                 Task::SyncVal(task::sync_val::Task::new(
-                                    task::sync_val::Value::Channel(channel_val),
+                                    channel_vals
+                                        .into_iter()
+                                        .map(task::sync_val::Value::Channel)
+                                        .collect(),
                                 )),
                             );
                         }
@@ -178,11 +348,14 @@ impl Repo {
                 },
                 fetcher::Response::PullItemVals(item_vals) => match item_vals {
                     Ok(item_vals) => {
-                        for item_val in item_vals {
+                        if !item_vals.is_empty() {
                             self.tasks.insert(
                                 0,
                                 Task::SyncVal(task::sync_val::Task::new(
-                                    task::sync_val::Value::Item(item_val),
+                                    item_vals
+                                        .into_iter()
+                                        .map(task::sync_val::Value::Item)
+                                        .collect(),
                                 )),
                             );
                         }
@@ -195,10 +368,14 @@ impl Repo {
                     },
                 },
                 fetcher::Response::PullDownload(item_id, data) => match data {
-                    Ok(data) => {
+                    Ok((content_type, head)) => {
                         self.tasks.insert(
                             0,
-                            Task::StoreEnclosure(task::store_enclosure::Task::new(item_id, data)),
+                            Task::StoreEnclosure(task::store_enclosure::Task::new(
+                                item_id,
+                                content_type,
+                                head,
+                            )),
                         );
 
                         Ok(())
@@ -208,6 +385,18 @@ impl Repo {
                         _ => Err(e),
                     },
                 },
+                fetcher::Response::DownloadChunk(item_id, index, data) => {
+                    self.tasks.insert(
+                        0,
+                        Task::StoreEnclosureChunk(task::store_enclosure_chunk::Task::new(
+                            item_id,
+                            index,
+                            Blob::new_with_u8_array_sequence(&js_sys::Array::of1(&data))?,
+                        )),
+                    );
+
+                    Ok(())
+                }
                 fetcher::Response::PullDownloadStarted(item_id) => {
                     self.tasks.insert(
                         0,
@@ -219,7 +408,24 @@ impl Repo {
                 fetcher::Response::Binary(_task_id, _res) => Ok(()),
                 fetcher::Response::Text(_task_id, _res) => Ok(()),
             },
+            Message::TabsMessage(tabs::Response::Leader(leader)) => {
+                // downloads left in progress by the previous leader are repaired, before the new one starts any
+                if leader && !self.leader {
+                    self.tasks
+                        .insert(0, Task::CheckConsistency(check_consistency::Task::new()));
+                }
+
+                self.leader = leader;
+                Ok(())
+            }
+            Message::TabsMessage(tabs::Response::Received(message)) => {
+                self.process_tab_message(message)
+            }
             Message::Interval(_) => {
+                if !self.leader {
+                    return Ok(());
+                }
+
                 self.tasks.insert(
                     0,
                     Task::GetKeys(task::get_keys::Task::new(task::get_keys::Kind::LastUpdate(
@@ -259,23 +465,104 @@ impl Repo {
                 Ok(())
             }
             Message::IdbRequest(_) => Ok(()),
+            Message::EnclosureAnalyzed(mut meta, metadata) => {
+                let metadata = metadata?;
+
+                if let Some(image) = metadata.image {
+                    self.tasks.insert(
+                        0,
+                        Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                            None,
+                            task::put_get_with_key::Kind::Image,
+                            serde_wasm_bindgen::to_value(&meta.id)?,
+                            Some(serde_wasm_bindgen::to_value(&utils::data_url(
+                                &image.mime_type,
+                                &image.data,
+                            )?)?),
+                        )),
+                    );
+                    meta.image = true;
+                }
+
+                meta.duration = metadata.duration;
+                meta.title = metadata.title;
+                self.tasks.insert(
+                    0,
+                    Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                        None,
+                        task::put_get_with_key::Kind::EnclosureMeta,
+                        serde_wasm_bindgen::to_value(&meta.id)?,
+                        Some(serde_wasm_bindgen::to_value(&meta)?),
+                    )),
+                );
+
+                Ok(())
+            }
         }
     }
 
-    fn process_handle_input(&mut self, msg: Request, handler_id: HandlerId) -> Result<(), JsError> {
-        match msg {
-            Request::AddFeed(_) => {
-                todo!()
+    /// Applies the changes made by another tab: written records are sent to the subscribers; other values are read again, which updates the cache and the subscribers.
+    fn process_tab_message(&mut self, message: TabMessage) -> Result<(), JsError> {
+        match message {
+            TabMessage::FeedsChanged(feeds) => {
+                self.notify_subscribers(&feeds, Response::FeedsChanged)
+            }
+            TabMessage::ChannelsChanged(channels) => {
+                self.notify_subscribers(&channels, Response::ChannelsChanged)
+            }
+            TabMessage::ItemsChanged(items) => {
+                self.notify_subscribers(&items, Response::ItemsChanged)
             }
-            Request::GetChannel(channel_id) => self.tasks.insert(
+            TabMessage::ConfigurationChanged(key) => self.tasks.insert(
                 0,
                 Task::PutGetWithKey(task::put_get_with_key::Task::new(
-                    Some(handler_id),
-                    task::put_get_with_key::Kind::Channel,
-                    serde_wasm_bindgen::to_value(&channel_id)?,
+                    None,
+                    task::put_get_with_key::Kind::Configuration,
+                    serde_wasm_bindgen::to_value(&key)?,
                     None,
                 )),
             ),
+            TabMessage::BookmarkChanged(id) => self.tasks.insert(
+                0,
+                Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                    None,
+                    task::put_get_with_key::Kind::Bookmark,
+                    serde_wasm_bindgen::to_value(&id)?,
+                    None,
+                )),
+            ),
+            TabMessage::BookmarkDeleted(id) => {
+                for subscriber in &self.subscribers {
+                    if subscriber.is_respondable() {
+                        self.link
+                            .respond(*subscriber, Response::BookmarkDeleted(id));
+                    }
+                }
+            }
+            // handled by the player and the queue
+            TabMessage::Playing | TabMessage::QueueChanged(_) => {}
+        }
+
+        Ok(())
+    }
+
+    fn process_handle_input(&mut self, msg: Request, handler_id: HandlerId) -> Result<(), JsError> {
+        match msg {
+            Request::AddFeed(_) => {
+                todo!()
+            }
+            Request::GetChannel(channel_id) => match self.cache.channel(&channel_id) {
+                Some(channel) => self.link.respond(handler_id, Response::Channel(channel)),
+                None => self.tasks.insert(
+                    0,
+                    Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                        Some(handler_id),
+                        task::put_get_with_key::Kind::Channel,
+                        serde_wasm_bindgen::to_value(&channel_id)?,
+                        None,
+                    )),
+                ),
+            },
             Request::GetYearMonthKeysByChannelId(channel_id) => self.tasks.insert(
                 0,
                 Task::GetKeys(task::get_keys::Task::new(
@@ -287,10 +574,14 @@ impl Repo {
             ),
             Request::GetEnclosure(id) => self.tasks.insert(
                 0,
-                Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                Task::GetEnclosure(task::get_enclosure::Task::new(handler_id, id)),
+            ),
+            Request::GetEnclosureChunks(id) => self.tasks.insert(
+                0,
+                Task::GetAll(task::get_all::Task::new(
                     Some(handler_id),
-                    task::put_get_with_key::Kind::Enclosure,
-                    serde_wasm_bindgen::to_value(&id)?,
+                    task::get_all::Kind::EnclosureChunks(id),
+                    Some(task::enclosure_chunks_range(&id)?.into()),
                     None,
                 )),
             ),
@@ -298,75 +589,205 @@ impl Repo {
                 0,
                 Task::DeleteEnclosure(task::delete_enclosure::Task::new(item)),
             ),
-            Request::GetItemsByDownloadOk => self.tasks.insert(
+            Request::Subscribe(query) => {
+                if !self.subscribe_cached(handler_id, &query) {
+                    self.tasks.insert(
+                        0,
+                        Task::GetAll(task::get_all::Task::for_query(handler_id, query)?),
+                    )
+                }
+            }
+            Request::Unsubscribe(query) => self.subscriptions.unsubscribe(handler_id, &query),
+            Request::GetConsistencyReport => self.link.respond(
+                handler_id,
+                Response::ConsistencyReport(self.consistency_report.clone()),
+            ),
+            Request::GetUpdaterConf(value) => {
+                self.process_configuration(handler_id, "updater", value, Response::UpdaterConfig)?
+            }
+            Request::GetPlayerConf(value) => {
+                self.process_configuration(handler_id, "player", value, Response::PlayerConfig)?
+            }
+            Request::GetPlayerState(value) => self.process_configuration(
+                handler_id,
+                "player-state",
+                value,
+                Response::PlayerState,
+            )?,
+            Request::GetQueue => self.tasks.insert(
                 0,
-                Task::GetAll(task::get_all::Task::new(
+                Task::PutGetWithKey(task::put_get_with_key::Task::new(
                     Some(handler_id),
-                    task::get_all::Kind::Item,
-                    Some(serde_wasm_bindgen::to_value(&vec![String::from("true")])?),
-                    Some(String::from("download_ok")),
+                    task::put_get_with_key::Kind::Queue,
+                    serde_wasm_bindgen::to_value("queue")?,
+                    None,
                 )),
             ),
-            Request::GetUpdaterConf(value) => self.tasks.insert(
+            Request::UpdateQueue(value) => self.tasks.insert(
                 0,
                 Task::PutGetWithKey(task::put_get_with_key::Task::new(
-                    None,
-                    task::put_get_with_key::Kind::Configuration,
-                    serde_wasm_bindgen::to_value("updater")?,
-                    match &value {
-                        Some(value) => Some(serde_wasm_bindgen::to_value(value)?),
-                        None => None,
-                    },
+                    Some(handler_id),
+                    task::put_get_with_key::Kind::Queue,
+                    serde_wasm_bindgen::to_value("queue")?,
+                    Some(serde_wasm_bindgen::to_value(&value)?),
                 )),
             ),
-            Request::UpdateItem(value) => self.tasks.insert(
+            Request::GetChapters(item_id) => self.tasks.insert(
                 0,
                 Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                    Some(handler_id),
+                    task::put_get_with_key::Kind::Chapters,
+                    serde_wasm_bindgen::to_value(&item_id)?,
                     None,
-                    task::put_get_with_key::Kind::Item,
-                    serde_wasm_bindgen::to_value(&value.get_id())?,
+                )),
+            ),
+            Request::UpdateChapters(value) => self.tasks.insert(
+                0,
+                Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                    Some(handler_id),
+                    task::put_get_with_key::Kind::Chapters,
+                    serde_wasm_bindgen::to_value(&value.id)?,
                     Some(serde_wasm_bindgen::to_value(&value)?),
                 )),
             ),
-            Request::UpdateChannel(value) => self.tasks.insert(
+            Request::GetEnclosureMetas(item_ids) => {
+                for item_id in item_ids {
+                    self.tasks.insert(
+                        0,
+                        Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                            Some(handler_id),
+                            task::put_get_with_key::Kind::EnclosureMeta,
+                            serde_wasm_bindgen::to_value(&item_id)?,
+                            None,
+                        )),
+                    );
+                }
+            }
+            Request::GetImage(item_id) => self.tasks.insert(
                 0,
                 Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                    Some(handler_id),
+                    task::put_get_with_key::Kind::Image,
+                    serde_wasm_bindgen::to_value(&item_id)?,
                     None,
-                    task::put_get_with_key::Kind::Channel,
-                    serde_wasm_bindgen::to_value(&value.val.id)?,
-                    Some(serde_wasm_bindgen::to_value(&value)?),
                 )),
             ),
-            Request::GetFeeds => self.tasks.insert(
+            Request::GetHistory => self.tasks.insert(
                 0,
                 Task::GetAll(task::get_all::Task::new(
                     Some(handler_id),
-                    task::get_all::Kind::Feed,
+                    task::get_all::Kind::History,
+                    None,
                     None,
+                )),
+            ),
+            Request::AddListeningSession(value) => self.tasks.insert(
+                0,
+                Task::PutGetWithKey(task::put_get_with_key::Task::new(
                     None,
+                    task::put_get_with_key::Kind::ListeningSession,
+                    serde_wasm_bindgen::to_value(&value.id)?,
+                    Some(serde_wasm_bindgen::to_value(&value)?),
                 )),
             ),
-            Request::GetChannels => self.tasks.insert(
+            Request::GetBookmarks => self.tasks.insert(
                 0,
                 Task::GetAll(task::get_all::Task::new(
                     Some(handler_id),
-                    task::get_all::Kind::Channel,
+                    task::get_all::Kind::Bookmarks,
                     None,
                     None,
                 )),
             ),
-            Request::GetItemsByChannelIdYearMonth(channel_id, year_month) => self.tasks.insert(
+            Request::UpdateBookmark(value) => self.tasks.insert(
                 0,
-                Task::GetAll(task::get_all::Task::new(
-                    Some(handler_id),
-                    task::get_all::Kind::Item,
-                    Some(serde_wasm_bindgen::to_value(&vec![
-                        channel_id.to_string(),
-                        year_month,
-                    ])?),
-                    Some("channel_id_year_month".into()),
+                Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                    None,
+                    task::put_get_with_key::Kind::Bookmark,
+                    serde_wasm_bindgen::to_value(&value.id)?,
+                    Some(serde_wasm_bindgen::to_value(&value)?),
                 )),
             ),
+            Request::DeleteBookmark(id) => self.tasks.insert(
+                0,
+                Task::DeleteWithKey(task::delete_with_key::Task::new(
+                    task::delete_with_key::Kind::Bookmark,
+                    serde_wasm_bindgen::to_value(&id)?,
+                )),
+            ),
+            Request::SetEnclosureError(mut item, error) => {
+                // the enclosure has to be downloaded again, which replaces the metadata
+                let meta = EnclosureMeta {
+                    id: item.get_id(),
+                    mime_type: None,
+                    duration: None,
+                    title: None,
+                    image: false,
+                    error: Some(error),
+                };
+
+                item.set_download_status(DownloadStatus::Error);
+                self.tasks.insert(
+                    0,
+                    Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                        None,
+                        task::put_get_with_key::Kind::EnclosureMeta,
+                        serde_wasm_bindgen::to_value(&meta.id)?,
+                        Some(serde_wasm_bindgen::to_value(&meta)?),
+                    )),
+                );
+                self.tasks.insert(
+                    0,
+                    Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                        None,
+                        task::put_get_with_key::Kind::Item,
+                        serde_wasm_bindgen::to_value(&item.get_id())?,
+                        Some(serde_wasm_bindgen::to_value(&item)?),
+                    )),
+                );
+            }
+            Request::UpdateItem(value) => self.tasks.insert(
+                0,
+                Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                    None,
+                    task::put_get_with_key::Kind::Item,
+                    serde_wasm_bindgen::to_value(&value.get_id())?,
+                    Some(serde_wasm_bindgen::to_value(&value)?),
+                )),
+            ),
+            Request::UpdateChannel(value) => self.tasks.insert(
+                0,
+                Task::PutGetWithKey(task::put_get_with_key::Task::new(
+                    None,
+                    task::put_get_with_key::Kind::Channel,
+                    serde_wasm_bindgen::to_value(&value.val.id)?,
+                    Some(serde_wasm_bindgen::to_value(&value)?),
+                )),
+            ),
+            Request::GetFeeds => match self.cache.feeds() {
+                Some(feeds) => self.link.respond(handler_id, Response::Feeds(feeds)),
+                None => self.tasks.insert(
+                    0,
+                    Task::GetAll(task::get_all::Task::new(
+                        Some(handler_id),
+                        task::get_all::Kind::Feed,
+                        None,
+                        None,
+                    )),
+                ),
+            },
+            Request::GetChannels => match self.cache.channels() {
+                Some(channels) => self.link.respond(handler_id, Response::Channels(channels)),
+                None => self.tasks.insert(
+                    0,
+                    Task::GetAll(task::get_all::Task::new(
+                        Some(handler_id),
+                        task::get_all::Kind::Channel,
+                        None,
+                        None,
+                    )),
+                ),
+            },
             Request::GetItemsByDownloadRequired => self.tasks.insert(
                 0,
                 Task::GetAll(task::get_all::Task::new(
@@ -433,11 +854,20 @@ impl Agent for Repo {
             Err(e) => notifier.send(notifier::Request::NotifyError(e)),
         }
 
+        let mut tabs = tabs::Tabs::bridge(link.callback(Message::TabsMessage));
+
+        tabs.send(tabs::Request::GetLeadership);
+
         let mut obj = Self {
             link,
             subscribers: HashSet::new(),
+            subscriptions: Subscriptions::default(),
+            cache: Cache::default(),
             db: None,
+            consistency_report: None,
             fetcher: fetcher::Fetcher::bridge(fetcher_cb),
+            tabs,
+            leader: false,
             notifier,
             tasks: Vec::new(),
             idb_closure_error,
@@ -449,6 +879,15 @@ impl Agent for Repo {
         };
 
         obj.tasks.insert(0, Task::OpenDb(open_db::Task::new()));
+        // fill the cache
+        obj.tasks.insert(
+            0,
+            Task::GetAll(get_all::Task::new(None, get_all::Kind::Channel, None, None)),
+        );
+        obj.tasks.insert(
+            0,
+            Task::GetAll(get_all::Task::new(None, get_all::Kind::Feed, None, None)),
+        );
         obj.process_tasks();
         obj
     }
@@ -479,5 +918,6 @@ impl Agent for Repo {
 
     fn disconnected(&mut self, id: HandlerId) {
         self.subscribers.remove(&id);
+        self.subscriptions.remove_handler(id);
     }
 }
@@ -0,0 +1,99 @@
+use crate::{
+    agents::tabs::TabMessage,
+    objects::{Channel, FeedVal, Item},
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+use wasm_bindgen::JsValue;
+
+/// # Cache
+///
+/// Write-through cache of the channels, feeds, and configuration values.
+/// Channels and feeds are loaded completely once the database is opened; configuration values are cached on first access.
+/// The cache is kept up to date by the repository's own writes and the changes announced by other tabs.
+#[derive(Debug, Default)]
+pub struct Cache {
+    channels: Option<HashMap<Uuid, Channel>>,
+    feeds: Option<HashMap<Uuid, FeedVal>>,
+    configuration: HashMap<String, JsValue>,
+}
+
+impl Cache {
+    pub fn channels(&self) -> Option<Vec<Channel>> {
+        self.channels
+            .as_ref()
+            .map(|channels| channels.values().cloned().collect())
+    }
+
+    pub fn channel(&self, id: &Uuid) -> Option<Channel> {
+        self.channels
+            .as_ref()
+            .and_then(|channels| channels.get(id).cloned())
+    }
+
+    pub fn set_channels(&mut self, channels: &[Channel]) {
+        self.channels = Some(channels.iter().map(|c| (c.val.id, c.clone())).collect());
+    }
+
+    pub fn feeds(&self) -> Option<Vec<FeedVal>> {
+        self.feeds
+            .as_ref()
+            .map(|feeds| feeds.values().cloned().collect())
+    }
+
+    pub fn set_feeds(&mut self, feeds: &[FeedVal]) {
+        self.feeds = Some(feeds.iter().map(|f| (f.id, f.clone())).collect());
+    }
+
+    /// Returns the cached value for the key; `None` if the key has not been cached yet.
+    /// Keys without a value in the database are cached as `undefined`.
+    pub fn configuration(&self, key: &str) -> Option<JsValue> {
+        self.configuration.get(key).cloned()
+    }
+
+    pub fn set_configuration(&mut self, key: &str, value: JsValue) {
+        self.configuration.insert(key.into(), value);
+    }
+}
+
+/// Records the cache has to be updated for when they are written.
+pub trait Cached: Sized {
+    fn update_cache(_cache: &mut Cache, _records: &[Self]) {}
+
+    /// Message announcing the written records to the other tabs.
+    fn changed(records: Vec<Self>) -> TabMessage;
+}
+
+impl Cached for Item {
+    fn changed(records: Vec<Self>) -> TabMessage {
+        TabMessage::ItemsChanged(records)
+    }
+}
+
+impl Cached for Channel {
+    fn update_cache(cache: &mut Cache, records: &[Self]) {
+        if let Some(channels) = &mut cache.channels {
+            for channel in records {
+                channels.insert(channel.val.id, channel.clone());
+            }
+        }
+    }
+
+    fn changed(records: Vec<Self>) -> TabMessage {
+        TabMessage::ChannelsChanged(records)
+    }
+}
+
+impl Cached for FeedVal {
+    fn update_cache(cache: &mut Cache, records: &[Self]) {
+        if let Some(feeds) = &mut cache.feeds {
+            for feed in records {
+                feeds.insert(feed.id, feed.clone());
+            }
+        }
+    }
+
+    fn changed(records: Vec<Self>) -> TabMessage {
+        TabMessage::FeedsChanged(records)
+    }
+}
@@ -0,0 +1,161 @@
+use crate::objects::{Channel, DownloadStatus, FeedVal, Item};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use yew_agent::HandlerId;
+
+/// # Query
+///
+/// A query a subscriber can register with the repository.
+/// After the initial result set, the subscriber receives incremental diffs for all records that enter, change within, or leave the result set.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Query {
+    Feeds,
+    Channels,
+    Channel(Uuid),
+    Item(Uuid),
+    ItemsByChannelIdYearMonth(Uuid, String),
+    ItemsByDownloadOk,
+}
+
+#[derive(Debug, Clone)]
+pub enum Diff<T> {
+    Added(T),
+    Updated(T),
+    Removed(Uuid),
+}
+
+pub trait Record: Clone {
+    fn record_id(&self) -> Uuid;
+    fn matches(&self, query: &Query) -> bool;
+}
+
+impl Record for Item {
+    fn record_id(&self) -> Uuid {
+        self.get_id()
+    }
+
+    fn matches(&self, query: &Query) -> bool {
+        match query {
+            Query::Item(id) => self.get_id() == *id,
+            Query::ItemsByChannelIdYearMonth(channel_id, year_month) => {
+                self.get_channel_id() == channel_id
+                    // the same key the index of the initial result is built from
+                    && &self.get_year_month_key() == year_month
+            }
+            Query::ItemsByDownloadOk => matches!(self.get_download_status(), DownloadStatus::Ok),
+            _ => false,
+        }
+    }
+}
+
+impl Record for Channel {
+    fn record_id(&self) -> Uuid {
+        self.val.id
+    }
+
+    fn matches(&self, query: &Query) -> bool {
+        match query {
+            Query::Channels => true,
+            Query::Channel(id) => self.val.id == *id,
+            _ => false,
+        }
+    }
+}
+
+impl Record for FeedVal {
+    fn record_id(&self) -> Uuid {
+        self.id
+    }
+
+    fn matches(&self, query: &Query) -> bool {
+        matches!(query, Query::Feeds)
+    }
+}
+
+/// Applies a list of diffs to a locally held result set.
+pub fn apply_diffs<T: Record>(records: &mut Vec<T>, diffs: Vec<Diff<T>>) {
+    for diff in diffs {
+        match diff {
+            Diff::Added(record) | Diff::Updated(record) => {
+                match records
+                    .iter_mut()
+                    .find(|r| r.record_id() == record.record_id())
+                {
+                    Some(existing) => *existing = record,
+                    None => records.push(record),
+                }
+            }
+            Diff::Removed(id) => records.retain(|r| r.record_id() != id),
+        }
+    }
+}
+
+/// Keeps track of the registered queries and the ids of the records currently matching them.
+#[derive(Debug, Default)]
+pub struct Subscriptions {
+    queries: HashMap<HandlerId, HashMap<Query, HashSet<Uuid>>>,
+}
+
+impl Subscriptions {
+    pub fn subscribe<T: Record>(
+        &mut self,
+        handler_id: HandlerId,
+        query: Query,
+        records: &[T],
+    ) -> Vec<Diff<T>> {
+        let records: Vec<&T> = records.iter().filter(|r| r.matches(&query)).collect();
+
+        self.queries
+            .entry(handler_id)
+            .or_default()
+            .insert(query, records.iter().map(|r| r.record_id()).collect());
+
+        records.into_iter().cloned().map(Diff::Added).collect()
+    }
+
+    pub fn unsubscribe(&mut self, handler_id: HandlerId, query: &Query) {
+        if let Some(queries) = self.queries.get_mut(&handler_id) {
+            queries.remove(query);
+        }
+    }
+
+    pub fn remove_handler(&mut self, handler_id: HandlerId) {
+        self.queries.remove(&handler_id);
+    }
+
+    /// Computes the diffs the given (changed) records cause for each registered query.
+    /// Queries that are not affected are omitted.
+    pub fn changes<T: Record>(&mut self, records: &[T]) -> Vec<(HandlerId, Query, Vec<Diff<T>>)> {
+        let mut res = Vec::new();
+
+        for (handler_id, queries) in &mut self.queries {
+            for (query, ids) in queries.iter_mut() {
+                let mut diffs = Vec::new();
+
+                for record in records {
+                    let id = record.record_id();
+
+                    match (ids.contains(&id), record.matches(query)) {
+                        (true, true) => diffs.push(Diff::Updated(record.clone())),
+                        (false, true) => {
+                            ids.insert(id);
+                            diffs.push(Diff::Added(record.clone()));
+                        }
+                        (true, false) => {
+                            ids.remove(&id);
+                            diffs.push(Diff::Removed(id));
+                        }
+                        (false, false) => {}
+                    }
+                }
+
+                if !diffs.is_empty() {
+                    res.push((*handler_id, query.clone(), diffs));
+                }
+            }
+        }
+
+        res
+    }
+}
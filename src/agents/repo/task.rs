@@ -1,13 +1,18 @@
 use crate::objects::JsError;
+pub mod check_consistency;
 pub mod delete_enclosure;
+pub mod delete_with_key;
 pub mod download_started;
 pub mod get_all;
+pub mod get_enclosure;
 pub mod get_keys;
 pub mod open_db;
 pub mod put_get_with_key;
 pub mod store_enclosure;
+pub mod store_enclosure_chunk;
 pub mod sync_val;
-use web_sys::{IdbRequest, IdbTransaction};
+use uuid::Uuid;
+use web_sys::{IdbKeyRange, IdbRequest, IdbTransaction};
 
 #[derive(Debug)]
 pub enum Task {
@@ -15,10 +20,14 @@ pub enum Task {
     GetAll(get_all::Task),
     PutGetWithKey(put_get_with_key::Task),
     StoreEnclosure(store_enclosure::Task),
+    StoreEnclosureChunk(store_enclosure_chunk::Task),
     DownloadStarted(download_started::Task),
     DeleteEnclosure(delete_enclosure::Task),
+    DeleteWithKey(delete_with_key::Task),
     SyncVal(sync_val::Task),
     GetKeys(get_keys::Task),
+    CheckConsistency(check_consistency::Task),
+    GetEnclosure(get_enclosure::Task),
 }
 
 impl Task {
@@ -27,10 +36,14 @@ impl Task {
             Task::GetAll(task) => task.transaction_complete(),
             Task::PutGetWithKey(task) => task.transaction_complete(),
             Task::StoreEnclosure(task) => task.transaction_complete(),
+            Task::StoreEnclosureChunk(task) => task.transaction_complete(),
             Task::DownloadStarted(task) => task.transaction_complete(),
             Task::DeleteEnclosure(task) => task.transaction_complete(),
+            Task::DeleteWithKey(task) => task.transaction_complete(),
             Task::SyncVal(task) => task.transaction_complete(),
             Task::GetKeys(task) => task.transaction_complete(),
+            Task::CheckConsistency(task) => task.transaction_complete(),
+            Task::GetEnclosure(task) => task.transaction_complete(),
             Task::OpenDb(_) => {}
         }
     }
@@ -46,6 +59,14 @@ fn request_ok(request: &IdbRequest) -> Result<(), JsError> {
         .map_or(Ok::<(), JsError>(()), |e| Err(e.into()))
 }
 
+/// Key range of the chunks of an enclosure (key: [item id, index]).
+pub fn enclosure_chunks_range(item_id: &Uuid) -> Result<IdbKeyRange, JsError> {
+    Ok(IdbKeyRange::bound(
+        &serde_wasm_bindgen::to_value(&(item_id, 0u32))?,
+        &serde_wasm_bindgen::to_value(&(item_id, u32::MAX))?,
+    )?)
+}
+
 fn transaction_ok(transaction: &IdbTransaction) -> Result<(), JsError> {
     transaction
         .error()
@@ -0,0 +1,315 @@
+use crate::{
+    agents::{
+        repo::Response,
+        tabs::{self, TabMessage},
+    },
+    objects::{ConsistencyReport, JsError, PlayerConfig, Repair},
+};
+use podcast_player_common::Item;
+use std::collections::HashSet;
+use uuid::Uuid;
+use wasm_bindgen::JsCast;
+use web_sys::{IdbRequest, IdbRequestReadyState, IdbTransaction};
+
+/// # Check Consistency Task
+///
+/// Reconciles the items with the enclosures store, when the tab becomes the leader (i.e., before it starts downloading).
+///
+/// * downloads left in progress (e.g., the app was closed mid-download) are reset to pending
+/// * download states disagreeing with the enclosures store are fixed; enclosures marked as corrupt keep their error until downloaded again
+/// * enclosures (and their metadata) without an item or of removed downloads are deleted
+/// * chunks of interrupted downloads are deleted
+/// * per-channel player settings of channels no longer stored are deleted
+///
+/// ## Stages and Transitions
+///
+/// * Init (S)
+/// * WaitingForReadRequests (S)
+/// * WaitingForWriteRequests (S)
+/// * WaitingForTransaction (S)
+/// * transaction_complete (T)
+/// * TransactionCompleted (S)
+#[derive(Debug)]
+pub struct Task {
+    stage: Stage,
+    items_request: Option<IdbRequest>,
+    keys_request: Option<IdbRequest>,
+    channel_keys_request: Option<IdbRequest>,
+    config_request: Option<IdbRequest>,
+    write_requests: Vec<IdbRequest>,
+    transaction: Option<IdbTransaction>,
+    items: Vec<Item>,
+    player_config: Option<PlayerConfig>,
+    report: ConsistencyReport,
+}
+
+#[derive(Debug)]
+enum Stage {
+    Init,
+    WaitingForReadRequests,
+    WaitingForWriteRequests,
+    WaitingForTransaction,
+    TransactionCompleted,
+}
+
+impl Task {
+    pub fn new() -> Self {
+        Self {
+            stage: Stage::Init,
+            items_request: None,
+            keys_request: None,
+            channel_keys_request: None,
+            config_request: None,
+            write_requests: Vec::new(),
+            transaction: None,
+            items: Vec::new(),
+            player_config: None,
+            report: ConsistencyReport::default(),
+        }
+    }
+
+    pub fn transaction_complete(&mut self) {
+        self.stage = Stage::TransactionCompleted;
+    }
+}
+
+impl super::TaskProcessor<Task> for super::super::Repo {
+    fn process(&mut self, task: &mut Task) -> Result<bool, JsError> {
+        match task.stage {
+            Stage::Init => {
+                let db = self.db.as_ref().ok_or("db not set")?;
+                let trans = db.transaction_with_str_sequence_and_mode(
+                    &serde_wasm_bindgen::to_value(&vec![
+                        "items",
+                        "enclosures",
+                        "enclosures-meta",
+                        "enclosure-chunks",
+                        "channels",
+                        "configuration",
+                    ])?,
+                    web_sys::IdbTransactionMode::Readwrite,
+                )?;
+                let items_request = trans.object_store("items")?.get_all()?;
+                let keys_request = trans.object_store("enclosures")?.get_all_keys()?;
+                let channel_keys_request = trans.object_store("channels")?.get_all_keys()?;
+                let config_request = trans
+                    .object_store("configuration")?
+                    .get(&serde_wasm_bindgen::to_value("player")?)?;
+                // only the leader tab runs downloads and it checks the consistency before starting any
+                let chunks_request = trans.object_store("enclosure-chunks")?.clear()?;
+
+                trans.set_onabort(Some(self.idb_closure_trans_abort.as_ref().unchecked_ref()));
+                trans.set_onerror(Some(self.idb_closure_trans_error.as_ref().unchecked_ref()));
+                trans.set_oncomplete(Some(
+                    self.idb_closure_trans_complete.as_ref().unchecked_ref(),
+                ));
+                task.transaction = Some(trans);
+
+                items_request
+                    .set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                items_request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.items_request = Some(items_request);
+
+                keys_request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                keys_request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.keys_request = Some(keys_request);
+
+                channel_keys_request
+                    .set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                channel_keys_request
+                    .set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.channel_keys_request = Some(channel_keys_request);
+
+                config_request
+                    .set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                config_request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.config_request = Some(config_request);
+
+                chunks_request
+                    .set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                chunks_request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.write_requests.push(chunks_request);
+
+                task.stage = Stage::WaitingForReadRequests;
+                Ok(false)
+            }
+            Stage::WaitingForReadRequests => {
+                let items_request = task.items_request.as_ref().ok_or("items request not set")?;
+                let keys_request = task.keys_request.as_ref().ok_or("keys request not set")?;
+                let channel_keys_request = task
+                    .channel_keys_request
+                    .as_ref()
+                    .ok_or("channel keys request not set")?;
+                let config_request = task
+                    .config_request
+                    .as_ref()
+                    .ok_or("config request not set")?;
+                let read_requests = [
+                    items_request,
+                    keys_request,
+                    channel_keys_request,
+                    config_request,
+                ];
+
+                match read_requests
+                    .iter()
+                    .all(|r| r.ready_state() == IdbRequestReadyState::Done)
+                {
+                    true => {
+                        for request in read_requests {
+                            super::request_ok(request)?;
+                        }
+
+                        let items: Vec<Item> =
+                            serde_wasm_bindgen::from_value(items_request.result()?)?;
+                        let enclosure_ids: HashSet<Uuid> =
+                            serde_wasm_bindgen::from_value(keys_request.result()?)?;
+                        let item_ids: HashSet<Uuid> = items.iter().map(|i| i.get_id()).collect();
+                        let trans = task.transaction.as_ref().ok_or("transaction not set")?;
+                        let items_os = trans.object_store("items")?;
+                        let enclosures_os = trans.object_store("enclosures")?;
+                        let enclosures_meta_os = trans.object_store("enclosures-meta")?;
+                        let mut obsolete_ids: Vec<Uuid> =
+                            enclosure_ids.difference(&item_ids).cloned().collect();
+
+                        task.report.orphaned_enclosures_deleted += obsolete_ids.len() as u32;
+
+                        for mut item in items {
+                            let has_enclosure = enclosure_ids.contains(&item.get_id());
+
+                            match task
+                                .report
+                                .repair(&item.get_download_status(), has_enclosure)
+                            {
+                                Some(Repair::SetStatus(status)) => item.set_download_status(status),
+                                Some(Repair::DeleteEnclosure) => {
+                                    obsolete_ids.push(item.get_id());
+                                    continue;
+                                }
+                                None => continue,
+                            }
+
+                            let request = items_os.put_with_key(
+                                &serde_wasm_bindgen::to_value(&item)?,
+                                &serde_wasm_bindgen::to_value(&item.get_id())?,
+                            )?;
+
+                            request.set_onsuccess(Some(
+                                self.idb_closure_success.as_ref().unchecked_ref(),
+                            ));
+                            request
+                                .set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                            task.write_requests.push(request);
+                            task.items.push(item);
+                        }
+
+                        for id in &obsolete_ids {
+                            for os in [&enclosures_os, &enclosures_meta_os] {
+                                let request = os.delete(&serde_wasm_bindgen::to_value(id)?)?;
+
+                                request.set_onsuccess(Some(
+                                    self.idb_closure_success.as_ref().unchecked_ref(),
+                                ));
+                                request.set_onerror(Some(
+                                    self.idb_closure_error.as_ref().unchecked_ref(),
+                                ));
+                                task.write_requests.push(request);
+                            }
+                        }
+
+                        let channel_ids: HashSet<Uuid> =
+                            serde_wasm_bindgen::from_value(channel_keys_request.result()?)?;
+                        let player_config: Option<PlayerConfig> =
+                            serde_wasm_bindgen::from_value(config_request.result()?)?;
+
+                        if let Some(mut player_config) = player_config {
+                            let deleted = player_config.retain_channels(&channel_ids);
+
+                            if deleted > 0 {
+                                let request = trans.object_store("configuration")?.put_with_key(
+                                    &serde_wasm_bindgen::to_value(&player_config)?,
+                                    &serde_wasm_bindgen::to_value("player")?,
+                                )?;
+
+                                request.set_onsuccess(Some(
+                                    self.idb_closure_success.as_ref().unchecked_ref(),
+                                ));
+                                request.set_onerror(Some(
+                                    self.idb_closure_error.as_ref().unchecked_ref(),
+                                ));
+                                task.write_requests.push(request);
+                                task.report.channel_settings_deleted += deleted;
+                                task.player_config = Some(player_config);
+                            }
+                        }
+
+                        task.stage = match task.write_requests.is_empty() {
+                            true => Stage::WaitingForTransaction,
+                            false => Stage::WaitingForWriteRequests,
+                        };
+                    }
+                    false => {}
+                }
+
+                Ok(false)
+            }
+            Stage::WaitingForWriteRequests => {
+                if task
+                    .write_requests
+                    .iter()
+                    .all(|r| r.ready_state() == IdbRequestReadyState::Done)
+                {
+                    for request in &task.write_requests {
+                        super::request_ok(request)?;
+                    }
+
+                    task.stage = Stage::WaitingForTransaction;
+                }
+
+                Ok(false)
+            }
+            Stage::WaitingForTransaction => Ok(false),
+            Stage::TransactionCompleted => {
+                super::transaction_ok(task.transaction.as_ref().ok_or("transaction not set")?)?;
+
+                if !task.report.is_empty() {
+                    log::info!("database repaired: {:?}", task.report);
+                }
+
+                self.notify(&task.items, Response::ItemsChanged);
+
+                if let Some(player_config) = task.player_config.take() {
+                    self.cache
+                        .set_configuration("player", serde_wasm_bindgen::to_value(&player_config)?);
+                    // the other tabs read the written configuration again
+                    self.tabs
+                        .send(tabs::Request::Broadcast(TabMessage::ConfigurationChanged(
+                            "player".to_string(),
+                        )));
+
+                    for subscriber in &self.subscribers {
+                        if subscriber.is_respondable() {
+                            self.link.respond(
+                                *subscriber,
+                                Response::PlayerConfig(Some(player_config.clone())),
+                            );
+                        }
+                    }
+                }
+
+                self.consistency_report = Some(task.report.clone());
+
+                for subscriber in &self.subscribers {
+                    if subscriber.is_respondable() {
+                        self.link.respond(
+                            *subscriber,
+                            Response::ConsistencyReport(Some(task.report.clone())),
+                        );
+                    }
+                }
+
+                Ok(true)
+            }
+        }
+    }
+}
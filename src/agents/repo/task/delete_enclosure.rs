@@ -8,6 +8,8 @@ pub struct Task {
     stage: Stage,
     item: Item,
     enclosure_request: Option<web_sys::IdbRequest>,
+    meta_request: Option<web_sys::IdbRequest>,
+    image_request: Option<web_sys::IdbRequest>,
     item_request: Option<web_sys::IdbRequest>,
     transaction: Option<IdbTransaction>,
 }
@@ -25,6 +27,8 @@ impl Task {
         Self {
             stage: Stage::Init,
             enclosure_request: None,
+            meta_request: None,
+            image_request: None,
             item_request: None,
             item,
             transaction: None,
@@ -42,12 +46,23 @@ impl super::TaskProcessor<Task> for super::super::Repo {
             Stage::Init => {
                 let db = self.db.as_ref().ok_or("db not set")?;
                 let trans = db.transaction_with_str_sequence_and_mode(
-                    &serde_wasm_bindgen::to_value(&vec!["items", "enclosures"])?,
+                    &serde_wasm_bindgen::to_value(&vec![
+                        "items",
+                        "enclosures",
+                        "enclosures-meta",
+                        "images",
+                    ])?,
                     web_sys::IdbTransactionMode::Readwrite,
                 )?;
                 let enclosure_request = trans
                     .object_store("enclosures")?
                     .delete(&serde_wasm_bindgen::to_value(&task.item.get_id())?)?;
+                let meta_request = trans
+                    .object_store("enclosures-meta")?
+                    .delete(&serde_wasm_bindgen::to_value(&task.item.get_id())?)?;
+                let image_request = trans
+                    .object_store("images")?
+                    .delete(&serde_wasm_bindgen::to_value(&task.item.get_id())?)?;
                 task.item.set_download_status(DownloadStatus::NotRequested);
                 let item_request = trans.object_store("items")?.put_with_key(
                     &serde_wasm_bindgen::to_value(&task.item)?,
@@ -71,6 +86,15 @@ impl super::TaskProcessor<Task> for super::super::Repo {
                     .set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
                 task.enclosure_request = Some(enclosure_request);
 
+                meta_request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                meta_request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.meta_request = Some(meta_request);
+
+                image_request
+                    .set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                image_request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.image_request = Some(image_request);
+
                 task.stage = Stage::WaitingForRequest;
                 Ok(false)
             }
@@ -80,12 +104,18 @@ impl super::TaskProcessor<Task> for super::super::Repo {
                     .as_ref()
                     .ok_or("enclosure request not set")?;
 
+                let meta_request = task.meta_request.as_ref().ok_or("meta request not set")?;
+                let image_request = task.image_request.as_ref().ok_or("image request not set")?;
                 let item_request = task.item_request.as_ref().ok_or("item request not set")?;
 
                 if (enclosure_request.ready_state() == web_sys::IdbRequestReadyState::Done)
+                    & (meta_request.ready_state() == web_sys::IdbRequestReadyState::Done)
+                    & (image_request.ready_state() == web_sys::IdbRequestReadyState::Done)
                     & (item_request.ready_state() == web_sys::IdbRequestReadyState::Done)
                 {
                     super::request_ok(enclosure_request)?;
+                    super::request_ok(meta_request)?;
+                    super::request_ok(image_request)?;
                     super::request_ok(item_request)?;
 
                     task.stage = Stage::WaitingForTransaction;
@@ -96,14 +126,7 @@ impl super::TaskProcessor<Task> for super::super::Repo {
             Stage::WaitingForTransaction => Ok(false),
             Stage::TransactionCompleted => {
                 super::transaction_ok(task.transaction.as_ref().ok_or("transaction not set")?)?;
-                let item = task.item.clone();
-
-                for subscriber in &self.subscribers {
-                    if subscriber.is_respondable() {
-                        self.link
-                            .respond(*subscriber, Response::UpdatedItem(item.clone()));
-                    }
-                }
+                self.notify(&[task.item.clone()], Response::ItemsChanged);
 
                 Ok(true)
             }
@@ -0,0 +1,122 @@
+use crate::{
+    agents::{
+        repo,
+        tabs::{self, TabMessage},
+    },
+    objects::JsError,
+};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::IdbTransaction;
+
+#[derive(Debug)]
+pub struct Task {
+    stage: Stage,
+    kind: Kind,
+    key: JsValue,
+    request: Option<web_sys::IdbRequest>,
+    transaction: Option<IdbTransaction>,
+}
+
+#[derive(Debug)]
+enum Stage {
+    Init,
+    WaitingForRequest,
+    WaitingForTransaction,
+    TransactionCompleted,
+}
+
+#[derive(Debug)]
+pub enum Kind {
+    Bookmark,
+}
+
+impl Kind {
+    fn table_name(&self) -> &str {
+        match &self {
+            Self::Bookmark => "bookmarks",
+        }
+    }
+}
+
+impl Task {
+    pub fn new(kind: Kind, key: JsValue) -> Self {
+        Self {
+            stage: Stage::Init,
+            kind,
+            key,
+            request: None,
+            transaction: None,
+        }
+    }
+
+    pub fn transaction_complete(&mut self) {
+        self.stage = Stage::TransactionCompleted;
+    }
+}
+
+impl super::TaskProcessor<Task> for super::super::Repo {
+    fn process(&mut self, task: &mut Task) -> Result<bool, JsError> {
+        match task.stage {
+            Stage::Init => {
+                let db = self.db.as_ref().ok_or("db not set")?;
+                let trans = db.transaction_with_str_sequence_and_mode(
+                    &serde_wasm_bindgen::to_value(&vec![task.kind.table_name()])?,
+                    web_sys::IdbTransactionMode::Readwrite,
+                )?;
+                let request = trans
+                    .object_store(task.kind.table_name())?
+                    .delete(&task.key)?;
+
+                trans.set_onabort(Some(self.idb_closure_trans_abort.as_ref().unchecked_ref()));
+                trans.set_onerror(Some(self.idb_closure_trans_error.as_ref().unchecked_ref()));
+                trans.set_oncomplete(Some(
+                    self.idb_closure_trans_complete.as_ref().unchecked_ref(),
+                ));
+                task.transaction = Some(trans);
+
+                request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.request = Some(request);
+
+                task.stage = Stage::WaitingForRequest;
+                Ok(false)
+            }
+            Stage::WaitingForRequest => {
+                let request = task.request.as_ref().ok_or("request not set")?;
+
+                if request.ready_state() == web_sys::IdbRequestReadyState::Done {
+                    super::request_ok(request)?;
+
+                    task.stage = Stage::WaitingForTransaction;
+                }
+
+                Ok(false)
+            }
+            Stage::WaitingForTransaction => Ok(false),
+            Stage::TransactionCompleted => {
+                super::transaction_ok(task.transaction.as_ref().ok_or("transaction not set")?)?;
+
+                let (response, message) = match task.kind {
+                    Kind::Bookmark => {
+                        let id = serde_wasm_bindgen::from_value(task.key.clone())?;
+
+                        (
+                            repo::Response::BookmarkDeleted(id),
+                            TabMessage::BookmarkDeleted(id),
+                        )
+                    }
+                };
+
+                self.tabs.send(tabs::Request::Broadcast(message));
+
+                for subscriber in &self.subscribers {
+                    if subscriber.is_respondable() {
+                        self.link.respond(*subscriber, response.clone());
+                    }
+                }
+
+                Ok(true)
+            }
+        }
+    }
+}
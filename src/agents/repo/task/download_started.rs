@@ -109,14 +109,9 @@ impl super::TaskProcessor<Task> for super::super::Repo {
             }
             Stage::WaitingForTransaction => Ok(false),
             Stage::TransactionCompleted => {
-                let response =
-                    repo::Response::UpdatedItem(task.item.as_ref().ok_or("item not set")?.clone());
+                let item = task.item.as_ref().ok_or("item not set")?;
 
-                for subscriber in &self.subscribers {
-                    if subscriber.is_respondable() {
-                        self.link.respond(*subscriber, response.clone());
-                    }
-                }
+                self.notify(&[item.clone()], repo::Response::ItemsChanged);
 
                 Ok(true)
             }
@@ -1,11 +1,16 @@
 use crate::{
-    agents::{fetcher, repo},
-    objects::JsError,
+    agents::{
+        fetcher,
+        repo::{self, Query},
+    },
+    objects::{Bookmark, JsError, ListeningSession},
 };
 
-use podcast_player_common::Item;
+use js_sys::Array;
+use podcast_player_common::{Channel, FeedVal, Item};
+use uuid::Uuid;
 use wasm_bindgen::{JsCast, JsValue};
-use web_sys::IdbTransaction;
+use web_sys::{Blob, IdbTransaction};
 use yew_agent::HandlerId;
 
 #[derive(Debug)]
@@ -17,6 +22,7 @@ pub struct Task {
     request: Option<web_sys::IdbRequest>,
     handler_id: Option<HandlerId>,
     transaction: Option<IdbTransaction>,
+    query: Option<Query>,
 }
 
 #[derive(Debug)]
@@ -33,6 +39,9 @@ pub enum Kind {
     Channel,
     Item,
     ItemDownloadRequired,
+    EnclosureChunks(Uuid),
+    History,
+    Bookmarks,
 }
 
 impl Kind {
@@ -42,6 +51,9 @@ impl Kind {
             Self::Feed => "feeds",
             Self::Channel => "channels",
             Self::ItemDownloadRequired => "items",
+            Self::EnclosureChunks(_) => "enclosure-chunks",
+            Self::History => "history",
+            Self::Bookmarks => "bookmarks",
         }
     }
 }
@@ -61,9 +73,37 @@ impl Task {
             kind,
             handler_id,
             transaction: None,
+            query: None,
         }
     }
 
+    /// Creates a task fetching the initial result set of a query and registering the subscription.
+    pub fn for_query(handler_id: HandlerId, query: Query) -> Result<Self, JsError> {
+        let (kind, key, index) = match &query {
+            Query::Feeds => (Kind::Feed, None, None),
+            Query::Channels => (Kind::Channel, None, None),
+            Query::Channel(id) => (Kind::Channel, Some(serde_wasm_bindgen::to_value(id)?), None),
+            Query::Item(id) => (Kind::Item, Some(serde_wasm_bindgen::to_value(id)?), None),
+            Query::ItemsByChannelIdYearMonth(channel_id, year_month) => (
+                Kind::Item,
+                Some(serde_wasm_bindgen::to_value(&vec![
+                    channel_id.to_string(),
+                    year_month.clone(),
+                ])?),
+                Some(String::from("channel_id_year_month")),
+            ),
+            Query::ItemsByDownloadOk => (
+                Kind::Item,
+                Some(serde_wasm_bindgen::to_value(&vec![String::from("true")])?),
+                Some(String::from("download_ok")),
+            ),
+        };
+        let mut task = Self::new(Some(handler_id), kind, key, index);
+
+        task.query = Some(query);
+        Ok(task)
+    }
+
     pub fn transaction_complete(&mut self) {
         self.stage = Stage::TransactionCompleted;
     }
@@ -119,26 +159,101 @@ impl super::TaskProcessor<Task> for super::super::Repo {
                 super::transaction_ok(task.transaction.as_ref().ok_or("transaction not set")?)?;
                 let result = task.request.as_ref().ok_or("request not set")?.result()?;
 
-                match &task.kind {
-                    Kind::Item => {
+                match (&task.kind, task.query.take()) {
+                    (Kind::Item, Some(query)) => {
+                        let handler_id = task.handler_id.ok_or("handler id not set")?;
+                        let items: Vec<Item> = serde_wasm_bindgen::from_value(result)?;
+                        let diffs = self
+                            .subscriptions
+                            .subscribe(handler_id, query.clone(), &items);
+
+                        self.link
+                            .respond(handler_id, repo::Response::ItemsChanged(query, diffs));
+                    }
+                    (Kind::Channel, Some(query)) => {
+                        let handler_id = task.handler_id.ok_or("handler id not set")?;
+                        let channels: Vec<Channel> = serde_wasm_bindgen::from_value(result)?;
+
+                        if query == Query::Channels {
+                            self.cache.set_channels(&channels);
+                        }
+
+                        let diffs =
+                            self.subscriptions
+                                .subscribe(handler_id, query.clone(), &channels);
+
+                        self.link
+                            .respond(handler_id, repo::Response::ChannelsChanged(query, diffs));
+                    }
+                    (Kind::Feed, Some(query)) => {
+                        let handler_id = task.handler_id.ok_or("handler id not set")?;
+                        let feeds: Vec<FeedVal> = serde_wasm_bindgen::from_value(result)?;
+
+                        self.cache.set_feeds(&feeds);
+
+                        let diffs = self
+                            .subscriptions
+                            .subscribe(handler_id, query.clone(), &feeds);
+
+                        self.link
+                            .respond(handler_id, repo::Response::FeedsChanged(query, diffs));
+                    }
+                    (Kind::Item, None) => {
                         self.link.respond(
                             task.handler_id.ok_or("handler id not set")?,
                             repo::Response::Items(serde_wasm_bindgen::from_value(result)?),
                         );
                     }
-                    Kind::Channel => {
+                    (Kind::Channel, None) => {
+                        let channels: Vec<Channel> = serde_wasm_bindgen::from_value(result)?;
+
+                        if task.key.is_none() && task.index.is_none() {
+                            self.cache.set_channels(&channels);
+                        }
+                        if let Some(handler_id) = task.handler_id {
+                            self.link
+                                .respond(handler_id, repo::Response::Channels(channels));
+                        }
+                    }
+                    (Kind::Feed, None) => {
+                        let feeds: Vec<FeedVal> = serde_wasm_bindgen::from_value(result)?;
+
+                        if task.key.is_none() && task.index.is_none() {
+                            self.cache.set_feeds(&feeds);
+                        }
+                        if let Some(handler_id) = task.handler_id {
+                            self.link.respond(handler_id, repo::Response::Feeds(feeds));
+                        }
+                    }
+                    (Kind::EnclosureChunks(item_id), _) => {
+                        let chunks = Array::from(&result)
+                            .iter()
+                            .map(|chunk| chunk.dyn_into::<Blob>())
+                            .collect::<Result<Vec<Blob>, _>>()?;
+
                         self.link.respond(
                             task.handler_id.ok_or("handler id not set")?,
-                            repo::Response::Channels(serde_wasm_bindgen::from_value(result)?),
+                            repo::Response::EnclosureChunks(*item_id, chunks),
                         );
                     }
-                    Kind::Feed => {
+                    (Kind::History, _) => {
+                        let sessions: Vec<ListeningSession> =
+                            serde_wasm_bindgen::from_value(result)?;
+
+                        self.link.respond(
+                            task.handler_id.ok_or("handler id not set")?,
+                            repo::Response::History(sessions),
+                        );
+                    }
+                    (Kind::Bookmarks, _) => {
+                        let bookmarks: Vec<Bookmark> = serde_wasm_bindgen::from_value(result)?;
+
                         self.link.respond(
                             task.handler_id.ok_or("handler id not set")?,
-                            repo::Response::Feeds(serde_wasm_bindgen::from_value(result)?),
+                            repo::Response::Bookmarks(bookmarks),
                         );
                     }
-                    Kind::ItemDownloadRequired => {
+                    (Kind::ItemDownloadRequired, _) => {
                         let items: Vec<Item> = serde_wasm_bindgen::from_value(result)?;
 
                         for item in items {
@@ -0,0 +1,144 @@
+use crate::{
+    agents::repo::Response,
+    objects::{EnclosureMeta, JsError},
+    utils,
+};
+use js_sys::ArrayBuffer;
+use uuid::Uuid;
+use wasm_bindgen::JsCast;
+use web_sys::{Blob, IdbRequest, IdbRequestReadyState, IdbTransaction};
+use yew_agent::HandlerId;
+
+/// # Get Enclosure Task
+///
+/// Reads the enclosure along with its metadata and returns it as a blob typed with its MIME type.
+/// Enclosures stored as buffers (i.e., before enclosures were stored as blobs) are wrapped into a blob.
+///
+/// ## Stages and Transitions
+///
+/// * Init (S)
+/// * WaitingForRequests (S)
+/// * WaitingForTransaction (S)
+/// * transaction_complete (T)
+/// * TransactionCompleted (S)
+#[derive(Debug)]
+pub struct Task {
+    stage: Stage,
+    handler_id: HandlerId,
+    item_id: Uuid,
+    data_request: Option<IdbRequest>,
+    meta_request: Option<IdbRequest>,
+    transaction: Option<IdbTransaction>,
+}
+
+#[derive(Debug)]
+enum Stage {
+    Init,
+    WaitingForRequests,
+    WaitingForTransaction,
+    TransactionCompleted,
+}
+
+impl Task {
+    pub fn new(handler_id: HandlerId, item_id: Uuid) -> Self {
+        Self {
+            stage: Stage::Init,
+            handler_id,
+            item_id,
+            data_request: None,
+            meta_request: None,
+            transaction: None,
+        }
+    }
+
+    pub fn transaction_complete(&mut self) {
+        self.stage = Stage::TransactionCompleted;
+    }
+}
+
+impl super::TaskProcessor<Task> for super::super::Repo {
+    fn process(&mut self, task: &mut Task) -> Result<bool, JsError> {
+        match task.stage {
+            Stage::Init => {
+                let db = self.db.as_ref().ok_or("db not set")?;
+                let trans = db.transaction_with_str_sequence_and_mode(
+                    &serde_wasm_bindgen::to_value(&vec!["enclosures", "enclosures-meta"])?,
+                    web_sys::IdbTransactionMode::Readonly,
+                )?;
+                let key = serde_wasm_bindgen::to_value(&task.item_id)?;
+                let data_request = trans.object_store("enclosures")?.get(&key)?;
+                let meta_request = trans.object_store("enclosures-meta")?.get(&key)?;
+
+                trans.set_onabort(Some(self.idb_closure_trans_abort.as_ref().unchecked_ref()));
+                trans.set_onerror(Some(self.idb_closure_trans_error.as_ref().unchecked_ref()));
+                trans.set_oncomplete(Some(
+                    self.idb_closure_trans_complete.as_ref().unchecked_ref(),
+                ));
+                task.transaction = Some(trans);
+
+                data_request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                data_request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.data_request = Some(data_request);
+
+                meta_request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                meta_request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.meta_request = Some(meta_request);
+
+                task.stage = Stage::WaitingForRequests;
+                Ok(false)
+            }
+            Stage::WaitingForRequests => {
+                let data_request = task.data_request.as_ref().ok_or("data request not set")?;
+                let meta_request = task.meta_request.as_ref().ok_or("meta request not set")?;
+
+                match (data_request.ready_state(), meta_request.ready_state()) {
+                    (IdbRequestReadyState::Done, IdbRequestReadyState::Done) => {
+                        super::request_ok(data_request)?;
+                        super::request_ok(meta_request)?;
+                        task.stage = Stage::WaitingForTransaction;
+                    }
+                    (_, _) => {}
+                }
+
+                Ok(false)
+            }
+            Stage::WaitingForTransaction => Ok(false),
+            Stage::TransactionCompleted => {
+                super::transaction_ok(task.transaction.as_ref().ok_or("transaction not set")?)?;
+                let data = task
+                    .data_request
+                    .as_ref()
+                    .ok_or("data request not set")?
+                    .result()?;
+                let meta: Option<EnclosureMeta> = serde_wasm_bindgen::from_value(
+                    task.meta_request
+                        .as_ref()
+                        .ok_or("meta request not set")?
+                        .result()?,
+                )?;
+
+                let mime_type = meta.and_then(|m| m.mime_type);
+                let blob = match (data.dyn_into::<Blob>(), mime_type) {
+                    (Ok(blob), _) => blob,
+                    (Err(data), Some(mime_type)) if data.is_instance_of::<ArrayBuffer>() => {
+                        utils::blob_with_type(&data, &mime_type)?
+                    }
+                    (Err(data), None) if data.is_instance_of::<ArrayBuffer>() => {
+                        let mime_type = utils::sniff_mime_type(&utils::sniff_bytes(
+                            data.unchecked_ref::<ArrayBuffer>(),
+                        ))
+                        .unwrap_or("audio/mpeg");
+
+                        utils::blob_with_type(&data, mime_type)?
+                    }
+                    (Err(_), _) => return Err(JsError::from_str("enclosure not found")),
+                };
+
+                self.link
+                    .respond(task.handler_id, Response::Enclosure(task.item_id, blob));
+
+                Ok(true)
+            }
+        }
+    }
+}
@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use wasm_bindgen::{prelude::Closure, JsCast};
 use web_sys::{IdbDatabase, IdbIndexParameters};
 
+const DB_VERSION: u32 = 7;
+
 #[derive(Debug)]
 pub struct Task {
     stage: Stage,
@@ -62,7 +64,7 @@ impl super::TaskProcessor<Task> for super::super::Repo {
                 let idb_factory: web_sys::IdbFactory =
                     window.indexed_db()?.ok_or("could not get indexed db")?;
                 let idb_open_request: web_sys::IdbOpenDbRequest =
-                    idb_factory.open_with_u32("podcast-player", 1)?;
+                    idb_factory.open_with_u32("podcast-player", DB_VERSION)?;
                 let callback_update = self.link.callback(Message::OpenDbUpdate);
                 let callback_success = self.link.callback(Message::OpenDbResult);
                 let callback_error = self.link.callback(Message::OpenDbResult);
@@ -101,9 +103,15 @@ impl super::TaskProcessor<Task> for super::super::Repo {
                     "feeds",
                     "items",
                     "enclosures",
+                    "enclosures-meta",
+                    "enclosure-chunks",
                     "images",
                     "images-meta",
                     "configuration",
+                    "queue",
+                    "chapters",
+                    "history",
+                    "bookmarks",
                 ];
                 let mut indices = HashMap::new();
                 indices.insert(
@@ -134,7 +142,13 @@ impl super::TaskProcessor<Task> for super::super::Repo {
                     ],
                 );
 
+                let existing_object_stores = idb_db.object_store_names();
+
                 for object_store in object_stores {
+                    if existing_object_stores.contains(object_store) {
+                        continue;
+                    }
+
                     match idb_db.create_object_store(object_store) {
                         Ok(os) => {
                             if indices.contains_key(object_store) {
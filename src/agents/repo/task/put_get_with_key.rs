@@ -1,4 +1,11 @@
-use crate::{agents::repo, objects::JsError};
+use crate::{
+    agents::{
+        repo,
+        tabs::{self, TabMessage},
+    },
+    objects::{EnclosureMeta, ItemChapters, JsError},
+};
+use podcast_player_common::{Channel, FeedVal, Item};
 use wasm_bindgen::{JsCast, JsValue};
 use yew_agent::HandlerId;
 
@@ -28,17 +35,27 @@ pub enum Kind {
     Channel,
     Item,
     Configuration,
-    Enclosure,
+    Queue,
+    Chapters,
+    EnclosureMeta,
+    Image,
+    ListeningSession,
+    Bookmark,
 }
 
 impl Kind {
     fn table_name(&self) -> &str {
         match &self {
             Self::Configuration => "configuration",
+            Self::Queue => "queue",
+            Self::Chapters => "chapters",
+            Self::EnclosureMeta => "enclosures-meta",
+            Self::Image => "images",
+            Self::ListeningSession => "history",
+            Self::Bookmark => "bookmarks",
             Self::Item => "items",
             Self::Feed => "feeds",
             Self::Channel => "channels",
-            Self::Enclosure => "enclosures",
         }
     }
 }
@@ -57,7 +74,7 @@ impl Task {
             value,
             request: None,
             transaction: None,
-            handler_id: None,
+            handler_id,
         }
     }
 
@@ -156,38 +173,109 @@ impl super::TaskProcessor<Task> for super::super::Repo {
             }
             Stage::WaitingForTransaction => Ok(false),
             Stage::TransactionCompleted => {
+                super::transaction_ok(task.transaction.as_ref().ok_or("transaction not set")?)?;
                 let result = task.request.as_ref().ok_or("request not set")?.result()?;
-                let response = match &task.kind {
-                    Kind::Item => Ok(repo::Response::UpdatedItem(serde_wasm_bindgen::from_value(
-                        result.clone(),
-                    )?)),
-                    Kind::Feed => Ok(repo::Response::UpdatedFeed(serde_wasm_bindgen::from_value(
-                        result.clone(),
-                    )?)),
-                    Kind::Channel => Ok(repo::Response::UpdatedChannel(
-                        serde_wasm_bindgen::from_value(result.clone())?,
-                    )),
-                    Kind::Configuration => {
-                        let key: String = serde_wasm_bindgen::from_value(task.key.clone())?;
-
-                        match &*key {
-                            "updater" => Ok(repo::Response::UpdaterConfig(
-                                serde_wasm_bindgen::from_value(result.clone())?,
+
+                match (&task.kind, &task.value) {
+                    (Kind::Item, Some(_)) => self.notify(
+                        &[serde_wasm_bindgen::from_value::<Item>(result)?],
+                        repo::Response::ItemsChanged,
+                    ),
+                    (Kind::Feed, Some(_)) => self.notify(
+                        &[serde_wasm_bindgen::from_value::<FeedVal>(result)?],
+                        repo::Response::FeedsChanged,
+                    ),
+                    (Kind::Channel, Some(_)) => self.notify(
+                        &[serde_wasm_bindgen::from_value::<Channel>(result)?],
+                        repo::Response::ChannelsChanged,
+                    ),
+                    (_, _) => {
+                        // the other tabs read the written value again
+                        let changed = match (&task.kind, &task.value) {
+                            (Kind::Configuration, Some(_)) => {
+                                let key: String = serde_wasm_bindgen::from_value(task.key.clone())?;
+
+                                repo::SHARED_CONFIGURATION
+                                    .contains(&key.as_str())
+                                    .then(|| TabMessage::ConfigurationChanged(key))
+                            }
+                            (Kind::Bookmark, Some(_)) => Some(TabMessage::BookmarkChanged(
+                                serde_wasm_bindgen::from_value(task.key.clone())?,
                             )),
-                            _ => Err(JsError::from_str("unknown configuration requested")),
+                            (_, _) => None,
+                        };
+
+                        if let Some(message) = changed {
+                            self.tabs.send(tabs::Request::Broadcast(message));
                         }
-                    }
-                    Kind::Enclosure => Ok(repo::Response::Enclosure(result.dyn_into()?)),
-                }?;
 
-                match task.handler_id {
-                    Some(handler_id) => {
-                        self.link.respond(handler_id, response);
-                    }
-                    None => {
-                        for subscriber in &self.subscribers {
-                            if subscriber.is_respondable() {
-                                self.link.respond(*subscriber, response.clone());
+                        let response = match &task.kind {
+                            Kind::Item => {
+                                repo::Response::Item(serde_wasm_bindgen::from_value(result)?)
+                            }
+                            Kind::Feed => {
+                                repo::Response::Feed(serde_wasm_bindgen::from_value(result)?)
+                            }
+                            Kind::Channel => {
+                                repo::Response::Channel(serde_wasm_bindgen::from_value(result)?)
+                            }
+                            Kind::Chapters => repo::Response::Chapters(
+                                serde_wasm_bindgen::from_value(task.key.clone())?,
+                                serde_wasm_bindgen::from_value::<Option<ItemChapters>>(result)?
+                                    .map(|item_chapters| item_chapters.chapters),
+                            ),
+                            Kind::EnclosureMeta => repo::Response::EnclosureMetas(
+                                serde_wasm_bindgen::from_value::<Option<EnclosureMeta>>(result)?
+                                    .into_iter()
+                                    .collect(),
+                            ),
+                            Kind::Image => repo::Response::Image(
+                                serde_wasm_bindgen::from_value(task.key.clone())?,
+                                serde_wasm_bindgen::from_value(result)?,
+                            ),
+                            Kind::ListeningSession => repo::Response::ListeningSession(
+                                serde_wasm_bindgen::from_value(result)?,
+                            ),
+                            Kind::Bookmark => {
+                                repo::Response::Bookmark(serde_wasm_bindgen::from_value(result)?)
+                            }
+                            Kind::Queue => {
+                                repo::Response::Queue(serde_wasm_bindgen::from_value(result)?)
+                            }
+                            Kind::Configuration => {
+                                let key: String = serde_wasm_bindgen::from_value(task.key.clone())?;
+
+                                self.cache.set_configuration(&key, result.clone());
+
+                                match &*key {
+                                    "updater" => repo::Response::UpdaterConfig(
+                                        serde_wasm_bindgen::from_value(result)?,
+                                    ),
+                                    "player" => repo::Response::PlayerConfig(
+                                        serde_wasm_bindgen::from_value(result)?,
+                                    ),
+                                    "player-state" => repo::Response::PlayerState(
+                                        serde_wasm_bindgen::from_value(result)?,
+                                    ),
+                                    _ => {
+                                        return Err(JsError::from_str(
+                                            "unknown configuration requested",
+                                        ))
+                                    }
+                                }
+                            }
+                        };
+
+                        match task.handler_id {
+                            Some(handler_id) => {
+                                self.link.respond(handler_id, response);
+                            }
+                            None => {
+                                for subscriber in &self.subscribers {
+                                    if subscriber.is_respondable() {
+                                        self.link.respond(*subscriber, response.clone());
+                                    }
+                                }
                             }
                         }
                     }
@@ -1,41 +1,64 @@
-use crate::agents::repo;
-use js_sys::ArrayBuffer;
+use crate::{
+    agents::repo,
+    objects::{EnclosureMeta, JsError},
+    utils,
+};
+use js_sys::Array;
 use podcast_player_common::{item_meta::DownloadStatus, Item};
 use uuid::Uuid;
 use wasm_bindgen::JsCast;
-use web_sys::{IdbRequest, IdbRequestReadyState, IdbTransaction, IdbTransactionMode};
+use web_sys::{Blob, IdbRequest, IdbRequestReadyState, IdbTransaction, IdbTransactionMode};
 
+/// # Store Enclosure Task
+///
+/// Combines the chunks of a completed download into the enclosure and removes the chunks.
+/// Once stored, the enclosure is analyzed for embedded metadata (duration, title and cover image) in the background.
+///
+/// ## Stages and Transitions
+///
+/// * Init (S)
+/// * WaitingForIdbReadRequests (S)
+/// * WaitingForIdbWriteRequests (S)
+/// * WaitingForTransaction (S)
+/// * transaction_complete (T)
+/// * TransactionCompleted (S)
 #[derive(Debug)]
 pub struct Task {
     stage: Stage,
     item_id: Uuid,
-    data: ArrayBuffer,
+    content_type: Option<String>,
+    head: Vec<u8>,
     item_read_request: Option<IdbRequest>,
-    item_write_request: Option<IdbRequest>,
-    data_write_request: Option<IdbRequest>,
+    chunks_read_request: Option<IdbRequest>,
+    write_requests: Vec<IdbRequest>,
     item: Option<Item>,
+    meta: Option<EnclosureMeta>,
+    data: Option<Blob>,
     transaction: Option<IdbTransaction>,
 }
 
 #[derive(Debug)]
 enum Stage {
     Init,
-    WaitingForIdbReadRequest,
+    WaitingForIdbReadRequests,
     WaitingForIdbWriteRequests,
     WaitingForTransaction,
     TransactionCompleted,
 }
 
 impl Task {
-    pub fn new(item_id: Uuid, data: ArrayBuffer) -> Self {
+    pub fn new(item_id: Uuid, content_type: Option<String>, head: Vec<u8>) -> Self {
         Self {
             stage: Stage::Init,
             item_id,
-            data,
+            content_type,
+            head,
             item_read_request: None,
-            data_write_request: None,
-            item_write_request: None,
+            chunks_read_request: None,
+            write_requests: Vec::new(),
             item: None,
+            meta: None,
+            data: None,
             transaction: None,
         }
     }
@@ -54,92 +77,132 @@ impl super::TaskProcessor<Task> for super::super::Repo {
                     .as_ref()
                     .ok_or("db not set")?
                     .transaction_with_str_sequence_and_mode(
-                        &serde_wasm_bindgen::to_value(&vec!["items", "enclosures"])?,
+                        &serde_wasm_bindgen::to_value(&vec![
+                            "items",
+                            "enclosures",
+                            "enclosures-meta",
+                            "enclosure-chunks",
+                        ])?,
                         IdbTransactionMode::Readwrite,
                     )?;
 
-                let os = trans.object_store("items")?;
-
                 trans.set_onabort(Some(self.idb_closure_trans_abort.as_ref().unchecked_ref()));
                 trans.set_onerror(Some(self.idb_closure_trans_error.as_ref().unchecked_ref()));
                 trans.set_oncomplete(Some(
                     self.idb_closure_trans_complete.as_ref().unchecked_ref(),
                 ));
+
+                let item_request = trans
+                    .object_store("items")?
+                    .get(&serde_wasm_bindgen::to_value(&task.item_id)?)?;
+                let chunks_request = trans
+                    .object_store("enclosure-chunks")?
+                    .get_all_with_key(&super::enclosure_chunks_range(&task.item_id)?)?;
+
                 task.transaction = Some(trans);
 
-                let request = os.get(&serde_wasm_bindgen::to_value(&task.item_id)?)?;
+                item_request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                item_request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.item_read_request = Some(item_request);
 
-                request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
-                request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
-                task.item_read_request = Some(request);
+                chunks_request
+                    .set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                chunks_request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.chunks_read_request = Some(chunks_request);
 
-                task.stage = Stage::WaitingForIdbReadRequest;
+                task.stage = Stage::WaitingForIdbReadRequests;
                 Ok(false)
             }
-            Stage::WaitingForIdbReadRequest => {
-                let request = task
+            Stage::WaitingForIdbReadRequests => {
+                let item_request = task
                     .item_read_request
                     .as_ref()
-                    .ok_or("idb request not set")?;
+                    .ok_or("item read request not set")?;
+                let chunks_request = task
+                    .chunks_read_request
+                    .as_ref()
+                    .ok_or("chunks read request not set")?;
 
-                match request.ready_state() {
-                    IdbRequestReadyState::Done => {
-                        // TODO: check download size: task.data.byte_length()
-                        let trans = request.transaction().ok_or("transaction not set")?;
+                match (item_request.ready_state(), chunks_request.ready_state()) {
+                    (IdbRequestReadyState::Done, IdbRequestReadyState::Done) => {
+                        super::request_ok(item_request)?;
+                        super::request_ok(chunks_request)?;
 
-                        let mut item: Item = serde_wasm_bindgen::from_value(request.result()?)?;
+                        let trans = task.transaction.as_ref().ok_or("transaction not set")?;
+                        let mut item: Item =
+                            serde_wasm_bindgen::from_value(item_request.result()?)?;
 
                         item.set_download_status(DownloadStatus::Ok);
 
-                        let item_os = trans.object_store("items")?;
-                        let item_write_request = item_os.put_with_key(
-                            &serde_wasm_bindgen::to_value(&item)?,
-                            &serde_wasm_bindgen::to_value(&item.get_id())?,
-                        )?;
-
-                        let enclosure_os = trans.object_store("enclosures")?;
-                        let data_write_request = enclosure_os.put_with_key(
-                            &task.data,
-                            &serde_wasm_bindgen::to_value(&item.get_id())?,
-                        )?;
-
-                        item_write_request
-                            .set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
-                        item_write_request
-                            .set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
-
-                        data_write_request
-                            .set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
-                        data_write_request
-                            .set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
-
-                        task.item_write_request = Some(item_write_request);
-                        task.data_write_request = Some(data_write_request);
+                        let meta = EnclosureMeta {
+                            id: item.get_id(),
+                            mime_type: utils::enclosure_mime_type(
+                                task.content_type.as_deref(),
+                                &item,
+                                &task.head,
+                            ),
+                            duration: None,
+                            title: None,
+                            image: false,
+                            error: None,
+                        };
+                        // the chunks are stored as blobs; combining them does not copy the data
+                        let data =
+                            Blob::new_with_blob_sequence(&Array::from(&chunks_request.result()?))?;
+                        // the blob is stored with the normalized type, so it can be played as is
+                        let data = match &meta.mime_type {
+                            Some(mime_type) => utils::blob_with_type(&data, mime_type)?,
+                            None => data,
+                        };
+
+                        let requests = vec![
+                            trans.object_store("items")?.put_with_key(
+                                &serde_wasm_bindgen::to_value(&item)?,
+                                &serde_wasm_bindgen::to_value(&item.get_id())?,
+                            )?,
+                            trans.object_store("enclosures")?.put_with_key(
+                                &data,
+                                &serde_wasm_bindgen::to_value(&item.get_id())?,
+                            )?,
+                            trans.object_store("enclosures-meta")?.put_with_key(
+                                &serde_wasm_bindgen::to_value(&meta)?,
+                                &serde_wasm_bindgen::to_value(&meta.id)?,
+                            )?,
+                            trans
+                                .object_store("enclosure-chunks")?
+                                .delete(&super::enclosure_chunks_range(&item.get_id())?)?,
+                        ];
+
+                        for request in requests {
+                            request.set_onsuccess(Some(
+                                self.idb_closure_success.as_ref().unchecked_ref(),
+                            ));
+                            request
+                                .set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                            task.write_requests.push(request);
+                        }
+
                         task.item = Some(item);
+                        task.meta = Some(meta);
+                        task.data = Some(data);
                         task.stage = Stage::WaitingForIdbWriteRequests;
-
-                        Ok(false)
                     }
-                    _ => Ok(false),
+                    (_, _) => {}
                 }
+
+                Ok(false)
             }
             Stage::WaitingForIdbWriteRequests => {
-                let item_request = task
-                    .item_write_request
-                    .as_ref()
-                    .ok_or("item write request not set")?;
-                let data_request = task
-                    .data_write_request
-                    .as_ref()
-                    .ok_or("data write request not set")?;
-
-                match (item_request.ready_state(), data_request.ready_state()) {
-                    (IdbRequestReadyState::Done, IdbRequestReadyState::Done) => {
-                        super::request_ok(item_request)?;
-                        super::request_ok(data_request)?;
-                        task.stage = Stage::WaitingForTransaction;
+                if task
+                    .write_requests
+                    .iter()
+                    .all(|r| r.ready_state() == IdbRequestReadyState::Done)
+                {
+                    for request in &task.write_requests {
+                        super::request_ok(request)?;
                     }
-                    (_, _) => {}
+
+                    task.stage = Stage::WaitingForTransaction;
                 }
 
                 Ok(false)
@@ -148,16 +211,93 @@ impl super::TaskProcessor<Task> for super::super::Repo {
             Stage::TransactionCompleted => {
                 super::transaction_ok(task.transaction.as_ref().ok_or("transaction not set")?)?;
                 let item = task.item.as_ref().ok_or("item not set")?;
+                let meta = task.meta.take().ok_or("meta not set")?;
+                let data = task.data.take().ok_or("data not set")?;
 
-                for subscriber in &self.subscribers {
-                    if subscriber.is_respondable() {
-                        self.link
-                            .respond(*subscriber, repo::Response::UpdatedItem(item.clone()));
-                    }
-                }
+                self.notify(&[item.clone()], repo::Response::ItemsChanged);
+                self.link.send_future(async move {
+                    let metadata = read_media_metadata(&data, meta.mime_type.as_deref()).await;
+
+                    repo::Message::EnclosureAnalyzed(meta, metadata)
+                });
 
                 Ok(true)
             }
         }
     }
 }
+
+/// Reads the metadata embedded in the enclosure (ID3 tag, MPEG frame header or MP4 boxes).
+async fn read_media_metadata(
+    data: &Blob,
+    mime_type: Option<&str>,
+) -> Result<utils::MediaMetadata, JsError> {
+    let size = data.size();
+    let head =
+        utils::read_blob(&data.slice_with_f64_and_f64(0.0, utils::MP4_BOX_HEADER_LENGTH as f64)?)
+            .await?;
+    let tag_length = utils::id3_tag_length(&head);
+    let metadata = match tag_length {
+        Some(length) => {
+            let tag = utils::read_blob(&data.slice_with_f64_and_f64(0.0, length as f64)?).await?;
+
+            utils::parse_id3_metadata(&tag)
+        }
+        None => utils::MediaMetadata::default(),
+    };
+
+    if metadata.duration.is_some() {
+        return Ok(metadata);
+    }
+
+    match mime_type {
+        Some("audio/mpeg") => {
+            let start = tag_length.unwrap_or(0) as f64;
+            let frame = utils::read_blob(
+                &data.slice_with_f64_and_f64(start, start + utils::MPEG_SEARCH_LENGTH as f64)?,
+            )
+            .await?;
+
+            Ok(utils::MediaMetadata {
+                duration: utils::mpeg_duration(&frame, size - start),
+                ..metadata
+            })
+        }
+        Some("audio/mp4") => {
+            let mut pos = 0.0;
+
+            // the moov box is either at the beginning or at the end of the file
+            while pos < size {
+                let header = utils::read_blob(
+                    &data.slice_with_f64_and_f64(pos, pos + utils::MP4_BOX_HEADER_LENGTH as f64)?,
+                )
+                .await?;
+                let (box_type, box_size, header_length) = match utils::mp4_box_header(&header) {
+                    Some(header) => header,
+                    None => break,
+                };
+                let end = match box_size {
+                    Some(box_size) => pos + box_size as f64,
+                    None => size,
+                };
+
+                if &box_type == b"moov" {
+                    let moov = utils::read_blob(
+                        &data.slice_with_f64_and_f64(pos + header_length as f64, end)?,
+                    )
+                    .await?;
+
+                    return Ok(utils::parse_mp4_moov(&moov));
+                }
+                if end <= pos {
+                    break;
+                }
+
+                pos = end;
+            }
+
+            Ok(metadata)
+        }
+        _ => Ok(metadata),
+    }
+}
@@ -0,0 +1,100 @@
+use crate::objects::JsError;
+use uuid::Uuid;
+use wasm_bindgen::JsCast;
+use web_sys::{Blob, IdbRequest, IdbRequestReadyState, IdbTransaction};
+
+/// # Store Enclosure Chunk Task
+///
+/// Stores a chunk of a running download.
+/// The chunks are combined into the enclosure once the download is complete.
+///
+/// ## Stages and Transitions
+///
+/// * Init (S)
+/// * WaitingForRequest (S)
+/// * WaitingForTransaction (S)
+/// * transaction_complete (T)
+/// * TransactionCompleted (S)
+#[derive(Debug)]
+pub struct Task {
+    stage: Stage,
+    item_id: Uuid,
+    index: u32,
+    data: Blob,
+    request: Option<IdbRequest>,
+    transaction: Option<IdbTransaction>,
+}
+
+#[derive(Debug)]
+enum Stage {
+    Init,
+    WaitingForRequest,
+    WaitingForTransaction,
+    TransactionCompleted,
+}
+
+impl Task {
+    pub fn new(item_id: Uuid, index: u32, data: Blob) -> Self {
+        Self {
+            stage: Stage::Init,
+            item_id,
+            index,
+            data,
+            request: None,
+            transaction: None,
+        }
+    }
+
+    pub fn transaction_complete(&mut self) {
+        self.stage = Stage::TransactionCompleted;
+    }
+}
+
+impl super::TaskProcessor<Task> for super::super::Repo {
+    fn process(&mut self, task: &mut Task) -> Result<bool, JsError> {
+        match task.stage {
+            Stage::Init => {
+                let db = self.db.as_ref().ok_or("db not set")?;
+                let trans = db.transaction_with_str_sequence_and_mode(
+                    &serde_wasm_bindgen::to_value(&vec!["enclosure-chunks"])?,
+                    web_sys::IdbTransactionMode::Readwrite,
+                )?;
+                let request = trans.object_store("enclosure-chunks")?.put_with_key(
+                    &task.data,
+                    &serde_wasm_bindgen::to_value(&(task.item_id, task.index))?,
+                )?;
+
+                trans.set_onabort(Some(self.idb_closure_trans_abort.as_ref().unchecked_ref()));
+                trans.set_onerror(Some(self.idb_closure_trans_error.as_ref().unchecked_ref()));
+                trans.set_oncomplete(Some(
+                    self.idb_closure_trans_complete.as_ref().unchecked_ref(),
+                ));
+                task.transaction = Some(trans);
+
+                request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                task.request = Some(request);
+
+                task.stage = Stage::WaitingForRequest;
+                Ok(false)
+            }
+            Stage::WaitingForRequest => {
+                let request = task.request.as_ref().ok_or("request not set")?;
+
+                if request.ready_state() == IdbRequestReadyState::Done {
+                    super::request_ok(request)?;
+
+                    task.stage = Stage::WaitingForTransaction;
+                }
+
+                Ok(false)
+            }
+            Stage::WaitingForTransaction => Ok(false),
+            Stage::TransactionCompleted => {
+                super::transaction_ok(task.transaction.as_ref().ok_or("transaction not set")?)?;
+
+                Ok(true)
+            }
+        }
+    }
+}
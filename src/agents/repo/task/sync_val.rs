@@ -1,23 +1,31 @@
 use crate::{agents::repo, objects::JsError};
 use chrono::{DateTime, FixedOffset};
 use podcast_player_common::{channel_val::ChannelVal, item_val::ItemVal, Channel, FeedVal, Item};
+use std::collections::{hash_map::Entry, HashMap};
 use uuid::Uuid;
 use wasm_bindgen::JsCast;
+use web_sys::{IdbRequest, IdbRequestReadyState};
 
+/// # Sync Values Task
+///
+/// Applies a batch of pulled values in a single transaction.
+/// A value is only written, if it is newer than the stored object (based on `update_ts`); of several values of the same object in the batch, only the newest one is applied.
+/// Once the transaction completed, one aggregated change notification per object kind is sent.
 #[derive(Debug)]
 pub struct Task {
     stage: Stage,
-    value: Value,
-    request: Option<web_sys::IdbRequest>,
+    values: Vec<Value>,
+    read_requests: Vec<IdbRequest>,
+    write_requests: Vec<IdbRequest>,
     transaction: Option<web_sys::IdbTransaction>,
-    object: Option<Object>,
+    objects: Vec<Object>,
 }
 
 #[derive(Debug)]
 enum Stage {
     Init,
-    WaitingForReadRequest,
-    WaitingForWriteRequest,
+    WaitingForReadRequests,
+    WaitingForWriteRequests,
     WaitingForTransaction,
     TransactionCompleted,
 }
@@ -30,7 +38,7 @@ pub enum Value {
 }
 
 impl Value {
-    fn table_name(&self) -> &str {
+    fn table_name(&self) -> &'static str {
         match &self {
             Self::Item(_) => "items",
             Self::Feed(_) => "feeds",
@@ -103,132 +111,149 @@ impl Object {
 }
 
 impl Task {
-    pub fn new(value: Value) -> Self {
+    pub fn new(values: Vec<Value>) -> Self {
         Self {
             stage: Stage::Init,
-            value,
-            request: None,
+            values: newest_values(values),
+            read_requests: Vec::new(),
+            write_requests: Vec::new(),
             transaction: None,
-            object: None,
+            objects: Vec::new(),
         }
     }
 
     pub fn transaction_complete(&mut self) {
         self.stage = Stage::TransactionCompleted;
     }
+
+    fn table_names(&self) -> Vec<&str> {
+        let mut table_names: Vec<&str> = self.values.iter().map(|v| v.table_name()).collect();
+
+        table_names.sort();
+        table_names.dedup();
+        table_names
+    }
+}
+
+/// Keeps the newest value of each object (in the order the objects first appear).
+fn newest_values(values: Vec<Value>) -> Vec<Value> {
+    let mut newest: Vec<Value> = Vec::with_capacity(values.len());
+    let mut indices: HashMap<(&str, Uuid), usize> = HashMap::new();
+
+    for value in values {
+        match indices.entry((value.table_name(), *value.id())) {
+            Entry::Occupied(entry) => {
+                let existing = &mut newest[*entry.get()];
+
+                if existing.timestamp() < value.timestamp() {
+                    *existing = value;
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(newest.len());
+                newest.push(value);
+            }
+        }
+    }
+
+    newest
+}
+
+fn requests_done(requests: &[IdbRequest]) -> bool {
+    requests
+        .iter()
+        .all(|r| r.ready_state() == IdbRequestReadyState::Done)
 }
 
 impl super::TaskProcessor<Task> for super::super::Repo {
     fn process(&mut self, task: &mut Task) -> Result<bool, JsError> {
         match task.stage {
             Stage::Init => {
+                if task.values.is_empty() {
+                    return Ok(true);
+                }
+
                 let db = self.db.as_ref().ok_or("db not set")?;
                 let trans = db.transaction_with_str_sequence_and_mode(
-                    &serde_wasm_bindgen::to_value(&vec![task.value.table_name()])?,
+                    &serde_wasm_bindgen::to_value(&task.table_names())?,
                     web_sys::IdbTransactionMode::Readwrite,
                 )?;
-                let os = trans.object_store(task.value.table_name())?;
 
                 trans.set_onabort(Some(self.idb_closure_trans_abort.as_ref().unchecked_ref()));
                 trans.set_onerror(Some(self.idb_closure_trans_error.as_ref().unchecked_ref()));
                 trans.set_oncomplete(Some(
                     self.idb_closure_trans_complete.as_ref().unchecked_ref(),
                 ));
-                task.transaction = Some(trans);
 
-                let request = os.get(&serde_wasm_bindgen::to_value(task.value.id())?)?;
+                for value in &task.values {
+                    let request = trans
+                        .object_store(value.table_name())?
+                        .get(&serde_wasm_bindgen::to_value(value.id())?)?;
 
-                request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
-                request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
-                task.request = Some(request);
+                    request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                    request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                    task.read_requests.push(request);
+                }
 
-                task.stage = Stage::WaitingForReadRequest;
+                task.transaction = Some(trans);
+                task.stage = Stage::WaitingForReadRequests;
                 Ok(false)
             }
-            Stage::WaitingForReadRequest => {
-                let request = task.request.as_ref().ok_or("request not set")?;
+            Stage::WaitingForReadRequests => {
+                if !requests_done(&task.read_requests) {
+                    return Ok(false);
+                }
 
-                match request.ready_state() {
-                    web_sys::IdbRequestReadyState::Done => {
-                        super::request_ok(request)?;
+                let trans = task.transaction.as_ref().ok_or("transaction not set")?;
 
-                        let existing_item: Option<Object> = match task.value {
-                            Value::Channel(_) => serde_wasm_bindgen::from_value::<Option<Channel>>(
-                                request.result()?,
-                            )?
-                            .map(|channel| Object::Channel(channel)),
-                            Value::Feed(_) => serde_wasm_bindgen::from_value::<Option<FeedVal>>(
-                                request.result()?,
-                            )?
-                            .map(|feed| Object::Feed(feed)),
-                            Value::Item(_) => {
-                                serde_wasm_bindgen::from_value::<Option<Item>>(request.result()?)?
-                                    .map(|item| Object::Item(item))
-                            }
-                        };
-
-                        match existing_item {
-                            Some(existing_item) => {
-                                if existing_item.get_val_update() < &task.value.timestamp() {
-                                    let object: Object = task.value.as_ref().into();
-                                    let request = task
-                                        .transaction
-                                        .as_ref()
-                                        .ok_or("transaction not set")?
-                                        .object_store(task.value.table_name())?
-                                        .put_with_key(
-                                            &object.as_ref().try_into()?,
-                                            &serde_wasm_bindgen::to_value(task.value.id())?,
-                                        )?;
-                                    request.set_onsuccess(Some(
-                                        self.idb_closure_success.as_ref().unchecked_ref(),
-                                    ));
-                                    request.set_onerror(Some(
-                                        self.idb_closure_error.as_ref().unchecked_ref(),
-                                    ));
-                                    task.object = Some(object);
-                                    task.request = Some(request);
-                                    task.stage = Stage::WaitingForWriteRequest;
-
-                                    Ok(false)
-                                } else {
-                                    task.stage = Stage::WaitingForTransaction;
-                                    Ok(false)
-                                }
-                            }
-                            None => {
-                                let object: Object = task.value.as_ref().into();
-                                let request = task
-                                    .transaction
-                                    .as_ref()
-                                    .ok_or("transaction not set")?
-                                    .object_store(task.value.table_name())?
-                                    .add_with_key(
-                                        &object.as_ref().try_into()?,
-                                        &serde_wasm_bindgen::to_value(task.value.id())?,
-                                    )?;
-                                request.set_onsuccess(Some(
-                                    self.idb_closure_success.as_ref().unchecked_ref(),
-                                ));
-                                request.set_onerror(Some(
-                                    self.idb_closure_error.as_ref().unchecked_ref(),
-                                ));
-                                task.request = Some(request);
-                                task.stage = Stage::WaitingForWriteRequest;
-                                task.object = Some(object);
-
-                                Ok(false)
+                for (value, request) in task.values.iter().zip(task.read_requests.iter()) {
+                    super::request_ok(request)?;
+
+                    let existing_object: Option<Object> = match value {
+                        Value::Channel(_) => {
+                            serde_wasm_bindgen::from_value::<Option<Channel>>(request.result()?)?
+                                .map(Object::Channel)
+                        }
+                        Value::Feed(_) => {
+                            serde_wasm_bindgen::from_value::<Option<FeedVal>>(request.result()?)?
+                                .map(Object::Feed)
+                        }
+                        Value::Item(_) => {
+                            serde_wasm_bindgen::from_value::<Option<Item>>(request.result()?)?
+                                .map(Object::Item)
+                        }
+                    };
+                    let os = trans.object_store(value.table_name())?;
+                    let key = serde_wasm_bindgen::to_value(value.id())?;
+                    let object: Object = value.as_ref().into();
+                    let request = match existing_object {
+                        Some(existing_object) => {
+                            if existing_object.get_val_update() < &value.timestamp() {
+                                os.put_with_key(&object.as_ref().try_into()?, &key)?
+                            } else {
+                                continue;
                             }
                         }
-                    }
-                    _ => Ok(false),
+                        None => os.add_with_key(&object.as_ref().try_into()?, &key)?,
+                    };
+
+                    request.set_onsuccess(Some(self.idb_closure_success.as_ref().unchecked_ref()));
+                    request.set_onerror(Some(self.idb_closure_error.as_ref().unchecked_ref()));
+                    task.write_requests.push(request);
+                    task.objects.push(object);
                 }
-            }
-            Stage::WaitingForWriteRequest => {
-                let request = task.request.as_ref().ok_or("request not set")?;
 
-                if request.ready_state() == web_sys::IdbRequestReadyState::Done {
-                    super::request_ok(request)?;
+                task.stage = match task.write_requests.is_empty() {
+                    true => Stage::WaitingForTransaction,
+                    false => Stage::WaitingForWriteRequests,
+                };
+                Ok(false)
+            }
+            Stage::WaitingForWriteRequests => {
+                if requests_done(&task.write_requests) {
+                    for request in &task.write_requests {
+                        super::request_ok(request)?;
+                    }
 
                     task.stage = Stage::WaitingForTransaction;
                 }
@@ -237,24 +262,30 @@ impl super::TaskProcessor<Task> for super::super::Repo {
             }
             Stage::WaitingForTransaction => Ok(false),
             Stage::TransactionCompleted => {
-                match &task.object {
-                    Some(object) => {
-                        let response = match object {
-                            Object::Item(item) => repo::Response::UpdatedItem(item.clone()),
-                            Object::Feed(feed) => repo::Response::UpdatedFeed(feed.clone()),
-                            Object::Channel(channel) => {
-                                repo::Response::UpdatedChannel(channel.clone())
-                            }
-                        };
+                super::transaction_ok(task.transaction.as_ref().ok_or("transaction not set")?)?;
 
-                        for subscriber in &self.subscribers {
-                            if subscriber.is_respondable() {
-                                self.link.respond(*subscriber, response.clone());
-                            }
-                        }
+                let mut items = Vec::new();
+                let mut channels = Vec::new();
+                let mut feeds = Vec::new();
+
+                for object in task.objects.drain(..) {
+                    match object {
+                        Object::Item(item) => items.push(item),
+                        Object::Channel(channel) => channels.push(channel),
+                        Object::Feed(feed) => feeds.push(feed),
                     }
-                    None => {}
                 }
+
+                if !feeds.is_empty() {
+                    self.notify(&feeds, repo::Response::FeedsChanged);
+                }
+                if !channels.is_empty() {
+                    self.notify(&channels, repo::Response::ChannelsChanged);
+                }
+                if !items.is_empty() {
+                    self.notify(&items, repo::Response::ItemsChanged);
+                }
+
                 Ok(true)
             }
         }
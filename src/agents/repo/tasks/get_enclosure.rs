@@ -26,6 +26,9 @@ impl repo::RepositoryTask for GetEnclosureTask {
         &mut self,
         result: Result<wasm_bindgen::JsValue, wasm_bindgen::JsValue>,
     ) -> Result<Option<repo::Response>, JsError> {
-        Ok(Some(repo::Response::Enclosure(result?.dyn_into()?)))
+        Ok(Some(repo::Response::Enclosure(
+            self.id,
+            result?.dyn_into()?,
+        )))
     }
 }
@@ -0,0 +1,196 @@
+use super::notifier;
+use crate::objects::{Channel, FeedVal, Item, ItemQueue, JsError};
+use js_sys::{Function, Promise, Reflect};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{BroadcastChannel, MessageEvent};
+use yew_agent::{Agent, AgentLink, Context, Dispatched, Dispatcher, HandlerId};
+
+/// Name of the broadcast channel connecting the tabs.
+const CHANNEL_NAME: &str = "podcast-player";
+/// Name of the lock held by the leader tab.
+const LEADER_LOCK: &str = "podcast-player-leader";
+
+/// Message exchanged between the tabs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TabMessage {
+    /// a tab started playing (the others pause)
+    Playing,
+    FeedsChanged(Vec<FeedVal>),
+    ChannelsChanged(Vec<Channel>),
+    ItemsChanged(Vec<Item>),
+    /// key of a written configuration value
+    ConfigurationChanged(String),
+    QueueChanged(ItemQueue),
+    /// id of a written bookmark
+    BookmarkChanged(Uuid),
+    BookmarkDeleted(Uuid),
+}
+
+#[derive(Debug, Clone)]
+pub enum Request {
+    GetLeadership,         // returns Leader only to requester
+    Broadcast(TabMessage), // sends the message to the other tabs
+}
+
+#[derive(Debug, Clone)]
+pub enum Response {
+    /// Whether this tab is the leader; sent to all subscribers, when the leadership is acquired
+    Leader(bool),
+    /// Message received from another tab; sent to all subscribers
+    Received(TabMessage),
+}
+
+#[derive(Debug)]
+pub enum Message {
+    Received(MessageEvent),
+    LeadershipAcquired,
+}
+
+/// # Tabs
+///
+/// Coordinates the instances of the app open in several tabs (the agents live per tab).
+/// The tabs exchange messages through a broadcast channel, e.g., to keep their data current or to only play in one tab at a time.
+/// The leader tab runs the synchronization and the downloads; it is elected through the Web Locks API: the first tab requesting the lock holds it until it is closed, then the next waiting tab takes over.
+/// Without the Web Locks API, every tab acts as leader.
+pub struct Tabs {
+    link: AgentLink<Self>,
+    subscribers: HashSet<HandlerId>,
+    channel: Option<BroadcastChannel>,
+    leader: bool,
+    notifier: Dispatcher<notifier::Notifier>,
+    _message_closure: Closure<dyn Fn(MessageEvent)>,
+    _lock_closure: Closure<dyn FnMut(JsValue) -> Promise>,
+}
+
+impl Tabs {
+    fn process_update(&mut self, msg: Message) -> Result<(), JsError> {
+        match msg {
+            Message::Received(event) => {
+                let message: TabMessage = serde_wasm_bindgen::from_value(event.data())?;
+
+                self.send_response(Response::Received(message));
+            }
+            Message::LeadershipAcquired => {
+                self.leader = true;
+                self.send_response(Response::Leader(true));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn send_response(&self, response: Response) {
+        for handler_id in &self.subscribers {
+            if handler_id.is_respondable() {
+                self.link.respond(*handler_id, response.clone());
+            }
+        }
+    }
+
+    fn broadcast(&self, message: &TabMessage) -> Result<(), JsError> {
+        if let Some(channel) = &self.channel {
+            channel.post_message(&serde_wasm_bindgen::to_value(message)?)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Requests the leader lock; returns `false`, if the Web Locks API is not available.
+/// The callback is called once the lock is granted; the lock is held, until the promise it returns resolves, i.e., as long as the tab exists.
+fn request_leader_lock(callback: &Closure<dyn FnMut(JsValue) -> Promise>) -> Result<bool, JsError> {
+    let navigator = web_sys::window()
+        .ok_or("could not obtain window")?
+        .navigator();
+    let locks = Reflect::get(&navigator, &JsValue::from_str("locks"))?;
+
+    if locks.is_undefined() {
+        return Ok(false);
+    }
+
+    let request: Function = Reflect::get(&locks, &JsValue::from_str("request"))?.dyn_into()?;
+
+    request.call2(
+        &locks,
+        &JsValue::from_str(LEADER_LOCK),
+        callback.as_ref().unchecked_ref(),
+    )?;
+    Ok(true)
+}
+
+impl Agent for Tabs {
+    type Reach = Context<Self>;
+    type Message = Message;
+    type Input = Request;
+    type Output = Response;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        let mut notifier = notifier::Notifier::dispatcher();
+        let callback_message = link.callback(Message::Received);
+        let message_closure =
+            Closure::wrap(
+                Box::new(move |event: MessageEvent| callback_message.emit(event)) as Box<dyn Fn(_)>,
+            );
+        let callback_lock = link.callback(|_| Message::LeadershipAcquired);
+        let lock_closure = Closure::wrap(Box::new(move |_lock: JsValue| {
+            callback_lock.emit(());
+            Promise::new(&mut |_resolve, _reject| {})
+        }) as Box<dyn FnMut(JsValue) -> Promise>);
+        let channel = match BroadcastChannel::new(CHANNEL_NAME) {
+            Ok(channel) => {
+                channel.set_onmessage(Some(message_closure.as_ref().unchecked_ref()));
+                Some(channel)
+            }
+            Err(e) => {
+                log::warn!("could not open broadcast channel: {:?}", e);
+                None
+            }
+        };
+        let leader = match request_leader_lock(&lock_closure) {
+            Ok(requested) => !requested,
+            Err(e) => {
+                notifier.send(notifier::Request::NotifyError(e));
+                true
+            }
+        };
+
+        Self {
+            link,
+            subscribers: HashSet::new(),
+            channel,
+            leader,
+            notifier,
+            _message_closure: message_closure,
+            _lock_closure: lock_closure,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) {
+        match self.process_update(msg) {
+            Ok(()) => {}
+            Err(e) => self.notifier.send(notifier::Request::NotifyError(e)),
+        }
+    }
+
+    fn handle_input(&mut self, msg: Self::Input, handler_id: HandlerId) {
+        match msg {
+            Request::GetLeadership => self.link.respond(handler_id, Response::Leader(self.leader)),
+            Request::Broadcast(message) => {
+                if let Err(e) = self.broadcast(&message) {
+                    self.notifier.send(notifier::Request::NotifyError(e));
+                }
+            }
+        }
+    }
+
+    fn connected(&mut self, id: HandlerId) {
+        self.subscribers.insert(id);
+    }
+
+    fn disconnected(&mut self, id: HandlerId) {
+        self.subscribers.remove(&id);
+    }
+}
@@ -1,3 +1,4 @@
+mod bookmark_list;
 mod channel_list;
 mod feed_list;
 mod feed_new;
@@ -7,9 +8,12 @@ pub mod item_list_compact;
 mod nav_bar;
 mod notification;
 mod player;
+mod player_settings;
 mod range;
 mod router;
+mod shortcuts;
 mod top;
+pub use bookmark_list::*;
 pub use channel_list::*;
 pub use feed_list::*;
 pub use feed_new::*;
@@ -18,6 +22,7 @@ pub use item_list::*;
 pub use nav_bar::*;
 pub use notification::*;
 pub use player::*;
+pub use player_settings::*;
 pub use range::*;
 pub use router::*;
 pub use top::*;
@@ -0,0 +1,262 @@
+use super::{Icon, IconStyle};
+use crate::{
+    agents::{notifier, player, repo},
+    objects::{Bookmark, Item, JsError},
+};
+use std::{cmp::Reverse, collections::HashMap};
+use uuid::Uuid;
+use web_sys::HtmlInputElement;
+use yew::{prelude::*, Component, Properties};
+use yew_agent::{Bridge, Bridged, Dispatched, Dispatcher};
+
+/// # Bookmark List
+///
+/// Lists the bookmarks of an item (ordered by position) or, without an item, all bookmarks (newest first).
+/// Selecting a bookmark sets the item as source and seeks to the bookmarked position.
+pub struct BookmarkList {
+    repo: Box<dyn Bridge<repo::Repo>>,
+    player: Dispatcher<player::Player>,
+    notifier: Dispatcher<notifier::Notifier>,
+    bookmarks: Option<Vec<Bookmark>>,
+    /// items of the bookmarks (followed through item subscriptions)
+    items: HashMap<Uuid, Option<Item>>,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    /// only list the bookmarks of this item
+    #[prop_or_default]
+    pub item_id: Option<Uuid>,
+    /// the item is the current source; selecting a bookmark only seeks
+    #[prop_or_default]
+    pub current_source: bool,
+}
+
+pub enum Message {
+    RepoMessage(repo::Response),
+    Select(Uuid),
+    SetNote(Uuid, String),
+    Delete(Uuid),
+}
+
+impl BookmarkList {
+    fn process_update(&mut self, ctx: &Context<Self>, msg: Message) -> Result<bool, JsError> {
+        match msg {
+            Message::RepoMessage(response) => match response {
+                repo::Response::Bookmarks(bookmarks) => {
+                    self.bookmarks = Some(bookmarks);
+                    self.subscribe_items(ctx);
+                    Ok(true)
+                }
+                repo::Response::Bookmark(bookmark) => match &mut self.bookmarks {
+                    Some(bookmarks) => {
+                        match bookmarks.iter_mut().find(|b| b.id == bookmark.id) {
+                            Some(existing) => *existing = bookmark,
+                            None => bookmarks.push(bookmark),
+                        }
+
+                        self.subscribe_items(ctx);
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                },
+                repo::Response::BookmarkDeleted(id) => match &mut self.bookmarks {
+                    Some(bookmarks) => {
+                        bookmarks.retain(|b| b.id != id);
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                },
+                repo::Response::ItemsChanged(repo::Query::Item(_), diffs) => {
+                    let mut update = false;
+
+                    for diff in diffs {
+                        if let repo::Diff::Added(item) | repo::Diff::Updated(item) = diff {
+                            if self.items.contains_key(&item.get_id()) {
+                                self.items.insert(item.get_id(), Some(item));
+                                update = true;
+                            }
+                        }
+                    }
+
+                    Ok(update)
+                }
+                _ => Ok(false),
+            },
+            Message::Select(id) => {
+                let (item_id, position) = self
+                    .bookmark(id)
+                    .map(|bookmark| (bookmark.item_id, bookmark.position))?;
+
+                match ctx.props().current_source {
+                    true => self.player.send(player::Request::SetCurrentTime(position)),
+                    false => {
+                        let item = self
+                            .items
+                            .get(&item_id)
+                            .cloned()
+                            .flatten()
+                            .ok_or("item of the bookmark not loaded")?;
+
+                        self.player.send(player::Request::SetSource(
+                            item.clone(),
+                            player::SourceMode::for_item(&item),
+                            Some(position),
+                        ));
+                    }
+                }
+
+                Ok(false)
+            }
+            Message::SetNote(id, note) => {
+                let mut bookmark = self.bookmark(id)?.clone();
+
+                bookmark.note = note;
+                self.repo.send(repo::Request::UpdateBookmark(bookmark));
+                Ok(false)
+            }
+            Message::Delete(id) => {
+                self.repo.send(repo::Request::DeleteBookmark(id));
+                Ok(false)
+            }
+        }
+    }
+
+    fn bookmark(&self, id: Uuid) -> Result<&Bookmark, JsError> {
+        Ok(self
+            .bookmarks
+            .iter()
+            .flatten()
+            .find(|b| b.id == id)
+            .ok_or("bookmark not found")?)
+    }
+
+    /// Subscribes to the items of the listed bookmarks not followed yet.
+    fn subscribe_items(&mut self, ctx: &Context<Self>) {
+        let item_ids: Vec<Uuid> = self
+            .bookmarks
+            .iter()
+            .flatten()
+            .filter(|b| {
+                ctx.props()
+                    .item_id
+                    .map_or(true, |item_id| item_id == b.item_id)
+            })
+            .map(|b| b.item_id)
+            .collect();
+
+        for item_id in item_ids {
+            if !self.items.contains_key(&item_id) {
+                self.items.insert(item_id, None);
+                self.repo
+                    .send(repo::Request::Subscribe(repo::Query::Item(item_id)));
+            }
+        }
+    }
+}
+
+impl Component for BookmarkList {
+    type Message = Message;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut repo = repo::Repo::bridge(ctx.link().callback(Message::RepoMessage));
+
+        repo.send(repo::Request::GetBookmarks);
+
+        Self {
+            repo,
+            player: player::Player::dispatcher(),
+            notifier: notifier::Notifier::dispatcher(),
+            bookmarks: None,
+            items: HashMap::new(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match self.process_update(ctx, msg) {
+            Ok(update) => update,
+            Err(e) => {
+                self.notifier.send(notifier::Request::NotifyError(e));
+                false
+            }
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        self.subscribe_items(ctx);
+
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let mut bookmarks: Vec<&Bookmark> = match (&self.bookmarks, ctx.props().item_id) {
+            (Some(bookmarks), Some(item_id)) => {
+                bookmarks.iter().filter(|b| b.item_id == item_id).collect()
+            }
+            (Some(bookmarks), None) => bookmarks.iter().collect(),
+            (None, _) => return html! {},
+        };
+
+        match ctx.props().item_id {
+            Some(_) => bookmarks.sort_by(|a, b| a.position.total_cmp(&b.position)),
+            None => bookmarks.sort_by_key(|bookmark| Reverse(bookmark.created_at)),
+        }
+
+        if bookmarks.is_empty() {
+            return html! {<p class="has-text-centered">{"no bookmarks"}</p>};
+        }
+
+        html! {
+            <table class="table is-fullwidth is-striped">
+                <tbody>
+                    {bookmarks.into_iter().map(|bookmark| {
+                        let id = bookmark.id;
+
+                        html! {
+                            <tr>
+                                <td>
+                                    <button class="button is-small" onclick={ctx.link().callback(move |_| Message::Select(id))}>
+                                        <Icon name="play_arrow" style={IconStyle::Filled}/>
+                                        <span>{format_position(bookmark.position)}</span>
+                                    </button>
+                                </td>
+                                {match ctx.props().item_id {
+                                    Some(_) => html! {},
+                                    None => html! {
+                                        <td>{match self.items.get(&bookmark.item_id) {
+                                            Some(Some(item)) => item.get_title(),
+                                            _ => String::from("..."),
+                                        }}</td>
+                                    },
+                                }}
+                                <td>
+                                    <input class="input is-small" type="text" placeholder="note" value={bookmark.note.clone()} onchange={ctx.link().callback(move |e: Event| Message::SetNote(id, e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                                </td>
+                                <td class="has-text-right">
+                                    <button class="button is-small" onclick={ctx.link().callback(move |_| Message::Delete(id))}><Icon name="delete" style={IconStyle::Outlined}/></button>
+                                </td>
+                            </tr>
+                        }
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        for item_id in self.items.keys() {
+            self.repo
+                .send(repo::Request::Unsubscribe(repo::Query::Item(*item_id)));
+        }
+    }
+}
+
+fn format_position(position: f64) -> String {
+    let seconds = position.max(0.0) as u64;
+
+    match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{}:{:02}:{:02}", hours, seconds / 60 % 60, seconds % 60),
+    }
+}
@@ -1,7 +1,7 @@
 use super::router::AppRoute;
 use crate::agents::{
     notifier,
-    repo::{Repo, Request as RepoRequest, Response as RepoResponse},
+    repo::{apply_diffs, Query, Repo, Request as RepoRequest, Response as RepoResponse},
 };
 use crate::components::icon::{Icon, IconStyle};
 use crate::objects::{Channel, JsError};
@@ -119,21 +119,13 @@ impl ChannelList {
     fn process_update(&mut self, _ctx: &Context<Self>, msg: Message) -> Result<bool, JsError> {
         match msg {
             Message::RepoMessage(response) => match response {
-                RepoResponse::Channels(mut res) => {
-                    res.sort_by(|a, b| a.val.title.cmp(&b.val.title));
-                    self.channels = Some(res);
+                RepoResponse::ChannelsChanged(Query::Channels, diffs) => {
+                    let channels = self.channels.get_or_insert_with(Vec::new);
+
+                    apply_diffs(channels, diffs);
+                    channels.sort_by(|a, b| a.val.title.cmp(&b.val.title));
                     Ok(true)
                 }
-                RepoResponse::UpdatedChannel(channel) => {
-                    if let Some(channels) = &mut self.channels {
-                        channels.retain(|c| c.val.id != channel.val.id);
-                        channels.push(channel);
-                        channels.sort_by(|a, b| a.val.title.cmp(&b.val.title));
-                        Ok(true)
-                    } else {
-                        Ok(false)
-                    }
-                }
                 _ => Ok(false),
             },
             Message::SetShowAll(show_all) => {
@@ -165,7 +157,7 @@ impl Component for ChannelList {
         let cb = ctx.link().callback(Message::RepoMessage);
         let mut repo = Repo::bridge(cb);
 
-        repo.send(RepoRequest::GetChannels);
+        repo.send(RepoRequest::Subscribe(Query::Channels));
 
         Self {
             channels: None,
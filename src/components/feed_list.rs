@@ -1,4 +1,6 @@
-use crate::agents::repo::{Repo, Request as RepoRequest, Response as RepoResponse};
+use crate::agents::repo::{
+    apply_diffs, Query, Repo, Request as RepoRequest, Response as RepoResponse,
+};
 use crate::objects::{FeedVal, JsError};
 use yew::prelude::*;
 use yew_agent::{Bridge, Bridged};
@@ -63,7 +65,7 @@ impl Component for FeedList {
         let cb = ctx.link().callback(Message::RepoMessage);
         let mut repo = Repo::bridge(cb);
 
-        repo.send(RepoRequest::GetFeeds);
+        repo.send(RepoRequest::Subscribe(Query::Feeds));
 
         Self {
             feeds: None,
@@ -75,8 +77,8 @@ impl Component for FeedList {
     fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             Message::RepoMessage(response) => match response {
-                RepoResponse::Feeds(res) => {
-                    self.feeds = Some(res);
+                RepoResponse::FeedsChanged(Query::Feeds, diffs) => {
+                    apply_diffs(self.feeds.get_or_insert_with(Vec::new), diffs);
                     true
                 }
                 _ => false,
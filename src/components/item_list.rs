@@ -2,7 +2,7 @@ use std::cmp::Ordering;
 
 use crate::agents::{
     notifier,
-    repo::{Repo, Request as RepoRequest, Response as RepoResponse},
+    repo::{apply_diffs, Query, Repo, Request as RepoRequest, Response as RepoResponse},
 };
 use crate::components::item_list_compact::ItemListCompact;
 use crate::objects::{Item, JsError};
@@ -16,6 +16,7 @@ pub struct ItemList {
     repo: Box<dyn Bridge<Repo>>,
     keys: Option<Vec<String>>,
     current_index: usize,
+    query: Option<Query>,
     notifier: Dispatcher<notifier::Notifier>,
 }
 
@@ -131,12 +132,17 @@ impl ItemList {
                         return Ok(false);
                     }
 
+                    let query =
+                        Query::ItemsByChannelIdYearMonth(ctx.props().channel_id, keys[idx].clone());
+
+                    if let Some(query) = self.query.take() {
+                        self.repo.send(RepoRequest::Unsubscribe(query));
+                    }
+
                     self.current_index = idx;
                     self.items = None;
-                    self.repo.send(RepoRequest::GetItemsByChannelIdYearMonth(
-                        ctx.props().channel_id,
-                        keys[idx].clone(),
-                    ));
+                    self.repo.send(RepoRequest::Subscribe(query.clone()));
+                    self.query = Some(query);
                 }
                 Ok(false)
             }
@@ -146,34 +152,23 @@ impl ItemList {
                     ctx.link().send_message(Message::UpdateCurrentIndex(0));
                     Ok(true)
                 }
-                RepoResponse::Items(mut res) => {
+                RepoResponse::ItemsChanged(query, diffs) => {
+                    if self.query.as_ref() != Some(&query) {
+                        return Ok(false);
+                    }
+
+                    let items = self.items.get_or_insert_with(Vec::new);
+
+                    apply_diffs(items, diffs);
                     // unwrap is safe, as a default is provided
-                    res.sort_by(|a, b| {
+                    items.sort_by(|a, b| {
                         b.get_date()
                             .partial_cmp(&a.get_date())
                             .or(Some(Ordering::Equal))
                             .unwrap()
                     });
-
-                    self.items = Some(res);
                     Ok(true)
                 }
-                RepoResponse::UpdatedItem(item) => match &mut self.items {
-                    Some(items) => {
-                        match items
-                            .iter()
-                            .enumerate()
-                            .find(|(_index, iter_item)| iter_item.get_id() == item.get_id())
-                        {
-                            Some((index, _)) => {
-                                items[index] = item;
-                                Ok(true)
-                            }
-                            None => Ok(false),
-                        }
-                    }
-                    None => Ok(false),
-                },
                 _ => Ok(false),
             },
         }
@@ -199,6 +194,7 @@ impl Component for ItemList {
             repo,
             current_index: 0,
             keys: None,
+            query: None,
             notifier: notifier::Notifier::dispatcher(),
         }
     }
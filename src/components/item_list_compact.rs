@@ -1,17 +1,26 @@
 use super::{Icon, IconStyle};
 use crate::{
-    agents::{notifier, repo},
-    objects::JsError,
+    agents::{notifier, player, queue, repo},
+    objects::{EnclosureMeta, JsError},
+    utils,
 };
 use podcast_player_common::{item_meta::DownloadStatus, Item};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 use yew::{prelude::*, Component, Properties};
-use yew_agent::{Dispatched, Dispatcher};
+use yew_agent::{Bridge, Bridged, Dispatched, Dispatcher};
 
 pub struct ItemListCompact {
-    repo: Dispatcher<repo::Repo>,
+    repo: Box<dyn Bridge<repo::Repo>>,
+    player: Dispatcher<player::Player>,
+    queue: Dispatcher<queue::Queue>,
     show_content: bool,
     notifier: Dispatcher<notifier::Notifier>,
+    enclosure_metas: HashMap<Uuid, EnclosureMeta>,
+    /// items the enclosure metadata was requested for
+    requested_metas: HashSet<Uuid>,
+    /// embedded images (data URLs); `None` while being requested
+    images: HashMap<Uuid, Option<String>>,
 }
 
 #[derive(Properties, Clone, PartialEq)]
@@ -19,19 +28,51 @@ pub struct Props {
     pub items: Vec<Item>,
     pub show_details: bool,
     pub on_selected: Option<Callback<Item>>,
+    /// the items are the play queue (in order) and can be reordered and removed
+    #[prop_or_default]
+    pub queued: bool,
 }
 
 pub enum Message {
+    RepoMessage(repo::Response),
     ToggleShowContent,
     ToggleNew(Uuid),
+    TogglePlayed(Uuid),
     ToggleDownload(Uuid),
+    Play(Uuid, player::SourceMode),
+    Queue(queue::Request),
 }
 
 impl ItemListCompact {
     fn process_update(&mut self, ctx: &Context<Self>, msg: Message) -> Result<bool, JsError> {
         match msg {
+            Message::RepoMessage(response) => match response {
+                repo::Response::EnclosureMetas(metas) => {
+                    // the metadata is requested per item; items without enclosure have none
+                    let update = !metas.is_empty();
+
+                    for meta in metas {
+                        self.enclosure_metas.insert(meta.id, meta);
+                    }
+
+                    self.request_images(ctx);
+                    Ok(update)
+                }
+                repo::Response::Image(item_id, image) => {
+                    // images extracted for other items are sent to all subscribers
+                    match self.images.contains_key(&item_id) {
+                        true => {
+                            self.images.insert(item_id, image);
+                            Ok(true)
+                        }
+                        false => Ok(false),
+                    }
+                }
+                _ => Ok(false),
+            },
             Message::ToggleShowContent => {
                 self.show_content = !self.show_content;
+                self.request_images(ctx);
                 Ok(true)
             }
             Message::ToggleNew(item_id) => {
@@ -47,6 +88,27 @@ impl ItemListCompact {
                 self.repo.send(repo::Request::UpdateItem(item));
                 Ok(false)
             }
+            Message::TogglePlayed(item_id) => {
+                let item = ctx
+                    .props()
+                    .items
+                    .iter()
+                    .find(|i| i.get_id() == item_id)
+                    .ok_or("item not found")?;
+                let item = match item.get_play_count() {
+                    0 => {
+                        let mut item = item.clone();
+
+                        item.increment_play_count();
+                        item.set_playback_time(None);
+                        item
+                    }
+                    _ => utils::reset_play_count(item)?,
+                };
+
+                self.repo.send(repo::Request::UpdateItem(item));
+                Ok(false)
+            }
             Message::ToggleDownload(item_id) => {
                 let mut item = ctx
                     .props()
@@ -72,17 +134,82 @@ impl ItemListCompact {
 
                 Ok(false)
             }
+            Message::Play(item_id, mode) => {
+                let item = ctx
+                    .props()
+                    .items
+                    .iter()
+                    .find(|i| i.get_id() == item_id)
+                    .ok_or("item not found")?
+                    .clone();
+
+                self.player
+                    .send(player::Request::SetSource(item, mode, None));
+                self.player.send(player::Request::Play);
+                Ok(false)
+            }
+            Message::Queue(request) => {
+                self.queue.send(request);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Requests the enclosure metadata of the listed items not requested yet.
+    fn request_enclosure_metas(&mut self, ctx: &Context<Self>) {
+        let item_ids: Vec<Uuid> = ctx
+            .props()
+            .items
+            .iter()
+            .map(|item| item.get_id())
+            .filter(|item_id| self.requested_metas.insert(*item_id))
+            .collect();
+
+        if !item_ids.is_empty() {
+            self.repo.send(repo::Request::GetEnclosureMetas(item_ids));
         }
     }
 
-    fn view_card_content(&self, ctx: &yew::Context<Self>, item: &Item) -> Html {
+    /// Requests the embedded images of the items shown with their details.
+    fn request_images(&mut self, ctx: &Context<Self>) {
+        if !self.show_content {
+            return;
+        }
+
+        for item in &ctx.props().items {
+            let item_id = item.get_id();
+
+            if let Some(meta) = self.enclosure_metas.get(&item_id) {
+                if meta.image && !self.images.contains_key(&item_id) {
+                    self.images.insert(item_id, None);
+                    self.repo.send(repo::Request::GetImage(item_id));
+                }
+            }
+        }
+    }
+
+    fn view_card_content(&self, ctx: &yew::Context<Self>, position: usize, item: &Item) -> Html {
         let id = item.get_id();
+        let queue_item = item.clone();
+        let play_next_item = item.clone();
+        let duration = match item.get_download_status() {
+            DownloadStatus::Ok => self.enclosure_metas.get(&id).and_then(|meta| meta.duration),
+            _ => None,
+        };
 
         html! {<div class="card-content">
+            {match self.images.get(&id) {
+                Some(Some(image)) => html! {<figure class="image is-128x128 mb-3"><img src={image.clone()}/></figure>},
+                _ => html! {},
+            }}
             <div class="field is-grouped is-grouped-multiline">
                 <div class="control">
                     <div class="tags">
                         <span class="tag">{item.get_date().format("%Y-%m-%d").to_string()}</span>
+                        {match duration {
+                            Some(duration) => html! {<span class="tag">{format!("{}:{:02}", (duration / 60.0) as u64, (duration % 60.0) as u64)}</span>},
+                            None => html! {},
+                        }}
                     </div>
                 </div>
                 <div class="control">
@@ -92,18 +219,47 @@ impl ItemListCompact {
                     </div>
                 </div>
             </div>
+            {match (item.get_playback_time(), duration) {
+                (Some(playback_time), Some(duration)) => html! {
+                    <progress class="progress is-small is-primary" title={format!("{:.0} %", playback_time / duration * 100.0)} value={playback_time.to_string()} max={duration.to_string()}/>
+                },
+                (_, _) => html! {},
+            }}
             <p class="buttons">
                 {match item.get_new() {
                     true => html!(<button class="button is-primary" onclick={ctx.link().callback(move |_| Message::ToggleNew(id))}><Icon name="star" style={IconStyle::Filled}/><span>{"new"}</span></button>),
                     false => html!(<button class="button" onclick={ctx.link().callback(move |_| Message::ToggleNew(id))}><Icon name="star_outline" style={IconStyle::Filled}/><span>{"new"}</span></button>),
                 }}
-                <button class="button is-primary" onclick={ctx.link().callback(move |_| Message::ToggleDownload(id))}>{match item.get_download_status() {
+                {match item.get_play_count() {
+                    0 => html!(<button class="button" onclick={ctx.link().callback(move |_| Message::TogglePlayed(id))}><Icon name="check_circle_outline" style={IconStyle::Filled}/><span>{"mark played"}</span></button>),
+                    _ => html!(<button class="button is-primary" onclick={ctx.link().callback(move |_| Message::TogglePlayed(id))}><Icon name="check_circle" style={IconStyle::Filled}/><span>{"mark unplayed"}</span></button>),
+                }}
+                <button class="button is-primary" title={self.enclosure_metas.get(&id).and_then(|meta| meta.error.clone())} onclick={ctx.link().callback(move |_| Message::ToggleDownload(id))}>{match item.get_download_status() {
                     DownloadStatus::Pending => html!{<><Icon name="cloud_queue" style={IconStyle::Filled}/><span>{"download pending"}</span></>},
                     DownloadStatus::Ok => html!{<><Icon name="cloud_done" style={IconStyle::Filled}/><span>{"download ok"}</span></>},
                     DownloadStatus::InProgress => html!{<><Icon name="cloud_sync" style={IconStyle::Filled}/><span>{"downloading"}</span></>},
                     DownloadStatus::Error => html!{<><Icon name="cloud_off" style={IconStyle::Filled}/><span>{"download error"}</span></>},
                     _ => html!{<span>{"download"}</span>}
                 }}</button>
+                {match item.get_download_status() {
+                    DownloadStatus::Ok => html!{},
+                    // episodes being downloaded are played from the download as it progresses
+                    DownloadStatus::InProgress => html!{<button class="button" onclick={ctx.link().callback(move |_| Message::Play(id, player::SourceMode::Local))}><Icon name="play_arrow" style={IconStyle::Filled}/><span>{"play"}</span></button>},
+                    _ => html!{<button class="button" onclick={ctx.link().callback(move |_| Message::Play(id, player::SourceMode::Stream))}><Icon name="podcasts" style={IconStyle::Filled}/><span>{"stream"}</span></button>},
+                }}
+            </p>
+            <p class="buttons">
+                {match ctx.props().queued {
+                    true => html!{<>
+                        <button class="button" disabled={position == 0} onclick={ctx.link().callback(move |_| Message::Queue(queue::Request::Move(id, position.saturating_sub(1))))}><Icon name="arrow_upward" style={IconStyle::Filled}/></button>
+                        <button class="button" disabled={position + 1 == ctx.props().items.len()} onclick={ctx.link().callback(move |_| Message::Queue(queue::Request::Move(id, position + 1)))}><Icon name="arrow_downward" style={IconStyle::Filled}/></button>
+                        <button class="button" onclick={ctx.link().callback(move |_| Message::Queue(queue::Request::Remove(id)))}><Icon name="remove_circle_outline" style={IconStyle::Filled}/><span>{"remove"}</span></button>
+                    </>},
+                    false => html!{<>
+                        <button class="button" onclick={ctx.link().callback(move |_| Message::Queue(queue::Request::PlayNext(play_next_item.clone())))}><Icon name="playlist_play" style={IconStyle::Filled}/><span>{"play next"}</span></button>
+                        <button class="button" onclick={ctx.link().callback(move |_| Message::Queue(queue::Request::Append(queue_item.clone())))}><Icon name="playlist_add" style={IconStyle::Filled}/><span>{"queue"}</span></button>
+                    </>},
+                }}
             </p>
         </div>}
     }
@@ -114,11 +270,19 @@ impl Component for ItemListCompact {
     type Properties = Props;
 
     fn create(ctx: &yew::Context<Self>) -> Self {
-        Self {
-            repo: repo::Repo::dispatcher(),
+        let mut list = Self {
+            repo: repo::Repo::bridge(ctx.link().callback(Message::RepoMessage)),
+            player: player::Player::dispatcher(),
+            queue: queue::Queue::dispatcher(),
             show_content: ctx.props().show_details,
             notifier: notifier::Notifier::dispatcher(),
-        }
+            enclosure_metas: HashMap::new(),
+            requested_metas: HashSet::new(),
+            images: HashMap::new(),
+        };
+
+        list.request_enclosure_metas(ctx);
+        list
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
@@ -134,7 +298,7 @@ impl Component for ItemListCompact {
     fn view(&self, ctx: &yew::Context<Self>) -> Html {
         html! {
             <div class="columns is-multiline">
-                { ctx.props().items.iter().map(|i| {
+                { ctx.props().items.iter().enumerate().map(|(position, i)| {
                     let onselect_item = i.clone();
                     let on_selected = ctx.props().on_selected.clone();
                     html! { <div class="column is-one-quarter"><div class="card">
@@ -146,7 +310,7 @@ impl Component for ItemListCompact {
                         <button class="card-header-icon" onclick={ctx.link().callback(|_| Message::ToggleShowContent)}><Icon name={match self.show_content { true => "expand_less", false => "expand_more"}} style={IconStyle::Outlined}/></button>
                     </header>
                     {if self.show_content {
-                        self.view_card_content(ctx, i)
+                        self.view_card_content(ctx, position, i)
                     } else {
                         html!{}
                     }}
@@ -157,6 +321,8 @@ impl Component for ItemListCompact {
 
     fn changed(&mut self, ctx: &Context<Self>) -> bool {
         self.show_content = ctx.props().show_details;
+        self.request_enclosure_metas(ctx);
+        self.request_images(ctx);
 
         true
     }
@@ -1,30 +1,44 @@
 use crate::{
-    agents::{notifier, player, repo},
+    agents::{notifier, player, queue, repo},
     components::{
         icon::{Icon, IconStyle},
         item_list_compact::ItemListCompact,
-        Range,
+        BookmarkList, Range,
     },
-    objects::{DownloadStatus, Item, JsError},
+    objects::{AudioProcessing, Chapter, IntroOutro, Item, JsError, PlayerConfig, SkipIntervals},
 };
 use podcast_player_common::Channel;
-use std::cmp::Ordering;
+use web_sys::HtmlInputElement;
 use yew::prelude::*;
 use yew_agent::{Bridge, Bridged, Dispatched, Dispatcher};
 
 pub enum Tab {
     Unplayed,
     Downloaded,
+    Queue,
 }
 
 pub struct Player {
-    _repo: Box<dyn Bridge<repo::Repo>>,
+    repo: Box<dyn Bridge<repo::Repo>>,
     player: Box<dyn Bridge<player::Player>>,
+    queue: Box<dyn Bridge<queue::Queue>>,
+    config: Option<PlayerConfig>,
     items: Option<Vec<Item>>,
+    queued_items: Option<Vec<Item>>,
     source: Option<(Item, Channel)>,
+    /// image embedded in the enclosure of the current source (data URL)
+    image: Option<String>,
     duration: Option<f64>,
+    current_time: f64,
     notifier: Dispatcher<notifier::Notifier>,
     is_playing: bool,
+    is_buffering: bool,
+    /// error of the current source
+    error: Option<String>,
+    sleep_timer: Option<(player::SleepTimerMode, f64)>,
+    chapters: Vec<Chapter>,
+    /// note of the next bookmark
+    bookmark_note: String,
     show_sliders: bool,
     tab: Tab,
     status_obtained: bool,
@@ -32,13 +46,29 @@ pub struct Player {
 pub enum Message {
     RepoMessage(repo::Response),
     PlayerMessage(player::Response),
+    QueueMessage(queue::Response),
+    ClearQueue,
     SetSource(Option<Item>),
     Play,
     Pause,
+    SkipForward,
+    SkipBackward,
+    SetSkipForward(String),
+    SetSkipBackward(String),
+    ResetSkipIntervals,
+    ToggleNormalize,
+    ToggleSkipSilence,
+    SetIntro(String),
+    SetOutro(String),
     TimeChange(String),
     VolumeChange(String),
     PlaybackRateChange(String),
     ToggleShowSliders,
+    SetSleepTimer(Option<player::SleepTimerMode>),
+    ExtendSleepTimer,
+    JumpTo(f64),
+    SetBookmarkNote(String),
+    AddBookmark,
     SwitchTab(Tab),
 }
 
@@ -51,12 +81,21 @@ impl Player {
                         <ul>
                             <li class="is-active"><a>{"unplayed"}</a></li>
                             <li><a onclick={ctx.link().callback(|_| Message::SwitchTab(Tab::Downloaded))}>{"downloaded"}</a></li>
+                            <li><a onclick={ctx.link().callback(|_| Message::SwitchTab(Tab::Queue))}>{"queue"}</a></li>
                         </ul>
                     },
                     Tab::Downloaded => html!{
                         <ul>
                             <li><a onclick={ctx.link().callback(|_| Message::SwitchTab(Tab::Unplayed))}>{"unplayed"}</a></li>
                             <li class="is-active"><a>{"downloaded"}</a></li>
+                            <li><a onclick={ctx.link().callback(|_| Message::SwitchTab(Tab::Queue))}>{"queue"}</a></li>
+                        </ul>
+                    },
+                    Tab::Queue => html!{
+                        <ul>
+                            <li><a onclick={ctx.link().callback(|_| Message::SwitchTab(Tab::Unplayed))}>{"unplayed"}</a></li>
+                            <li><a onclick={ctx.link().callback(|_| Message::SwitchTab(Tab::Downloaded))}>{"downloaded"}</a></li>
+                            <li class="is-active"><a>{"queue"}</a></li>
                         </ul>
                     }
                 }}
@@ -65,25 +104,147 @@ impl Player {
     }
 
     fn view_item_list(&self, ctx: &Context<Self>) -> Html {
+        if let Tab::Queue = self.tab {
+            return match &self.queued_items {
+                Some(items) if !items.is_empty() => html! {
+                    <>
+                        <ItemListCompact items={items.clone()} show_details={true} queued={true} on_selected={ctx.link().callback(|i| Message::SetSource(Some(i)))} />
+                        <p class="buttons is-centered">
+                            <button class="button" onclick={ctx.link().callback(|_| Message::ClearQueue)}><Icon name="clear_all" style={IconStyle::Filled}/><span>{"clear"}</span></button>
+                        </p>
+                    </>
+                },
+                Some(_) => html! {<p class="has-text-centered">{"the queue is empty"}</p>},
+                None => html!(),
+            };
+        }
+
         match &self.items {
             Some(items) => {
                 let filtered_items: Vec<Item> = items
                     .iter()
                     .filter(|i| match self.tab {
-                        Tab::Downloaded => true,
                         Tab::Unplayed => i.get_play_count() == 0,
+                        _ => true,
                     })
                     .map(|i| i.clone())
                     .collect();
                 html! {<ItemListCompact items={filtered_items} show_details={match self.tab {
-                    Tab::Downloaded => true,
-                    Tab::Unplayed => false
+                    Tab::Unplayed => false,
+                    _ => true,
                 }} on_selected={ctx.link().callback(|i| Message::SetSource(Some(i)))} />}
             }
             None => html!(),
         }
     }
 
+    /// Remaining time of the sleep timer; the timer can be set, while the sliders are shown.
+    fn view_sleep_timer(&self, ctx: &Context<Self>) -> Html {
+        match (&self.sleep_timer, self.show_sliders) {
+            (Some((mode, remaining)), _) => html! {
+                <div class="card-content">
+                    <div class="level is-mobile">
+                        <div class="level-left">
+                            <div class="level-item"><Icon name="bedtime" style={IconStyle::Outlined}/></div>
+                            <div class="level-item">{match mode {
+                                player::SleepTimerMode::Duration(_) => format!("stopping in {}", self.format_time(*remaining)),
+                                player::SleepTimerMode::EndOfEpisode => String::from("stopping at the end of the episode"),
+                            }}</div>
+                        </div>
+                        <div class="level-right">
+                            <div class="level-item buttons">
+                                <button class="button" onclick={ctx.link().callback(|_| Message::ExtendSleepTimer)}>{"+5 min"}</button>
+                                <button class="button" onclick={ctx.link().callback(|_| Message::SetSleepTimer(None))}><Icon name="close" style={IconStyle::Outlined}/></button>
+                            </div>
+                        </div>
+                    </div>
+                </div>
+            },
+            (None, true) => html! {
+                <div class="card-content">
+                    <div class="buttons is-centered">
+                        <Icon name="bedtime" style={IconStyle::Outlined}/>
+                        {[15, 30, 60].iter().map(|minutes| {
+                            let seconds = *minutes as f64 * 60.0;
+
+                            html! {<button class="button" onclick={ctx.link().callback(move |_| Message::SetSleepTimer(Some(player::SleepTimerMode::Duration(seconds))))}>{format!("{} min", minutes)}</button>}
+                        }).collect::<Html>()}
+                        <button class="button" onclick={ctx.link().callback(|_| Message::SetSleepTimer(Some(player::SleepTimerMode::EndOfEpisode)))}>{"end of episode"}</button>
+                    </div>
+                </div>
+            },
+            (None, false) => html! {},
+        }
+    }
+
+    /// Chapters of the current source; the current chapter is highlighted.
+    fn view_chapters(&self, ctx: &Context<Self>) -> Html {
+        if !self.show_sliders || self.chapters.is_empty() {
+            return html! {};
+        }
+
+        let current = self
+            .chapters
+            .iter()
+            .rposition(|chapter| chapter.start <= self.current_time);
+
+        html! {
+            <div class="card-content">
+                <aside class="menu">
+                    <ul class="menu-list">
+                        {self.chapters.iter().enumerate().map(|(index, chapter)| {
+                            let start = chapter.start;
+
+                            html! {
+                                <li><a class={classes!(match current == Some(index) { true => Some("is-active"), false => None })} onclick={ctx.link().callback(move |_| Message::JumpTo(start))}>
+                                    {format!("{} {}", self.format_time(start), chapter.title)}
+                                </a></li>
+                            }
+                        }).collect::<Html>()}
+                    </ul>
+                </aside>
+            </div>
+        }
+    }
+
+    /// Bookmarks of the current source and a field to bookmark the current time.
+    fn view_bookmarks(&self, ctx: &Context<Self>) -> Html {
+        match (&self.source, self.show_sliders) {
+            (Some(source), true) => html! {
+                <div class="card-content">
+                    <div class="field has-addons">
+                        <div class="control is-expanded">
+                            <input class="input" type="text" placeholder="note" value={self.bookmark_note.clone()} onchange={ctx.link().callback(|e: Event| Message::SetBookmarkNote(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                        <div class="control">
+                            <button class="button" onclick={ctx.link().callback(|_| Message::AddBookmark)}><Icon name="bookmark_add" style={IconStyle::Outlined}/><span>{format!("bookmark {}", self.format_time(self.current_time))}</span></button>
+                        </div>
+                    </div>
+                    <BookmarkList item_id={Some(source.0.get_id())} current_source={true}/>
+                </div>
+            },
+            (_, _) => html! {},
+        }
+    }
+
+    /// Artwork of the current source; the channel image is shown, if the episode has none.
+    fn view_image(&self) -> Html {
+        let image = match (&self.image, &self.source) {
+            (Some(image), _) => image.clone(),
+            (None, Some(source)) => source.1.val.image.clone(),
+            (None, None) => return html! {},
+        };
+
+        match self.show_sliders && !image.is_empty() {
+            true => html! {
+                <div class="card-image">
+                    <figure class="image is-square"><img src={image}/></figure>
+                </div>
+            },
+            false => html! {},
+        }
+    }
+
     fn view_sliders(&self, ctx: &Context<Self>) -> Html {
         match self.show_sliders {
             true => {
@@ -91,25 +252,25 @@ impl Player {
                     <div class="card-content">
                         {match (&self.source, self.duration) {
                             (Some(source), Some(duration)) => {
-                                let current_time = match source.0.get_playback_time() {
-                                    Some(curr_time) => curr_time,
-                                    None => 0.0
-                                };
+                                let current_time = self.current_time;
                                 html! {
                                 <>
-                                    <Range min="0" step="any" value={current_time.to_string()} max={duration.to_string()} onchange={ctx.link().callback(|e| Message::TimeChange(e))}/>
+                                    <Range min="0" step="any" value={current_time.to_string()} max={duration.to_string()} ticks={self.chapters.iter().map(|c| c.start).collect::<Vec<f64>>()} onchange={ctx.link().callback(Message::TimeChange)}/>
                                     <div class="columns is-mobile">
                                         <div class="column is-one-third has-text-left">{self.format_time(current_time)}</div>
                                         <div class="column is-one-third has-text-centered">{format!("{}@{}", self.format_time(duration / source.1.meta.playback_rate), source.1.meta.playback_rate)}</div>
                                         <div class="column is-one-third has-text-right">{self.format_time(duration)}</div>
                                     </div>
+                                    {self.view_skip_intervals(ctx, source)}
+                                    {self.view_intro_outro(ctx, source)}
+                                    {self.view_audio_processing(ctx, source)}
                                     <Range min="0" step="0.1" value={source.1.meta.volume.to_string()} max="1.0" onchange={ctx.link().callback(|e| Message::VolumeChange(e))}/>
                                     <div class="columns is-mobile">
                                         <div class="column is-one-third has-text-left"><Icon name="volume_down" style={IconStyle::Outlined}/></div>
                                         <div class="column is-one-third has-text-centered">{format!("{:.1}", source.1.meta.volume)}</div>
                                         <div class="column is-one-third has-text-right"><Icon name="volume_up" style={IconStyle::Outlined}/></div>
                                     </div>
-                                    <Range min="0.5" step="0.1" value={source.1.meta.playback_rate.to_string()} max="2.5" onchange={ctx.link().callback(|e| Message::PlaybackRateChange(e))}/>
+                                    <Range min={player::MIN_PLAYBACK_RATE.to_string()} step="0.1" value={source.1.meta.playback_rate.to_string()} max={player::MAX_PLAYBACK_RATE.to_string()} onchange={ctx.link().callback(Message::PlaybackRateChange)}/>
                                     <div class="columns is-mobile">
                                         <div class="column is-one-third has-text-left"><Icon name="play_arrow" style={IconStyle::Outlined}/></div>
                                         <div class="column is-one-third has-text-centered">{format!("{:.1}", source.1.meta.playback_rate)}</div>
@@ -148,6 +309,108 @@ impl Player {
         }
     }
 
+    /// Skip lengths of the channel of the current source.
+    fn view_skip_intervals(&self, ctx: &Context<Self>, source: &(Item, Channel)) -> Html {
+        match &self.config {
+            Some(config) => {
+                let intervals = config.skip_intervals(&source.1.val.id);
+                let own = config.channel_skip_intervals.contains_key(&source.1.val.id);
+
+                html! {
+                    <div class="field is-grouped is-grouped-centered">
+                        <div class="control">
+                            <input class="input" type="number" min="1" step="1" title="skip backward (seconds)" value={intervals.backward.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetSkipBackward(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                        <div class="control">
+                            <input class="input" type="number" min="1" step="1" title="skip forward (seconds)" value={intervals.forward.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetSkipForward(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                        <div class="control">
+                            <button class="button" disabled={!own} title="use the default skip lengths" onclick={ctx.link().callback(|_| Message::ResetSkipIntervals)}><Icon name="restart_alt" style={IconStyle::Outlined}/></button>
+                        </div>
+                    </div>
+                }
+            }
+            None => html! {},
+        }
+    }
+
+    /// Lengths of the intro and outro skipped for the channel of the current source.
+    fn view_intro_outro(&self, ctx: &Context<Self>, source: &(Item, Channel)) -> Html {
+        match &self.config {
+            Some(config) => {
+                let intro_outro = config.intro_outro(&source.1.val.id);
+
+                html! {
+                    <div class="field is-grouped is-grouped-centered">
+                        <div class="control">
+                            <input class="input" type="number" min="0" step="1" title="skip intro (seconds)" value={intro_outro.intro.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetIntro(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                        <div class="control">
+                            <input class="input" type="number" min="0" step="1" title="skip outro (seconds)" value={intro_outro.outro.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetOutro(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                    </div>
+                }
+            }
+            None => html! {},
+        }
+    }
+
+    /// Intro and outro lengths of the channel of the current source, changed by `update`.
+    fn channel_intro_outro(
+        &self,
+        update: impl FnOnce(&mut IntroOutro),
+    ) -> Result<IntroOutro, JsError> {
+        let config = self.config.as_ref().ok_or("configuration not loaded")?;
+        let source = self.source.as_ref().ok_or("source not set")?;
+        let mut intro_outro = config.intro_outro(&source.1.val.id);
+
+        update(&mut intro_outro);
+        Ok(intro_outro)
+    }
+
+    /// Audio processing of the channel of the current source.
+    fn view_audio_processing(&self, ctx: &Context<Self>, source: &(Item, Channel)) -> Html {
+        match &self.config {
+            Some(config) => {
+                let processing = config.audio_processing(&source.1.val.id);
+
+                html! {
+                    <p class="buttons is-centered">
+                        <button class={classes!("button", processing.normalize.then(|| "is-primary"))} title="level the loudness" onclick={ctx.link().callback(|_| Message::ToggleNormalize)}><Icon name="equalizer" style={IconStyle::Outlined}/><span>{"normalize"}</span></button>
+                        <button class={classes!("button", processing.skip_silence.then(|| "is-primary"))} title="speed up silent passages" onclick={ctx.link().callback(|_| Message::ToggleSkipSilence)}><Icon name="speed" style={IconStyle::Outlined}/><span>{"skip silence"}</span></button>
+                    </p>
+                }
+            }
+            None => html! {},
+        }
+    }
+
+    /// Audio processing of the channel of the current source, changed by `update`.
+    fn channel_audio_processing(
+        &self,
+        update: impl FnOnce(&mut AudioProcessing),
+    ) -> Result<AudioProcessing, JsError> {
+        let config = self.config.as_ref().ok_or("configuration not loaded")?;
+        let source = self.source.as_ref().ok_or("source not set")?;
+        let mut processing = config.audio_processing(&source.1.val.id);
+
+        update(&mut processing);
+        Ok(processing)
+    }
+
+    /// Skip lengths of the channel of the current source, changed by `update`.
+    fn channel_skip_intervals(
+        &self,
+        update: impl FnOnce(&mut SkipIntervals),
+    ) -> Result<SkipIntervals, JsError> {
+        let config = self.config.as_ref().ok_or("configuration not loaded")?;
+        let source = self.source.as_ref().ok_or("source not set")?;
+        let mut intervals = config.skip_intervals(&source.1.val.id).clone();
+
+        update(&mut intervals);
+        Ok(intervals)
+    }
+
     fn format_time(&self, time: f64) -> String {
         format!("{}:{:02}", (time / 60.0) as u64, (time % 60.0) as u64)
     }
@@ -158,58 +421,94 @@ impl Player {
                 self.tab = tab;
                 Ok(true)
             }
+            Message::ClearQueue => {
+                self.queue.send(queue::Request::Clear);
+                Ok(false)
+            }
+            Message::QueueMessage(response) => match response {
+                queue::Response::Queue(items) => {
+                    self.queued_items = Some(items);
+                    Ok(matches!(self.tab, Tab::Queue))
+                }
+                // the player agent advances the queue
+                queue::Response::Next(_) => Ok(false),
+            },
+            Message::SetSleepTimer(mode) => {
+                self.player.send(player::Request::SetSleepTimer(mode));
+                Ok(false)
+            }
+            Message::JumpTo(time) => {
+                self.player.send(player::Request::SetCurrentTime(time));
+                Ok(false)
+            }
+            Message::SetBookmarkNote(note) => {
+                self.bookmark_note = note;
+                Ok(true)
+            }
+            Message::AddBookmark => {
+                self.player.send(player::Request::AddBookmark(
+                    self.bookmark_note.trim().to_string(),
+                ));
+                self.bookmark_note.clear();
+                Ok(true)
+            }
+            Message::ExtendSleepTimer => {
+                self.player.send(player::Request::ExtendSleepTimer(300.0));
+                Ok(false)
+            }
             Message::ToggleShowSliders => {
                 self.show_sliders = !self.show_sliders;
                 Ok(true)
             }
             Message::RepoMessage(response) => match response {
-                repo::Response::Items(mut items) => {
-                    items.sort_by(|a, b| {
-                        a.get_date()
-                            .partial_cmp(&b.get_date())
-                            .unwrap_or(Ordering::Equal)
-                    });
-
-                    self.items = Some(items);
-                    self.set_item_from_playlist(ctx);
-
-                    Ok(true)
+                repo::Response::PlayerConfig(config) => {
+                    self.config = Some(config.unwrap_or_default());
+                    Ok(self.show_sliders)
                 }
-                repo::Response::UpdatedItem(item) => {
-                    let mut res = false;
-
-                    if let Some(source) = &mut self.source {
-                        if source.0.get_id() == item.get_id() {
-                            self.source = Some((item.clone(), source.1.clone()));
-                            res = true;
+                repo::Response::Image(item_id, image) => match &self.source {
+                    Some(source) if source.0.get_id() == item_id => {
+                        self.image = image;
+                        Ok(true)
+                    }
+                    _ => Ok(false),
+                },
+                repo::Response::ItemsChanged(repo::Query::ItemsByDownloadOk, diffs) => {
+                    if let Some(source) = &self.source {
+                        if let Some(item) = diffs.iter().rev().find_map(|diff| match diff {
+                            repo::Diff::Added(item) | repo::Diff::Updated(item)
+                                if item.get_id() == source.0.get_id() =>
+                            {
+                                Some(item.clone())
+                            }
+                            _ => None,
+                        }) {
+                            self.source = Some((item, source.1.clone()));
                         }
                     }
 
-                    if let Some(self_items) = &mut self.items {
-                        let len_before = self_items.len();
+                    let initial = self.items.is_none();
+                    let items = self.items.get_or_insert_with(Vec::new);
 
-                        self_items.retain(|i| i.get_id() != item.get_id());
-                        res = len_before != self_items.len();
+                    repo::apply_diffs(items, diffs);
+                    items.sort_by(|a, b| a.get_date().cmp(&b.get_date()));
 
-                        match item.get_download_status() {
-                            DownloadStatus::Ok => {
-                                self_items.push(item.clone());
-                                res = true;
-                            }
-                            _ => {}
-                        }
-                        self_items.sort_by(|a, b| a.get_date().cmp(&b.get_date()));
+                    if initial {
+                        self.set_item_from_playlist(ctx);
                     }
 
-                    Ok(res)
+                    Ok(true)
                 }
-                repo::Response::UpdatedChannel(channel) => {
+                repo::Response::ChannelsChanged(repo::Query::Channel(_), diffs) => {
                     let mut res = false;
 
-                    if let Some(source) = &mut self.source {
-                        if source.1.val.id == channel.val.id {
-                            self.source = Some((source.0.clone(), channel));
-                            res = true;
+                    for diff in diffs {
+                        if let repo::Diff::Added(channel) | repo::Diff::Updated(channel) = diff {
+                            if let Some(source) = &self.source {
+                                if source.1.val.id == channel.val.id {
+                                    self.source = Some((source.0.clone(), channel));
+                                    res = true;
+                                }
+                            }
                         }
                     }
 
@@ -225,19 +524,120 @@ impl Player {
                 self.player.send(player::Request::Play);
                 Ok(false)
             }
+            Message::SkipForward => {
+                self.player.send(player::Request::SkipForward);
+                Ok(false)
+            }
+            Message::SkipBackward => {
+                self.player.send(player::Request::SkipBackward);
+                Ok(false)
+            }
+            Message::SetSkipForward(value) => {
+                let value: f64 = value.parse()?;
+                let intervals =
+                    self.channel_skip_intervals(|intervals| intervals.forward = value.max(1.0))?;
+
+                self.player
+                    .send(player::Request::SetSkipIntervals(Some(intervals)));
+                Ok(false)
+            }
+            Message::SetSkipBackward(value) => {
+                let value: f64 = value.parse()?;
+                let intervals =
+                    self.channel_skip_intervals(|intervals| intervals.backward = value.max(1.0))?;
+
+                self.player
+                    .send(player::Request::SetSkipIntervals(Some(intervals)));
+                Ok(false)
+            }
+            Message::ResetSkipIntervals => {
+                self.player.send(player::Request::SetSkipIntervals(None));
+                Ok(false)
+            }
+            Message::SetIntro(value) => {
+                let value: f64 = value.parse()?;
+                let intro_outro =
+                    self.channel_intro_outro(|intro_outro| intro_outro.intro = value.max(0.0))?;
+
+                self.player
+                    .send(player::Request::SetIntroOutro(intro_outro));
+                Ok(false)
+            }
+            Message::SetOutro(value) => {
+                let value: f64 = value.parse()?;
+                let intro_outro =
+                    self.channel_intro_outro(|intro_outro| intro_outro.outro = value.max(0.0))?;
+
+                self.player
+                    .send(player::Request::SetIntroOutro(intro_outro));
+                Ok(false)
+            }
+            Message::ToggleNormalize => {
+                let processing = self.channel_audio_processing(|processing| {
+                    processing.normalize = !processing.normalize
+                })?;
+
+                self.player
+                    .send(player::Request::SetAudioProcessing(processing));
+                Ok(false)
+            }
+            Message::ToggleSkipSilence => {
+                let processing = self.channel_audio_processing(|processing| {
+                    processing.skip_silence = !processing.skip_silence
+                })?;
+
+                self.player
+                    .send(player::Request::SetAudioProcessing(processing));
+                Ok(false)
+            }
             Message::SetSource(source) => {
                 if let Some(item) = source {
-                    self.player.send(player::Request::SetSource(item.clone()));
+                    self.player.send(player::Request::SetSource(
+                        item.clone(),
+                        player::SourceMode::Local,
+                        None,
+                    ));
                 }
 
                 Ok(true)
             }
             Message::PlayerMessage(player_message) => match player_message {
                 player::Response::SourceSet(item, channel, duration) => {
+                    self.set_channel_subscription(&channel);
+                    self.image = None;
+                    self.repo.send(repo::Request::GetImage(item.get_id()));
+                    self.current_time = item.get_playback_time().unwrap_or(0.0);
                     self.source = Some((item, channel));
+                    self.duration = Some(duration);
+                    self.error = None;
+                    Ok(true)
+                }
+                player::Response::Error(error) => {
+                    self.error = Some(error.description);
+                    self.is_playing = false;
+                    self.is_buffering = false;
+                    Ok(true)
+                }
+                player::Response::TimeUpdate(current_time) => {
+                    self.current_time = current_time;
+                    Ok(self.show_sliders)
+                }
+                player::Response::DurationChange(duration) => {
                     self.duration = Some(duration);
                     Ok(true)
                 }
+                player::Response::Chapters(chapters) => {
+                    self.chapters = chapters;
+                    Ok(true)
+                }
+                player::Response::SleepTimer(sleep_timer) => {
+                    self.sleep_timer = sleep_timer;
+                    Ok(true)
+                }
+                player::Response::Buffering(is_buffering) => {
+                    self.is_buffering = is_buffering;
+                    Ok(true)
+                }
                 player::Response::Paused => {
                     self.is_playing = false;
                     Ok(true)
@@ -248,25 +648,15 @@ impl Player {
                 }
                 player::Response::End => {
                     self.is_playing = false;
-                    match (&self.source, &self.items) {
-                        (Some(curr_item), Some(items)) => {
-                            if let Some(new_item) = items.iter().find(|i| {
-                                i.get_id() != curr_item.0.get_id() && i.get_play_count() == 0
-                            }) {
-                                self.player
-                                    .send(player::Request::SetSource(new_item.clone()));
-                                self.player.send(player::Request::Play)
-                            }
-                        }
-                        (_, _) => {}
-                    };
-                    Ok(false)
+                    Ok(true)
                 }
                 player::Response::Status(status) => {
                     self.status_obtained = true;
 
                     match status {
                         Some(status) => {
+                            self.set_channel_subscription(&status.1);
+                            self.current_time = status.0.get_playback_time().unwrap_or(0.0);
                             self.source = Some((status.0, status.1));
                             self.duration = Some(status.2);
                             self.is_playing = status.3;
@@ -296,6 +686,25 @@ impl Player {
         }
     }
 
+    /// Follows updates of the channel of the current source (e.g., volume and playback rate).
+    fn set_channel_subscription(&mut self, channel: &Channel) {
+        if let Some(source) = &self.source {
+            if source.1.val.id == channel.val.id {
+                return;
+            }
+
+            self.repo
+                .send(repo::Request::Unsubscribe(repo::Query::Channel(
+                    source.1.val.id,
+                )));
+        }
+
+        self.repo
+            .send(repo::Request::Subscribe(repo::Query::Channel(
+                channel.val.id,
+            )));
+    }
+
     fn set_item_from_playlist(&mut self, ctx: &Context<Self>) {
         match (&self.items, self.status_obtained, &self.source) {
             (Some(items), true, None) => {
@@ -327,10 +736,18 @@ impl Component for Player {
                         <header class="card-header">
                             {match (&self.source, self.is_playing) {
                                 (Some(_), true) => html! {
-                                    <button class="card-header-icon" onclick={ctx.link().callback(|_| Message::Pause)}><Icon name="pause" style={IconStyle::Outlined}/></button>
+                                    <>
+                                        <button class="card-header-icon" onclick={ctx.link().callback(|_| Message::SkipBackward)}><Icon name="fast_rewind" style={IconStyle::Outlined}/></button>
+                                        <button class="card-header-icon" onclick={ctx.link().callback(|_| Message::Pause)}><Icon name="pause" style={IconStyle::Outlined}/></button>
+                                        <button class="card-header-icon" onclick={ctx.link().callback(|_| Message::SkipForward)}><Icon name="fast_forward" style={IconStyle::Outlined}/></button>
+                                    </>
                                 },
                                 (Some(_), false) => html! {
-                                    <button class="card-header-icon" onclick={ctx.link().callback(|_| Message::Play)}><Icon name="play_arrow" style={IconStyle::Outlined}/></button>
+                                    <>
+                                        <button class="card-header-icon" onclick={ctx.link().callback(|_| Message::SkipBackward)}><Icon name="fast_rewind" style={IconStyle::Outlined}/></button>
+                                        <button class="card-header-icon" onclick={ctx.link().callback(|_| Message::Play)}><Icon name="play_arrow" style={IconStyle::Outlined}/></button>
+                                        <button class="card-header-icon" onclick={ctx.link().callback(|_| Message::SkipForward)}><Icon name="fast_forward" style={IconStyle::Outlined}/></button>
+                                    </>
                                 },
                                 (None, _) => html! {
                                     <button class="card-header-icon" disabled={true}><Icon name="play_arrow" style={IconStyle::Outlined}/></button>
@@ -339,7 +756,19 @@ impl Component for Player {
                             <p class="card-header-title">{item_title}</p>
                             <button class="card-header-icon" onclick={ctx.link().callback(|_| Message::ToggleShowSliders)}><Icon name={match self.show_sliders { true => "expand_less", false => "expand_more"}} style={IconStyle::Outlined}/></button>
                         </header>
+                        {match self.is_buffering {
+                            true => html! {<progress class="progress is-small is-primary" max="100"/>},
+                            false => html! {},
+                        }}
+                        {match &self.error {
+                            Some(error) => html! {<div class="card-content"><div class="notification is-danger is-light">{error}</div></div>},
+                            None => html! {},
+                        }}
+                        { self.view_sleep_timer(ctx) }
+                        { self.view_image() }
                         { self.view_sliders(ctx) }
+                        { self.view_chapters(ctx) }
+                        { self.view_bookmarks(ctx) }
                     </div>
                 </section>
                 <section class="section">
@@ -354,20 +783,35 @@ impl Component for Player {
         let cb = ctx.link().callback(Message::RepoMessage);
         let mut repo = repo::Repo::bridge(cb);
 
-        repo.send(repo::Request::GetItemsByDownloadOk);
+        repo.send(repo::Request::Subscribe(repo::Query::ItemsByDownloadOk));
+        repo.send(repo::Request::GetPlayerConf(None));
 
         let mut player = player::Player::bridge(ctx.link().callback(Message::PlayerMessage));
 
         player.send(player::Request::GetStatus);
 
+        let mut queue = queue::Queue::bridge(ctx.link().callback(Message::QueueMessage));
+
+        queue.send(queue::Request::GetQueue);
+
         Self {
-            _repo: repo,
+            repo,
+            config: None,
             items: None,
+            queued_items: None,
+            queue,
             source: None,
+            image: None,
             player,
             duration: None,
+            current_time: 0.0,
             notifier: notifier::Notifier::dispatcher(),
             is_playing: false,
+            is_buffering: false,
+            error: None,
+            sleep_timer: None,
+            chapters: Vec::new(),
+            bookmark_note: String::new(),
             show_sliders: false,
             tab: Tab::Unplayed,
             status_obtained: false,
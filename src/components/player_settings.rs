@@ -0,0 +1,225 @@
+use crate::{
+    agents::{notifier, repo},
+    objects::{CompletionThreshold, JsError, PlayerConfig},
+};
+use std::mem::discriminant;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_agent::{Bridge, Bridged, Dispatched, Dispatcher};
+
+pub struct PlayerSettings {
+    repo: Box<dyn Bridge<repo::Repo>>,
+    config: Option<PlayerConfig>,
+    notifier: Dispatcher<notifier::Notifier>,
+}
+
+pub enum Message {
+    RepoMessage(repo::Response),
+    SetPositionSaveInterval(String),
+    ToggleDownloadStreamedEpisodes,
+    SetSkipForward(String),
+    SetSkipBackward(String),
+    SetResumeRewind(String),
+    SetResumeRewindAfter(String),
+    SetCompletionThresholdKind(CompletionThreshold),
+    SetCompletionThresholdValue(String),
+    SetCrossfade(String),
+}
+
+impl PlayerSettings {
+    fn update_config(&mut self, update: impl FnOnce(&mut PlayerConfig)) -> Result<(), JsError> {
+        let config = self.config.as_mut().ok_or("configuration not loaded")?;
+
+        update(config);
+        self.repo
+            .send(repo::Request::GetPlayerConf(Some(config.clone())));
+        Ok(())
+    }
+
+    fn process_update(&mut self, _ctx: &Context<Self>, msg: Message) -> Result<bool, JsError> {
+        match msg {
+            Message::RepoMessage(response) => match response {
+                repo::Response::PlayerConfig(config) => {
+                    self.config = Some(config.unwrap_or_default());
+                    Ok(true)
+                }
+                _ => Ok(false),
+            },
+            Message::SetPositionSaveInterval(value) => {
+                let value: f64 = value.parse()?;
+
+                self.update_config(|config| config.position_save_interval = value.max(1.0))?;
+                Ok(true)
+            }
+            Message::ToggleDownloadStreamedEpisodes => {
+                self.update_config(|config| {
+                    config.download_streamed_episodes = !config.download_streamed_episodes
+                })?;
+                Ok(true)
+            }
+            Message::SetSkipForward(value) => {
+                let value: f64 = value.parse()?;
+
+                self.update_config(|config| config.skip_intervals.forward = value.max(1.0))?;
+                Ok(true)
+            }
+            Message::SetSkipBackward(value) => {
+                let value: f64 = value.parse()?;
+
+                self.update_config(|config| config.skip_intervals.backward = value.max(1.0))?;
+                Ok(true)
+            }
+            Message::SetResumeRewind(value) => {
+                let value: f64 = value.parse()?;
+
+                self.update_config(|config| config.resume_rewind = value.max(0.0))?;
+                Ok(true)
+            }
+            Message::SetResumeRewindAfter(value) => {
+                let value: f64 = value.parse()?;
+
+                self.update_config(|config| config.resume_rewind_after = value.max(0.0))?;
+                Ok(true)
+            }
+            Message::SetCompletionThresholdKind(threshold) => {
+                self.update_config(|config| {
+                    // keep the value, if the kind does not change
+                    if discriminant(&config.completion_threshold) != discriminant(&threshold) {
+                        config.completion_threshold = threshold
+                    }
+                })?;
+                Ok(true)
+            }
+            Message::SetCompletionThresholdValue(value) => {
+                let value: f64 = value.parse()?;
+
+                self.update_config(|config| {
+                    config.completion_threshold = match config.completion_threshold {
+                        CompletionThreshold::Percentage(_) => {
+                            CompletionThreshold::Percentage(value.clamp(1.0, 100.0))
+                        }
+                        CompletionThreshold::Remaining(_) => {
+                            CompletionThreshold::Remaining(value.max(0.0))
+                        }
+                    }
+                })?;
+                Ok(true)
+            }
+            Message::SetCrossfade(value) => {
+                let value: f64 = value.parse()?;
+
+                self.update_config(|config| config.crossfade = value.clamp(0.0, 30.0))?;
+                Ok(true)
+            }
+        }
+    }
+}
+
+impl PlayerSettings {
+    fn view_completion_threshold(&self, ctx: &Context<Self>, config: &PlayerConfig) -> Html {
+        let (percentage, value) = match config.completion_threshold {
+            CompletionThreshold::Percentage(value) => (true, value),
+            CompletionThreshold::Remaining(value) => (false, value),
+        };
+
+        html! {
+            <div class="field">
+                <label class="label">{"mark episodes as played once reached"}</label>
+                <div class="field has-addons">
+                    <div class="control">
+                        <input class="input" type="number" min="0" step="1" value={value.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetCompletionThresholdValue(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                    </div>
+                    <div class="control">
+                        <button class={classes!("button", percentage.then(|| "is-primary"))} onclick={ctx.link().callback(|_| Message::SetCompletionThresholdKind(CompletionThreshold::Percentage(95.0)))}>{"% played"}</button>
+                    </div>
+                    <div class="control">
+                        <button class={classes!("button", (!percentage).then(|| "is-primary"))} onclick={ctx.link().callback(|_| Message::SetCompletionThresholdKind(CompletionThreshold::Remaining(30.0)))}>{"seconds remaining"}</button>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl Component for PlayerSettings {
+    type Message = Message;
+    type Properties = ();
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut repo = repo::Repo::bridge(ctx.link().callback(Message::RepoMessage));
+
+        repo.send(repo::Request::GetPlayerConf(None));
+
+        Self {
+            repo,
+            config: None,
+            notifier: notifier::Notifier::dispatcher(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match self.process_update(ctx, msg) {
+            Ok(res) => res,
+            Err(e) => {
+                self.notifier.send(notifier::Request::NotifyError(e));
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        match &self.config {
+            Some(config) => html! {
+                <section class="section">
+                    <div class="title">{"Player"}</div>
+                    <div class="field">
+                        <label class="label">{"save playback position every (seconds)"}</label>
+                        <div class="control">
+                            <input class="input" type="number" min="1" step="1" value={config.position_save_interval.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetPositionSaveInterval(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <div class="control">
+                            <label class="checkbox">
+                                <input type="checkbox" checked={config.download_streamed_episodes} onchange={ctx.link().callback(|_| Message::ToggleDownloadStreamedEpisodes)}/>
+                                {" download episodes when streaming them"}
+                            </label>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <label class="label">{"skip forward (seconds)"}</label>
+                        <div class="control">
+                            <input class="input" type="number" min="1" step="1" value={config.skip_intervals.forward.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetSkipForward(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <label class="label">{"skip backward (seconds)"}</label>
+                        <div class="control">
+                            <input class="input" type="number" min="1" step="1" value={config.skip_intervals.backward.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetSkipBackward(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <label class="label">{"rewind when resuming (seconds, 0 to disable)"}</label>
+                        <div class="control">
+                            <input class="input" type="number" min="0" step="1" value={config.resume_rewind.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetResumeRewind(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                    </div>
+                    <div class="field">
+                        <label class="label">{"only rewind after pauses of at least (seconds)"}</label>
+                        <div class="control">
+                            <input class="input" type="number" min="0" step="1" value={config.resume_rewind_after.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetResumeRewindAfter(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                    </div>
+                    {self.view_completion_threshold(ctx, config)}
+                    <div class="field">
+                        <label class="label">{"crossfade into the next queued episode (seconds, 0 for a gapless transition)"}</label>
+                        <div class="control">
+                            <input class="input" type="number" min="0" max="30" step="1" value={config.crossfade.to_string()} onchange={ctx.link().callback(|e: Event| Message::SetCrossfade(e.target_unchecked_into::<HtmlInputElement>().value()))}/>
+                        </div>
+                    </div>
+                </section>
+            },
+            None => html! {},
+        }
+    }
+}
@@ -17,6 +17,9 @@ pub struct Props {
     pub min: String,
     pub max: String,
     pub step: String,
+    /// values marked on the range (e.g., chapters)
+    #[prop_or_default]
+    pub ticks: Vec<f64>,
 }
 
 pub enum Message {
@@ -54,8 +57,24 @@ impl Component for Range {
     }
 
     fn view(&self, ctx: &yew::Context<Self>) -> yew::Html {
-        html! {
+        let input = html! {
             <input type="range" min={ctx.props().min.clone()} step={ctx.props().step.clone()} value={ctx.props().value.clone()} max={ctx.props().max.clone()} style="width: 100%" onfocus={ctx.link().callback(|e| Message::OnFocus(e))} onchange={ctx.link().callback(|e| Message::OnChange(e))}/>
+        };
+
+        match (
+            ctx.props().ticks.is_empty(),
+            ctx.props().min.parse::<f64>(),
+            ctx.props().max.parse::<f64>(),
+        ) {
+            (false, Ok(min), Ok(max)) if max > min => html! {
+                <div style="position: relative">
+                    {input}
+                    {ctx.props().ticks.iter().map(|tick| html! {
+                        <span style={format!("position: absolute; left: {}%; top: 0; height: 0.5em; width: 2px; background: currentColor; opacity: 0.5; pointer-events: none", (tick - min) / (max - min) * 100.0)}/>
+                    }).collect::<yew::Html>()}
+                </div>
+            },
+            _ => input,
         }
     }
 
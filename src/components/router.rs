@@ -13,6 +13,12 @@ pub enum AppRoute {
     FeedsPage,
     #[at("/info")]
     InfoPage,
+    #[at("/settings")]
+    SettingsPage,
+    #[at("/stats")]
+    StatsPage,
+    #[at("/bookmarks")]
+    BookmarksPage,
     #[at("/")]
     Home,
 }
@@ -45,6 +51,9 @@ fn switch(routes: &AppRoute) -> Html {
         AppRoute::ChannelsPage => html! {<ChannelsPage/>},
         AppRoute::FeedsPage => html! {<FeedsPage/>},
         AppRoute::InfoPage => html! {<InfoPage/>},
+        AppRoute::SettingsPage => html! {<SettingsPage/>},
+        AppRoute::StatsPage => html! {<StatsPage/>},
+        AppRoute::BookmarksPage => html! {<BookmarksPage/>},
         AppRoute::ItemsPage { channel_id } => html! {<ItemsPage channel_id={channel_id.clone()}/>},
     }
 }
@@ -0,0 +1,145 @@
+use super::router::AppRoute;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, KeyboardEvent};
+
+/// Change of the playback rate per key press.
+const PLAYBACK_RATE_STEP: f64 = 0.1;
+
+/// Action triggered by a keyboard shortcut.
+#[derive(Clone, PartialEq)]
+pub enum Shortcut {
+    TogglePlayback,
+    SkipForward,
+    SkipBackward,
+    /// changes the playback rate by the given amount
+    ChangePlaybackRate(f64),
+    /// seeks to the given fraction of the episode
+    SeekToFraction(f64),
+    AddBookmark,
+    Navigate(AppRoute),
+    ToggleHelp,
+    CloseHelp,
+}
+
+/// Keys and descriptions of the shortcuts (shown in the help overlay).
+pub const SHORTCUTS_HELP: &[(&str, &str)] = &[
+    ("space", "play / pause"),
+    ("→", "skip forward"),
+    ("←", "skip backward"),
+    ("shift + →", "increase playback rate"),
+    ("shift + ←", "decrease playback rate"),
+    ("0 - 9", "seek to 0 % - 90 %"),
+    ("m", "bookmark the current time"),
+    ("h", "go to home"),
+    ("c", "go to channels"),
+    ("f", "go to feeds"),
+    ("b", "go to bookmarks"),
+    ("t", "go to statistics"),
+    ("s", "go to settings"),
+    ("i", "go to info"),
+    ("?", "show / hide this help"),
+    ("esc", "close this help"),
+];
+
+impl Shortcut {
+    /// Returns the shortcut for a key press; key presses with modifiers (other than shift) and those while editing text are ignored.
+    /// Space presses a focused button instead of toggling the playback; the arrow keys move a focused slider instead of skipping.
+    pub fn from_event(event: &KeyboardEvent) -> Option<Self> {
+        let focus = Focus::of(event);
+
+        if event.ctrl_key() || event.alt_key() || event.meta_key() || focus == Focus::Editing {
+            return None;
+        }
+
+        match (event.key().as_str(), event.shift_key()) {
+            (" ", false) if focus == Focus::Button => None,
+            (" ", false) => Some(Self::TogglePlayback),
+            ("ArrowRight" | "ArrowLeft", _) if focus == Focus::Slider => None,
+            ("ArrowRight", false) => Some(Self::SkipForward),
+            ("ArrowLeft", false) => Some(Self::SkipBackward),
+            ("ArrowRight", true) => Some(Self::ChangePlaybackRate(PLAYBACK_RATE_STEP)),
+            ("ArrowLeft", true) => Some(Self::ChangePlaybackRate(-PLAYBACK_RATE_STEP)),
+            (key, false) if key.len() == 1 && key.as_bytes()[0].is_ascii_digit() => Some(
+                Self::SeekToFraction((key.as_bytes()[0] - b'0') as f64 / 10.0),
+            ),
+            ("m", false) => Some(Self::AddBookmark),
+            ("h", false) => Some(Self::Navigate(AppRoute::Home)),
+            ("c", false) => Some(Self::Navigate(AppRoute::ChannelsPage)),
+            ("f", false) => Some(Self::Navigate(AppRoute::FeedsPage)),
+            ("b", false) => Some(Self::Navigate(AppRoute::BookmarksPage)),
+            ("t", false) => Some(Self::Navigate(AppRoute::StatsPage)),
+            ("s", false) => Some(Self::Navigate(AppRoute::SettingsPage)),
+            ("i", false) => Some(Self::Navigate(AppRoute::InfoPage)),
+            ("?", _) => Some(Self::ToggleHelp),
+            ("Escape", _) => Some(Self::CloseHelp),
+            _ => None,
+        }
+    }
+}
+
+/// Element a key press is directed at.
+#[derive(Debug, PartialEq)]
+enum Focus {
+    /// text field, selection or editable content (e.g., the URL field of a new feed)
+    Editing,
+    /// button, check box or radio button
+    Button,
+    /// slider (e.g., the time, volume or playback rate)
+    Slider,
+    /// any other element
+    Other,
+}
+
+impl Focus {
+    fn of(event: &KeyboardEvent) -> Self {
+        let element = match event
+            .target()
+            .and_then(|target| target.dyn_into::<HtmlElement>().ok())
+        {
+            Some(element) => element,
+            None => return Self::Other,
+        };
+
+        if element.is_content_editable() {
+            return Self::Editing;
+        }
+
+        match element.tag_name().as_str() {
+            "TEXTAREA" | "SELECT" => Self::Editing,
+            "BUTTON" => Self::Button,
+            "INPUT" => Self::of_input_type(
+                &element
+                    .get_attribute("type")
+                    .unwrap_or_default()
+                    .to_ascii_lowercase(),
+            ),
+            _ => Self::Other,
+        }
+    }
+
+    /// Inputs without a (known) type are text fields.
+    fn of_input_type(input_type: &str) -> Self {
+        match input_type {
+            "button" | "submit" | "reset" | "image" | "checkbox" | "radio" => Self::Button,
+            "range" => Self::Slider,
+            "color" | "file" => Self::Other,
+            _ => Self::Editing,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_type() {
+        assert_eq!(Focus::of_input_type(""), Focus::Editing);
+        assert_eq!(Focus::of_input_type("url"), Focus::Editing);
+        assert_eq!(Focus::of_input_type("number"), Focus::Editing);
+        assert_eq!(Focus::of_input_type("checkbox"), Focus::Button);
+        assert_eq!(Focus::of_input_type("submit"), Focus::Button);
+        assert_eq!(Focus::of_input_type("range"), Focus::Slider);
+        assert_eq!(Focus::of_input_type("file"), Focus::Other);
+    }
+}
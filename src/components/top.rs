@@ -1,14 +1,48 @@
-use super::router::Router;
+use super::{
+    router::Router,
+    shortcuts::{Shortcut, SHORTCUTS_HELP},
+};
 use crate::agents::player;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::KeyboardEvent;
 use yew::{prelude::*, Component};
 use yew_agent::{Bridge, Bridged};
-use yew_router::BrowserRouter;
+use yew_router::{
+    history::{BrowserHistory, History},
+    BrowserRouter,
+};
 
 pub struct Top {
-    _player: Box<dyn Bridge<player::Player>>,
+    player: Box<dyn Bridge<player::Player>>,
+    help_visible: bool,
+    keydown_closure: Closure<dyn Fn(KeyboardEvent)>,
 }
 pub enum Message {
     PlayerMessage(player::Response),
+    Shortcut(Shortcut),
+}
+
+impl Top {
+    fn view_help(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <div class="modal is-active">
+                <div class="modal-background" onclick={ctx.link().callback(|_| Message::Shortcut(Shortcut::CloseHelp))}></div>
+                <div class="modal-content">
+                    <div class="box">
+                        <div class="title">{"Keyboard Shortcuts"}</div>
+                        <table class="table is-fullwidth is-striped">
+                            <tbody>
+                                {SHORTCUTS_HELP.iter().map(|(key, description)| html! {
+                                    <tr><td><span class="tag">{key}</span></td><td>{description}</td></tr>
+                                }).collect::<Html>()}
+                            </tbody>
+                        </table>
+                    </div>
+                </div>
+                <button class="modal-close is-large" aria-label="close" onclick={ctx.link().callback(|_| Message::Shortcut(Shortcut::CloseHelp))}></button>
+            </div>
+        }
+    }
 }
 
 impl Component for Top {
@@ -17,21 +51,98 @@ impl Component for Top {
 
     fn create(ctx: &Context<Self>) -> Self {
         let player_cb = ctx.link().callback(Message::PlayerMessage);
+        let shortcut_cb = ctx.link().callback(Message::Shortcut);
+        let keydown_closure = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+            if let Some(shortcut) = Shortcut::from_event(&event) {
+                // e.g., keep the space bar from scrolling the page
+                event.prevent_default();
+                shortcut_cb.emit(shortcut);
+            }
+        }) as Box<dyn Fn(_)>);
+
+        if let Some(document) = web_sys::window().and_then(|window| window.document()) {
+            if let Err(e) = document.add_event_listener_with_callback(
+                "keydown",
+                keydown_closure.as_ref().unchecked_ref(),
+            ) {
+                log::warn!("could not register keyboard shortcuts: {:?}", e);
+            }
+        }
 
         Self {
-            _player: player::Player::bridge(player_cb),
+            player: player::Player::bridge(player_cb),
+            help_visible: false,
+            keydown_closure,
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, _msg: Self::Message) -> bool {
-        false
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Message::PlayerMessage(_) => false,
+            Message::Shortcut(shortcut) => match shortcut {
+                Shortcut::TogglePlayback => {
+                    self.player.send(player::Request::TogglePlayback);
+                    false
+                }
+                Shortcut::SkipForward => {
+                    self.player.send(player::Request::SkipForward);
+                    false
+                }
+                Shortcut::SkipBackward => {
+                    self.player.send(player::Request::SkipBackward);
+                    false
+                }
+                Shortcut::ChangePlaybackRate(delta) => {
+                    self.player.send(player::Request::ChangePlaybackRate(delta));
+                    false
+                }
+                Shortcut::SeekToFraction(fraction) => {
+                    self.player.send(player::Request::SeekToFraction(fraction));
+                    false
+                }
+                Shortcut::AddBookmark => {
+                    self.player
+                        .send(player::Request::AddBookmark(String::new()));
+                    false
+                }
+                Shortcut::Navigate(route) => {
+                    BrowserHistory::new().push(route);
+                    false
+                }
+                Shortcut::ToggleHelp => {
+                    self.help_visible = !self.help_visible;
+                    true
+                }
+                Shortcut::CloseHelp => {
+                    let update = self.help_visible;
+
+                    self.help_visible = false;
+                    update
+                }
+            },
+        }
     }
 
-    fn view(&self, _ctx: &Context<Self>) -> yew::Html {
+    fn view(&self, ctx: &Context<Self>) -> yew::Html {
         html! {
-            <BrowserRouter>
-                <Router/>
-            </BrowserRouter>
+            <>
+                <BrowserRouter>
+                    <Router/>
+                </BrowserRouter>
+                {match self.help_visible {
+                    true => self.view_help(ctx),
+                    false => html! {},
+                }}
+            </>
+        }
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        if let Some(document) = web_sys::window().and_then(|window| window.document()) {
+            let _ = document.remove_event_listener_with_callback(
+                "keydown",
+                self.keydown_closure.as_ref().unchecked_ref(),
+            );
         }
     }
 }
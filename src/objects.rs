@@ -1,8 +1,24 @@
+mod bookmark;
+pub use bookmark::*;
+mod chapter;
+pub use chapter::*;
+mod consistency_report;
+pub use consistency_report::*;
+mod enclosure_meta;
+pub use enclosure_meta::*;
 mod js_error;
 pub use js_error::*;
+mod listening_session;
+pub use listening_session::*;
 pub use podcast_player_common::{
     channel_meta::ChannelMeta, channel_val::ChannelVal, item_meta::DownloadStatus,
     item_val::ItemVal, Channel, FeedUrl, FeedVal, Item,
 };
+mod player_config;
+pub use player_config::*;
+mod player_state;
+pub use player_state::*;
+mod queue;
+pub use queue::*;
 mod updater_config;
 pub use updater_config::*;
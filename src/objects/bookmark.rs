@@ -0,0 +1,14 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Position in an item marked by the user, optionally with a note (key: id).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    /// position (in seconds) in the item
+    pub position: f64,
+    pub note: String,
+    pub created_at: DateTime<FixedOffset>,
+}
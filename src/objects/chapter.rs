@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Chapter of an episode (times in seconds).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chapter {
+    pub start: f64,
+    pub end: Option<f64>,
+    pub title: String,
+    pub url: Option<String>,
+    pub image: Option<String>,
+}
+
+/// Chapters stored for an item (key: item id).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemChapters {
+    pub id: Uuid,
+    pub chapters: Vec<Chapter>,
+}
@@ -0,0 +1,112 @@
+use podcast_player_common::DownloadStatus;
+
+/// Summary of the repairs done by the consistency check run when the database is opened.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConsistencyReport {
+    pub downloads_reset: u32,
+    pub download_states_fixed: u32,
+    pub orphaned_enclosures_deleted: u32,
+    pub channel_settings_deleted: u32,
+}
+
+impl ConsistencyReport {
+    pub fn is_empty(&self) -> bool {
+        self.downloads_reset == 0
+            && self.download_states_fixed == 0
+            && self.orphaned_enclosures_deleted == 0
+            && self.channel_settings_deleted == 0
+    }
+
+    /// Returns the repair of a download, if it disagrees with the enclosures store, and counts the repair.
+    pub fn repair(&mut self, status: &DownloadStatus, has_enclosure: bool) -> Option<Repair> {
+        match (status, has_enclosure) {
+            (DownloadStatus::InProgress, false) => {
+                self.downloads_reset += 1;
+                Some(Repair::SetStatus(DownloadStatus::Pending))
+            }
+            (DownloadStatus::Ok, false) => {
+                self.download_states_fixed += 1;
+                Some(Repair::SetStatus(DownloadStatus::Pending))
+            }
+            (DownloadStatus::InProgress | DownloadStatus::Pending, true) => {
+                self.download_states_fixed += 1;
+                Some(Repair::SetStatus(DownloadStatus::Ok))
+            }
+            // the download was removed by the user, but its enclosure was left behind
+            (DownloadStatus::NotRequested, true) => {
+                self.orphaned_enclosures_deleted += 1;
+                Some(Repair::DeleteEnclosure)
+            }
+            // the stored enclosure of an error is corrupt; it is replaced by the next download
+            _ => None,
+        }
+    }
+}
+
+/// Repair of the download of an item.
+#[derive(Debug)]
+pub enum Repair {
+    SetStatus(DownloadStatus),
+    /// the enclosure (and its metadata) is deleted
+    DeleteEnclosure,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repair_without_enclosure() {
+        let mut report = ConsistencyReport::default();
+
+        assert!(matches!(
+            report.repair(&DownloadStatus::InProgress, false),
+            Some(Repair::SetStatus(DownloadStatus::Pending))
+        ));
+        assert!(matches!(
+            report.repair(&DownloadStatus::Ok, false),
+            Some(Repair::SetStatus(DownloadStatus::Pending))
+        ));
+        assert!(report.repair(&DownloadStatus::Pending, false).is_none());
+        assert!(report
+            .repair(&DownloadStatus::NotRequested, false)
+            .is_none());
+        assert!(report.repair(&DownloadStatus::Error, false).is_none());
+        assert_eq!(report.downloads_reset, 1);
+        assert_eq!(report.download_states_fixed, 1);
+        assert_eq!(report.orphaned_enclosures_deleted, 0);
+    }
+
+    #[test]
+    fn repair_with_enclosure() {
+        let mut report = ConsistencyReport::default();
+
+        for status in [DownloadStatus::InProgress, DownloadStatus::Pending] {
+            assert!(matches!(
+                report.repair(&status, true),
+                Some(Repair::SetStatus(DownloadStatus::Ok))
+            ));
+        }
+        assert!(matches!(
+            report.repair(&DownloadStatus::NotRequested, true),
+            Some(Repair::DeleteEnclosure)
+        ));
+        assert!(report.repair(&DownloadStatus::Ok, true).is_none());
+        assert!(report.repair(&DownloadStatus::Error, true).is_none());
+        assert_eq!(report.downloads_reset, 0);
+        assert_eq!(report.download_states_fixed, 2);
+        assert_eq!(report.orphaned_enclosures_deleted, 1);
+    }
+
+    #[test]
+    fn is_empty() {
+        let mut report = ConsistencyReport::default();
+
+        assert!(report.is_empty());
+        report.orphaned_enclosures_deleted += 1;
+        assert!(!report.is_empty());
+        report = ConsistencyReport::default();
+        report.channel_settings_deleted += 1;
+        assert!(!report.is_empty());
+    }
+}
@@ -1,8 +1,21 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// Metadata stored alongside an enclosure (key: item id).
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct EnclosureMeta {
     pub id: Uuid,
-    pub download_result: anyhow::Result<u32>,
+    pub mime_type: Option<String>,
+    /// duration in seconds, as found in the enclosure
+    #[serde(default)]
+    pub duration: Option<f64>,
+    /// title embedded in the enclosure
+    #[serde(default)]
+    pub title: Option<String>,
+    /// an embedded cover image is stored in the images store (key: item id)
+    #[serde(default)]
+    pub image: bool,
+    /// reason the enclosure could not be played (it has to be downloaded again)
+    #[serde(default)]
+    pub error: Option<String>,
 }
@@ -0,0 +1,100 @@
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Uninterrupted stretch of listening to an item (key: id).
+/// Sessions end on pausing, seeking, at the end of the item and when the app is hidden.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ListeningSession {
+    pub id: Uuid,
+    pub item_id: Uuid,
+    pub channel_id: Uuid,
+    /// position (in seconds) at the start of the session
+    pub start_position: f64,
+    /// position (in seconds) at the end of the session
+    pub end_position: f64,
+    pub started_at: DateTime<FixedOffset>,
+    pub ended_at: DateTime<FixedOffset>,
+    /// playback rate of the channel during the session
+    pub playback_rate: f64,
+}
+
+impl ListeningSession {
+    /// Wall-clock time (in seconds) spent listening.
+    pub fn listened(&self) -> f64 {
+        ((self.ended_at - self.started_at).num_milliseconds() as f64 / 1000.0).max(0.0)
+    }
+
+    /// Length (in seconds) of the part of the item played.
+    pub fn played(&self) -> f64 {
+        (self.end_position - self.start_position).max(0.0)
+    }
+
+    /// Time (in seconds) saved by playing faster than real time (e.g., higher playback rates or skipped silence).
+    pub fn saved(&self) -> f64 {
+        self.played() - self.listened()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(
+        start_position: f64,
+        end_position: f64,
+        started_at: &str,
+        ended_at: &str,
+    ) -> ListeningSession {
+        ListeningSession {
+            id: Uuid::new_v4(),
+            item_id: Uuid::new_v4(),
+            channel_id: Uuid::new_v4(),
+            start_position,
+            end_position,
+            started_at: DateTime::parse_from_rfc3339(started_at).unwrap(),
+            ended_at: DateTime::parse_from_rfc3339(ended_at).unwrap(),
+            playback_rate: 1.5,
+        }
+    }
+
+    #[test]
+    fn times() {
+        let session = session(
+            100.0,
+            190.0,
+            "2026-10-18T12:00:00+02:00",
+            "2026-10-18T12:01:00+02:00",
+        );
+
+        assert_eq!(session.listened(), 60.0);
+        assert_eq!(session.played(), 90.0);
+        assert_eq!(session.saved(), 30.0);
+    }
+
+    #[test]
+    fn times_across_time_zones() {
+        let session = session(
+            0.0,
+            30.0,
+            "2026-10-18T12:00:00+02:00",
+            "2026-10-18T10:00:30Z",
+        );
+
+        assert_eq!(session.listened(), 30.0);
+        assert_eq!(session.saved(), 0.0);
+    }
+
+    #[test]
+    fn times_never_negative() {
+        let session = session(
+            190.0,
+            100.0,
+            "2026-10-18T12:01:00+02:00",
+            "2026-10-18T12:00:00+02:00",
+        );
+
+        assert_eq!(session.listened(), 0.0);
+        assert_eq!(session.played(), 0.0);
+    }
+}
@@ -0,0 +1,342 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerConfig {
+    /// interval (in seconds) in which the playback position is persisted while playing
+    pub position_save_interval: f64,
+    /// download episodes when they are streamed (they are downloaded separately, the streamed data is not kept)
+    #[serde(alias = "keep_streamed_episodes")]
+    pub download_streamed_episodes: bool,
+    /// skip lengths used for channels without their own
+    pub skip_intervals: SkipIntervals,
+    /// skip lengths by channel id
+    pub channel_skip_intervals: HashMap<Uuid, SkipIntervals>,
+    /// time (in seconds) to rewind when resuming after a long pause (0 disables rewinding)
+    pub resume_rewind: f64,
+    /// minimum length (in seconds) of a pause to rewind on resuming
+    pub resume_rewind_after: f64,
+    /// audio processing by channel id (channels without an entry are played unprocessed)
+    pub channel_audio_processing: HashMap<Uuid, AudioProcessing>,
+    /// lengths of intros and outros to skip by channel id
+    pub channel_intro_outro: HashMap<Uuid, IntroOutro>,
+    /// point from which an episode counts as played
+    pub completion_threshold: CompletionThreshold,
+    /// length (in seconds) of the crossfade into the next queued episode (0 switches without a gap at the end)
+    pub crossfade: f64,
+}
+
+/// Point from which an episode counts as played (e.g., to not require listening to the credits).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum CompletionThreshold {
+    /// played, once the given percentage of the episode is reached
+    Percentage(f64),
+    /// played, once at most the given number of seconds remain
+    Remaining(f64),
+}
+
+impl CompletionThreshold {
+    pub fn is_reached(&self, position: f64, duration: f64) -> bool {
+        match self {
+            Self::Percentage(percentage) => position >= duration * percentage / 100.0,
+            Self::Remaining(remaining) => duration - position <= *remaining,
+        }
+    }
+}
+
+impl Default for CompletionThreshold {
+    fn default() -> Self {
+        Self::Remaining(30.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkipIntervals {
+    /// seconds skipped forward
+    pub forward: f64,
+    /// seconds skipped backward
+    pub backward: f64,
+}
+
+/// Processing of the audio of a channel.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioProcessing {
+    /// level the loudness with a compressor
+    pub normalize: bool,
+    /// raise the playback rate during silence
+    pub skip_silence: bool,
+}
+
+/// Lengths (in seconds) of the intro and outro of the episodes of a channel (0 disables skipping).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntroOutro {
+    /// seconds skipped at the start of an episode
+    pub intro: f64,
+    /// seconds skipped at the end of an episode
+    pub outro: f64,
+}
+
+impl IntroOutro {
+    /// Position (in seconds) episodes started from the beginning start at.
+    pub fn start(&self, duration: f64) -> f64 {
+        match duration.is_finite() {
+            true => self.intro.min(duration),
+            false => self.intro,
+        }
+    }
+
+    /// Whether the given position (in seconds) is within the outro.
+    pub fn outro_reached(&self, position: f64, duration: f64) -> bool {
+        self.outro > 0.0 && duration.is_finite() && position >= duration - self.outro
+    }
+}
+
+impl PlayerConfig {
+    pub fn skip_intervals(&self, channel_id: &Uuid) -> &SkipIntervals {
+        self.channel_skip_intervals
+            .get(channel_id)
+            .unwrap_or(&self.skip_intervals)
+    }
+
+    /// Removes the settings of channels not among the given ones (i.e., no longer stored); returns the number of settings removed.
+    pub fn retain_channels(&mut self, channel_ids: &HashSet<Uuid>) -> u32 {
+        let count = self.channel_settings_count();
+
+        self.channel_skip_intervals
+            .retain(|channel_id, _| channel_ids.contains(channel_id));
+        self.channel_audio_processing
+            .retain(|channel_id, _| channel_ids.contains(channel_id));
+        self.channel_intro_outro
+            .retain(|channel_id, _| channel_ids.contains(channel_id));
+        (count - self.channel_settings_count()) as u32
+    }
+
+    fn channel_settings_count(&self) -> usize {
+        self.channel_skip_intervals.len()
+            + self.channel_audio_processing.len()
+            + self.channel_intro_outro.len()
+    }
+
+    /// Returns the time (in seconds) to rewind when resuming after a pause of the given length (in seconds).
+    pub fn resume_rewind(&self, paused_for: f64) -> Option<f64> {
+        match self.resume_rewind > 0.0 && paused_for >= self.resume_rewind_after {
+            true => Some(self.resume_rewind),
+            false => None,
+        }
+    }
+
+    pub fn audio_processing(&self, channel_id: &Uuid) -> AudioProcessing {
+        self.channel_audio_processing
+            .get(channel_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn intro_outro(&self, channel_id: &Uuid) -> IntroOutro {
+        self.channel_intro_outro
+            .get(channel_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            position_save_interval: 10.0,
+            download_streamed_episodes: false,
+            skip_intervals: SkipIntervals::default(),
+            channel_skip_intervals: HashMap::new(),
+            resume_rewind: 0.0,
+            resume_rewind_after: 300.0,
+            channel_audio_processing: HashMap::new(),
+            channel_intro_outro: HashMap::new(),
+            completion_threshold: CompletionThreshold::default(),
+            crossfade: 0.0,
+        }
+    }
+}
+
+impl Default for SkipIntervals {
+    fn default() -> Self {
+        Self {
+            forward: 30.0,
+            backward: 10.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_defaults() {
+        let config: PlayerConfig = serde_json::from_str("{}").unwrap();
+
+        assert_eq!(config, PlayerConfig::default());
+    }
+
+    #[test]
+    fn deserialize_partial() {
+        let config: PlayerConfig =
+            serde_json::from_str(r#"{"position_save_interval":5.0}"#).unwrap();
+
+        assert_eq!(config.position_save_interval, 5.0);
+        assert_eq!(config.skip_intervals, SkipIntervals::default());
+    }
+
+    #[test]
+    fn deserialize_keep_streamed_episodes() {
+        let config: PlayerConfig =
+            serde_json::from_str(r#"{"keep_streamed_episodes":true}"#).unwrap();
+
+        assert!(config.download_streamed_episodes);
+    }
+
+    #[test]
+    fn skip_intervals() {
+        let channel_id = Uuid::new_v4();
+        let channel_intervals = SkipIntervals {
+            forward: 60.0,
+            backward: 5.0,
+        };
+        let mut config = PlayerConfig::default();
+
+        config
+            .channel_skip_intervals
+            .insert(channel_id, channel_intervals.clone());
+        assert_eq!(config.skip_intervals(&channel_id), &channel_intervals);
+        assert_eq!(
+            config.skip_intervals(&Uuid::new_v4()),
+            &SkipIntervals::default()
+        );
+    }
+
+    #[test]
+    fn retain_channels() {
+        let channel_id = Uuid::new_v4();
+        let mut config = PlayerConfig::default();
+
+        config
+            .channel_skip_intervals
+            .insert(channel_id, SkipIntervals::default());
+        config
+            .channel_skip_intervals
+            .insert(Uuid::new_v4(), SkipIntervals::default());
+        config
+            .channel_audio_processing
+            .insert(Uuid::new_v4(), AudioProcessing::default());
+        config
+            .channel_intro_outro
+            .insert(channel_id, IntroOutro::default());
+        assert_eq!(config.retain_channels(&HashSet::from([channel_id])), 2);
+        assert!(config.channel_intro_outro.contains_key(&channel_id));
+        assert!(config.channel_skip_intervals.contains_key(&channel_id));
+        assert!(config.channel_audio_processing.is_empty());
+        assert_eq!(config.retain_channels(&HashSet::from([channel_id])), 0);
+    }
+
+    #[test]
+    fn resume_rewind() {
+        let mut config = PlayerConfig::default();
+
+        assert_eq!(config.resume_rewind(3600.0), None);
+        config.resume_rewind = 15.0;
+        assert_eq!(config.resume_rewind(299.0), None);
+        assert_eq!(config.resume_rewind(300.0), Some(15.0));
+    }
+
+    #[test]
+    fn audio_processing() {
+        let channel_id = Uuid::new_v4();
+        let processing = AudioProcessing {
+            normalize: true,
+            skip_silence: false,
+        };
+        let mut config = PlayerConfig::default();
+
+        config
+            .channel_audio_processing
+            .insert(channel_id, processing.clone());
+        assert_eq!(config.audio_processing(&channel_id), processing);
+        assert_eq!(
+            config.audio_processing(&Uuid::new_v4()),
+            AudioProcessing::default()
+        );
+    }
+
+    #[test]
+    fn intro_outro() {
+        let channel_id = Uuid::new_v4();
+        let intro_outro = IntroOutro {
+            intro: 45.0,
+            outro: 60.0,
+        };
+        let mut config = PlayerConfig::default();
+
+        config
+            .channel_intro_outro
+            .insert(channel_id, intro_outro.clone());
+        assert_eq!(config.intro_outro(&channel_id), intro_outro);
+        assert_eq!(config.intro_outro(&Uuid::new_v4()), IntroOutro::default());
+    }
+
+    #[test]
+    fn intro_start() {
+        let intro_outro = IntroOutro {
+            intro: 45.0,
+            outro: 0.0,
+        };
+
+        assert_eq!(intro_outro.start(3600.0), 45.0);
+        assert_eq!(intro_outro.start(30.0), 30.0);
+        assert_eq!(intro_outro.start(f64::NAN), 45.0);
+        assert_eq!(IntroOutro::default().start(3600.0), 0.0);
+    }
+
+    #[test]
+    fn outro_reached() {
+        let intro_outro = IntroOutro {
+            intro: 0.0,
+            outro: 60.0,
+        };
+
+        assert!(!intro_outro.outro_reached(3539.0, 3600.0));
+        assert!(intro_outro.outro_reached(3540.0, 3600.0));
+        assert!(!intro_outro.outro_reached(3540.0, f64::INFINITY));
+        assert!(!IntroOutro::default().outro_reached(3600.0, 3600.0));
+    }
+
+    #[test]
+    fn completion_threshold() {
+        let percentage = CompletionThreshold::Percentage(90.0);
+        let remaining = CompletionThreshold::Remaining(30.0);
+
+        assert!(!percentage.is_reached(3239.0, 3600.0));
+        assert!(percentage.is_reached(3240.0, 3600.0));
+        assert!(!remaining.is_reached(3569.0, 3600.0));
+        assert!(remaining.is_reached(3570.0, 3600.0));
+        assert!(remaining.is_reached(3600.0, 3600.0));
+    }
+
+    #[test]
+    fn completion_threshold_serde() {
+        let config: PlayerConfig =
+            serde_json::from_str(r#"{"completion_threshold":{"Percentage":95.0}}"#).unwrap();
+
+        assert_eq!(
+            config.completion_threshold,
+            CompletionThreshold::Percentage(95.0)
+        );
+        assert_eq!(
+            PlayerConfig::default().completion_threshold,
+            CompletionThreshold::Remaining(30.0)
+        );
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Current source of the player; stored to continue with it after a reload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub item_id: Uuid,
+    pub channel_id: Uuid,
+    /// position (in seconds) in the item
+    pub position: f64,
+    /// the item was playing (it is restored paused nevertheless, as browsers block autoplay)
+    pub playing: bool,
+    /// time (ms since the epoch) the item was paused
+    #[serde(default)]
+    pub paused_at: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let state = PlayerState {
+            item_id: Uuid::new_v4(),
+            channel_id: Uuid::new_v4(),
+            position: 90.5,
+            playing: false,
+            paused_at: Some(1_792_324_800_000.0),
+        };
+        let json = serde_json::to_string(&state).unwrap();
+
+        assert_eq!(serde_json::from_str::<PlayerState>(&json).unwrap(), state);
+    }
+
+    #[test]
+    fn deserialize_without_paused_at() {
+        let item_id = Uuid::new_v4();
+        let channel_id = Uuid::new_v4();
+        let json = format!(
+            r#"{{"item_id":"{}","channel_id":"{}","position":90.5,"playing":true}}"#,
+            item_id, channel_id
+        );
+        let state: PlayerState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            state,
+            PlayerState {
+                item_id,
+                channel_id,
+                position: 90.5,
+                playing: true,
+                paused_at: None,
+            }
+        );
+    }
+}
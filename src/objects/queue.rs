@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Ordered list of the items to be played next (stored as a single record, so reordering is atomic).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemQueue {
+    pub item_ids: Vec<Uuid>,
+}
+
+impl ItemQueue {
+    /// Puts the item at the front of the queue; an item already queued is moved.
+    pub fn play_next(&mut self, item_id: Uuid) {
+        self.remove(&item_id);
+        self.item_ids.insert(0, item_id);
+    }
+
+    /// Puts the item at the end of the queue; an item already queued is moved.
+    pub fn append(&mut self, item_id: Uuid) {
+        self.remove(&item_id);
+        self.item_ids.push(item_id);
+    }
+
+    /// Removes the item from the queue; returns `false`, if it was not queued.
+    pub fn remove(&mut self, item_id: &Uuid) -> bool {
+        let len = self.item_ids.len();
+
+        self.item_ids.retain(|id| id != item_id);
+        self.item_ids.len() != len
+    }
+
+    /// Moves a queued item to the given position (clamped to the end of the queue).
+    pub fn move_to(&mut self, item_id: &Uuid, position: usize) {
+        if self.remove(item_id) {
+            self.item_ids
+                .insert(position.min(self.item_ids.len()), *item_id);
+        }
+    }
+
+    pub fn contains(&self, item_id: &Uuid) -> bool {
+        self.item_ids.contains(item_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(len: usize) -> (ItemQueue, Vec<Uuid>) {
+        let ids: Vec<Uuid> = (0..len).map(|_| Uuid::new_v4()).collect();
+
+        (
+            ItemQueue {
+                item_ids: ids.clone(),
+            },
+            ids,
+        )
+    }
+
+    #[test]
+    fn play_next() {
+        let (mut queue, ids) = queue(3);
+        let new_id = Uuid::new_v4();
+
+        queue.play_next(new_id);
+        assert_eq!(queue.item_ids, vec![new_id, ids[0], ids[1], ids[2]]);
+        queue.play_next(ids[2]);
+        assert_eq!(queue.item_ids, vec![ids[2], new_id, ids[0], ids[1]]);
+    }
+
+    #[test]
+    fn append() {
+        let (mut queue, ids) = queue(3);
+        let new_id = Uuid::new_v4();
+
+        queue.append(new_id);
+        assert_eq!(queue.item_ids, vec![ids[0], ids[1], ids[2], new_id]);
+        queue.append(ids[0]);
+        assert_eq!(queue.item_ids, vec![ids[1], ids[2], new_id, ids[0]]);
+    }
+
+    #[test]
+    fn remove() {
+        let (mut queue, ids) = queue(3);
+
+        assert!(queue.remove(&ids[1]));
+        assert_eq!(queue.item_ids, vec![ids[0], ids[2]]);
+        assert!(!queue.remove(&ids[1]));
+        assert!(!queue.contains(&ids[1]));
+    }
+
+    #[test]
+    fn move_to() {
+        let (mut queue, ids) = queue(3);
+
+        queue.move_to(&ids[2], 0);
+        assert_eq!(queue.item_ids, vec![ids[2], ids[0], ids[1]]);
+        queue.move_to(&ids[2], 1);
+        assert_eq!(queue.item_ids, vec![ids[0], ids[2], ids[1]]);
+    }
+
+    #[test]
+    fn move_to_clamped() {
+        let (mut queue, ids) = queue(3);
+
+        queue.move_to(&ids[0], 10);
+        assert_eq!(queue.item_ids, vec![ids[1], ids[2], ids[0]]);
+    }
+
+    #[test]
+    fn move_to_not_queued() {
+        let (mut queue, ids) = queue(2);
+
+        queue.move_to(&Uuid::new_v4(), 0);
+        assert_eq!(queue.item_ids, ids);
+    }
+}
@@ -1,3 +1,5 @@
+mod bookmarks_page;
+pub use bookmarks_page::*;
 mod channels_page;
 pub use channels_page::*;
 mod feeds_page;
@@ -8,3 +10,7 @@ mod info_page;
 pub use info_page::*;
 mod items_page;
 pub use items_page::*;
+mod settings_page;
+pub use settings_page::*;
+mod stats_page;
+pub use stats_page::*;
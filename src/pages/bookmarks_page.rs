@@ -0,0 +1,30 @@
+use crate::components::{BookmarkList, NavBar};
+use yew::{prelude::*, Html};
+
+pub struct BookmarksPage {}
+pub enum Message {}
+
+impl Component for BookmarksPage {
+    type Message = Message;
+    type Properties = ();
+
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        html! {
+            <>
+                <NavBar/>
+                <section class="section">
+                    <div class="title">{"Bookmarks"}</div>
+                    <BookmarkList/>
+                </section>
+            </>
+        }
+    }
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {}
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, _msg: Self::Message) -> bool {
+        false
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
-    agents::notifier,
+    agents::{notifier, repo},
     components::{NavBar, Notification},
-    objects::JsError,
+    objects::{ConsistencyReport, JsError},
     utils,
 };
 use serde::Deserialize;
@@ -9,16 +9,19 @@ use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::ConnectionType;
 use yew::prelude::*;
-use yew_agent::{Dispatched, Dispatcher};
+use yew_agent::{Bridge, Bridged, Dispatched, Dispatcher};
 
 /// TODO: move persist request to repository
 pub struct InfoPage {
     estimate: Option<Estimate>,
     connection_type: Option<ConnectionType>,
     persisted: Option<bool>,
+    consistency_report: Option<ConsistencyReport>,
     notifier: Dispatcher<notifier::Notifier>,
+    _repo: Box<dyn Bridge<repo::Repo>>,
 }
 pub enum Message {
+    RepoMessage(repo::Response),
     GetEstimate(Result<JsValue, JsValue>),
     GetPersisted(Result<JsValue, JsValue>),
     // GetPersist(Result<JsValue, JsValue>),
@@ -99,6 +102,43 @@ impl InfoPage {
         }
     }
 
+    fn view_consistency_info(&self, _ctx: &Context<Self>) -> Html {
+        match &self.consistency_report {
+            Some(report) => html! {
+                <section class="section">
+                    <div class="title">{"Database Consistency"}</div>
+                    <nav class="level">
+                        <div class="level-item has-text-centered">
+                            <div>
+                                <p class="heading">{"downloads reset"}</p>
+                                <p class="title">{report.downloads_reset}</p>
+                            </div>
+                        </div>
+                        <div class="level-item has-text-centered">
+                            <div>
+                                <p class="heading">{"download states fixed"}</p>
+                                <p class="title">{report.download_states_fixed}</p>
+                            </div>
+                        </div>
+                        <div class="level-item has-text-centered">
+                            <div>
+                                <p class="heading">{"orphaned enclosures deleted"}</p>
+                                <p class="title">{report.orphaned_enclosures_deleted}</p>
+                            </div>
+                        </div>
+                        <div class="level-item has-text-centered">
+                            <div>
+                                <p class="heading">{"channel settings deleted"}</p>
+                                <p class="title">{report.channel_settings_deleted}</p>
+                            </div>
+                        </div>
+                    </nav>
+                </section>
+            },
+            None => html! {},
+        }
+    }
+
     fn process_estimate(&mut self, res: Result<JsValue, JsValue>) -> Result<(), JsError> {
         let val = res?;
         let est = serde_wasm_bindgen::from_value::<Estimate>(val)?;
@@ -124,6 +164,7 @@ impl Component for InfoPage {
                 // <Notification/>
                 { self.view_network_info(ctx) }
                 { self.view_storage_info(ctx) }
+                { self.view_consistency_info(ctx) }
             </>
         }
     }
@@ -143,6 +184,9 @@ impl Component for InfoPage {
                 .send_future(async move { Message::GetPersisted(est.await) }),
             Err(e) => notifier.send(notifier::Request::NotifyError(e)),
         };
+        let mut repo = repo::Repo::bridge(ctx.link().callback(Message::RepoMessage));
+
+        repo.send(repo::Request::GetConsistencyReport);
         // ctx.link().send_future(async move {
         //     let storage_manager = web_sys::window().unwrap().navigator().storage();
         //     Message::GetPersist(JsFuture::from(storage_manager.persist().unwrap()).await)
@@ -162,11 +206,20 @@ impl Component for InfoPage {
             },
             notifier,
             persisted: None,
+            consistency_report: None,
+            _repo: repo,
         }
     }
 
     fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
+            Message::RepoMessage(response) => match response {
+                repo::Response::ConsistencyReport(report) => {
+                    self.consistency_report = report;
+                    true
+                }
+                _ => false,
+            },
             Message::GetEstimate(res) => match self.process_estimate(res) {
                 Ok(()) => true,
                 Err(e) => {
@@ -0,0 +1,27 @@
+use crate::components::{NavBar, PlayerSettings};
+use yew::{prelude::*, Html};
+
+pub struct SettingsPage {}
+pub enum Message {}
+
+impl Component for SettingsPage {
+    type Message = Message;
+    type Properties = ();
+
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        html! {
+            <>
+                <NavBar/>
+                <PlayerSettings/>
+            </>
+        }
+    }
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {}
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, _msg: Self::Message) -> bool {
+        false
+    }
+}
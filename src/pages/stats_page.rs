@@ -0,0 +1,296 @@
+use crate::{
+    agents::{notifier, repo},
+    components::NavBar,
+    objects::{Bookmark, Channel, Item, JsError, ListeningSession},
+    utils::{self, Backup, ListeningStats},
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+use yew::prelude::*;
+use yew_agent::{Bridge, Bridged, Dispatched, Dispatcher};
+
+/// Number of recently played episodes shown.
+const RECENT_LENGTH: usize = 10;
+
+pub struct StatsPage {
+    repo: Box<dyn Bridge<repo::Repo>>,
+    notifier: Dispatcher<notifier::Notifier>,
+    sessions: Option<Vec<ListeningSession>>,
+    bookmarks: Option<Vec<Bookmark>>,
+    stats: ListeningStats,
+    channels: HashMap<Uuid, Channel>,
+    /// recently played items (followed through item subscriptions)
+    items: HashMap<Uuid, Option<Item>>,
+    /// exports (data URLs): backup (JSON), history (CSV) and bookmarks (CSV)
+    exports: Option<(String, String, String)>,
+}
+
+pub enum Message {
+    RepoMessage(repo::Response),
+}
+
+impl StatsPage {
+    fn process_update(&mut self, msg: Message) -> Result<bool, JsError> {
+        match msg {
+            Message::RepoMessage(response) => match response {
+                repo::Response::History(sessions) => {
+                    self.sessions = Some(sessions);
+                    self.update_stats()?;
+                    Ok(true)
+                }
+                repo::Response::ListeningSession(session) => match &mut self.sessions {
+                    Some(sessions) => {
+                        sessions.push(session);
+                        self.update_stats()?;
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                },
+                repo::Response::Bookmarks(bookmarks) => {
+                    self.bookmarks = Some(bookmarks);
+                    self.update_exports()?;
+                    Ok(true)
+                }
+                repo::Response::Bookmark(bookmark) => match &mut self.bookmarks {
+                    Some(bookmarks) => {
+                        bookmarks.retain(|b| b.id != bookmark.id);
+                        bookmarks.push(bookmark);
+                        self.update_exports()?;
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                },
+                repo::Response::BookmarkDeleted(id) => match &mut self.bookmarks {
+                    Some(bookmarks) => {
+                        bookmarks.retain(|b| b.id != id);
+                        self.update_exports()?;
+                        Ok(true)
+                    }
+                    None => Ok(false),
+                },
+                repo::Response::Channels(channels) => {
+                    self.channels = channels
+                        .into_iter()
+                        .map(|channel| (channel.val.id, channel))
+                        .collect();
+                    Ok(true)
+                }
+                repo::Response::ItemsChanged(repo::Query::Item(_), diffs) => {
+                    for diff in diffs {
+                        if let repo::Diff::Added(item) | repo::Diff::Updated(item) = diff {
+                            self.items.insert(item.get_id(), Some(item));
+                        }
+                    }
+
+                    Ok(true)
+                }
+                _ => Ok(false),
+            },
+        }
+    }
+
+    /// Recomputes the statistics and exports; subscribes to recently played items not followed yet.
+    fn update_stats(&mut self) -> Result<(), JsError> {
+        let sessions = self.sessions.as_ref().ok_or("history not loaded")?;
+
+        self.stats = ListeningStats::new(sessions);
+        self.update_exports()?;
+
+        for (item_id, _) in self.stats.recent.iter().take(RECENT_LENGTH) {
+            if !self.items.contains_key(item_id) {
+                self.items.insert(*item_id, None);
+                self.repo
+                    .send(repo::Request::Subscribe(repo::Query::Item(*item_id)));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Recreates the exports, once the history and the bookmarks are loaded.
+    fn update_exports(&mut self) -> Result<(), JsError> {
+        if let (Some(sessions), Some(bookmarks)) = (&self.sessions, &self.bookmarks) {
+            let backup = Backup {
+                history: sessions,
+                bookmarks,
+            };
+
+            self.exports = Some((
+                utils::data_url(
+                    "application/json",
+                    serde_json::to_string_pretty(&backup)
+                        .map_err(|e| JsError::from_str(&format!("could not export data: {}", e)))?
+                        .as_bytes(),
+                )?,
+                utils::data_url("text/csv", utils::history_csv(sessions).as_bytes())?,
+                utils::data_url("text/csv", utils::bookmarks_csv(bookmarks).as_bytes())?,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn view_summary(&self) -> Html {
+        html! {
+            <nav class="level">
+                <div class="level-item has-text-centered">
+                    <div>
+                        <p class="heading">{"time listened"}</p>
+                        <p class="title">{format_duration(self.stats.listened)}</p>
+                    </div>
+                </div>
+                <div class="level-item has-text-centered">
+                    <div>
+                        <p class="heading">{"time saved"}</p>
+                        <p class="title">{format_duration(self.stats.saved)}</p>
+                    </div>
+                </div>
+            </nav>
+        }
+    }
+
+    fn view_table(&self, title: &str, rows: Vec<(String, f64)>) -> Html {
+        html! {
+            <div class="column">
+                <table class="table is-fullwidth is-striped">
+                    <thead><tr><th>{title}</th><th class="has-text-right">{"listened"}</th></tr></thead>
+                    <tbody>
+                        {rows.into_iter().map(|(label, time)| html! {
+                            <tr><td>{label}</td><td class="has-text-right">{format_duration(time)}</td></tr>
+                        }).collect::<Html>()}
+                    </tbody>
+                </table>
+            </div>
+        }
+    }
+
+    fn view_recent(&self) -> Html {
+        html! {
+            <table class="table is-fullwidth is-striped">
+                <thead><tr><th>{"episode"}</th><th class="has-text-right">{"last played"}</th></tr></thead>
+                <tbody>
+                    {self.stats.recent.iter().take(RECENT_LENGTH).map(|(item_id, ended_at)| html! {
+                        <tr>
+                            <td>{match self.items.get(item_id) {
+                                Some(Some(item)) => item.get_title(),
+                                _ => String::from("..."),
+                            }}</td>
+                            <td class="has-text-right">{ended_at.format("%Y-%m-%d %H:%M").to_string()}</td>
+                        </tr>
+                    }).collect::<Html>()}
+                </tbody>
+            </table>
+        }
+    }
+
+    fn view_exports(&self) -> Html {
+        match &self.exports {
+            Some((backup, history, bookmarks)) => html! {
+                <p class="buttons">
+                    <a class="button" href={backup.clone()} download="backup.json">{"export history and bookmarks (JSON)"}</a>
+                    <a class="button" href={history.clone()} download="history.csv">{"export history (CSV)"}</a>
+                    <a class="button" href={bookmarks.clone()} download="bookmarks.csv">{"export bookmarks (CSV)"}</a>
+                </p>
+            },
+            None => html! {},
+        }
+    }
+}
+
+impl Component for StatsPage {
+    type Message = Message;
+    type Properties = ();
+
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        let per_day = self
+            .stats
+            .per_day
+            .iter()
+            .take(14)
+            .map(|(date, time)| (date.format("%Y-%m-%d").to_string(), *time))
+            .collect();
+        let per_week = self
+            .stats
+            .per_week
+            .iter()
+            .take(8)
+            .map(|((year, week), time)| (format!("{}-W{:02}", year, week), *time))
+            .collect();
+        let per_channel = self
+            .stats
+            .per_channel
+            .iter()
+            .map(|(channel_id, time)| {
+                (
+                    match self.channels.get(channel_id) {
+                        Some(channel) => channel.val.title.clone(),
+                        None => channel_id.to_string(),
+                    },
+                    *time,
+                )
+            })
+            .collect();
+
+        html! {
+            <>
+                <NavBar/>
+                {match &self.sessions {
+                    Some(_) => html! {
+                        <>
+                            <section class="section">
+                                <div class="title">{"Listening Statistics"}</div>
+                                {self.view_summary()}
+                                <div class="columns">
+                                    {self.view_table("day", per_day)}
+                                    {self.view_table("week", per_week)}
+                                    {self.view_table("channel", per_channel)}
+                                </div>
+                            </section>
+                            <section class="section">
+                                <div class="title">{"Recently Played"}</div>
+                                {self.view_recent()}
+                                {self.view_exports()}
+                            </section>
+                        </>
+                    },
+                    None => html! {},
+                }}
+            </>
+        }
+    }
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut repo = repo::Repo::bridge(ctx.link().callback(Message::RepoMessage));
+
+        repo.send(repo::Request::GetHistory);
+        repo.send(repo::Request::GetBookmarks);
+        repo.send(repo::Request::GetChannels);
+
+        Self {
+            repo,
+            notifier: notifier::Notifier::dispatcher(),
+            sessions: None,
+            bookmarks: None,
+            stats: ListeningStats::default(),
+            channels: HashMap::new(),
+            items: HashMap::new(),
+            exports: None,
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match self.process_update(msg) {
+            Ok(update) => update,
+            Err(e) => {
+                self.notifier.send(notifier::Request::NotifyError(e));
+                false
+            }
+        }
+    }
+}
+
+fn format_duration(seconds: f64) -> String {
+    let minutes = (seconds.max(0.0) / 60.0) as u64;
+
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
@@ -1,5 +1,19 @@
-use crate::objects::JsError;
-use web_sys::ConnectionType;
+mod chapters;
+mod export;
+mod id3;
+mod listening_stats;
+mod media_metadata;
+mod mime;
+use crate::objects::{Item, JsError};
+pub use chapters::*;
+pub use export::*;
+pub use id3::{id3_tag_length, ID3_HEADER_LENGTH};
+use js_sys::Uint8Array;
+pub use listening_stats::*;
+pub use media_metadata::*;
+pub use mime::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, ConnectionType};
 
 pub fn get_connection_type() -> Result<ConnectionType, JsError> {
     let net_info = web_sys::window()
@@ -11,3 +25,39 @@ pub fn get_connection_type() -> Result<ConnectionType, JsError> {
         false => Ok(net_info.type_()),
     }
 }
+
+/// Returns a copy of the item with its play count reset (the item itself only supports incrementing it).
+pub fn reset_play_count(item: &Item) -> Result<Item, JsError> {
+    let map_err =
+        |e: serde_json::Error| JsError::from_str(&format!("could not reset play count: {}", e));
+    let mut value = serde_json::to_value(item).map_err(map_err)?;
+
+    value
+        .get_mut("meta")
+        .and_then(|meta| meta.as_object_mut())
+        .ok_or("item without meta data")?
+        .insert("play_count".into(), 0.into());
+
+    let item: Item = serde_json::from_value(value).map_err(map_err)?;
+
+    match item.get_play_count() {
+        0 => Ok(item),
+        _ => Err(JsError::from_str("could not reset play count")),
+    }
+}
+
+/// Creates a data URL (base64) for the data.
+pub fn data_url(mime_type: &str, data: &[u8]) -> Result<String, JsError> {
+    // btoa expects a "binary string" (one character per byte)
+    let binary: String = data.iter().map(|&byte| byte as char).collect();
+    let encoded = web_sys::window()
+        .ok_or("could not obtain window")?
+        .btoa(&binary)?;
+
+    Ok(format!("data:{};base64,{}", mime_type, encoded))
+}
+
+/// Reads the data of a blob.
+pub async fn read_blob(data: &Blob) -> Result<Vec<u8>, JsError> {
+    Ok(Uint8Array::new(&JsFuture::from(data.array_buffer()).await?).to_vec())
+}
@@ -0,0 +1,358 @@
+use super::id3;
+use crate::objects::{Chapter, Item};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AnnouncedChapters {
+    val: AnnouncedChaptersVal,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AnnouncedChaptersVal {
+    chapters_url: Option<String>,
+}
+
+/// Returns the URL of the chapters file (Podcasting 2.0) linked by the item.
+pub fn announced_chapters_url(item: &Item) -> Option<String> {
+    serde_json::to_value(item)
+        .ok()
+        .and_then(|value| serde_json::from_value::<AnnouncedChapters>(value).ok())
+        .and_then(|announced| announced.val.chapters_url)
+        .filter(|url| !url.is_empty())
+}
+
+#[derive(Deserialize)]
+struct JsonChapters {
+    chapters: Vec<JsonChapter>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonChapter {
+    start_time: f64,
+    end_time: Option<f64>,
+    #[serde(default)]
+    title: String,
+    img: Option<String>,
+    url: Option<String>,
+    toc: Option<bool>,
+}
+
+/// Parses a Podcasting 2.0 chapters file; chapters excluded from the table of contents are skipped.
+pub fn parse_json_chapters(json: &str) -> Result<Vec<Chapter>, serde_json::Error> {
+    let mut chapters: Vec<Chapter> = serde_json::from_str::<JsonChapters>(json)?
+        .chapters
+        .into_iter()
+        .filter(|chapter| chapter.toc.unwrap_or(true))
+        .map(|chapter| Chapter {
+            start: chapter.start_time,
+            end: chapter.end_time,
+            title: chapter.title,
+            url: chapter.url,
+            image: chapter.img,
+        })
+        .collect();
+
+    chapters.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(chapters)
+}
+
+/// Parses the chapters (`CHAP` frames) of an ID3v2.3 or ID3v2.4 tag.
+///
+/// The chapters are ordered as listed by the top-level table of contents (`CTOC` frame); without one, they are ordered by their start time.
+/// Malformed frames end the parsing; the chapters found until then are returned.
+pub fn parse_id3_chapters(tag: &[u8]) -> Vec<Chapter> {
+    let (major, frames) = match id3::tag_frames(tag) {
+        Some(tag_frames) => tag_frames,
+        None => return Vec::new(),
+    };
+    let mut chapters = HashMap::new();
+    let mut order = Vec::new();
+
+    for (id, body) in frames {
+        match id {
+            b"CHAP" => {
+                if let Some((element_id, chapter)) = parse_chap(body, major) {
+                    chapters.insert(element_id, chapter);
+                }
+            }
+            b"CTOC" => {
+                if let Some((true, children)) = parse_ctoc(body) {
+                    order = children;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut res: Vec<Chapter> = order
+        .iter()
+        .filter_map(|element_id| chapters.remove(element_id))
+        .collect();
+    let mut rest: Vec<Chapter> = chapters.into_values().collect();
+
+    rest.sort_by(|a, b| a.start.total_cmp(&b.start));
+    res.append(&mut rest);
+    res
+}
+
+fn parse_chap(body: &[u8], major: u8) -> Option<(Vec<u8>, Chapter)> {
+    let (element_id, rest) = id3::split_terminated(body, 0)?;
+    let times = rest.get(0..16)?;
+    let start = id3::be_u32(&times[0..4]);
+    let end = id3::be_u32(&times[4..8]);
+    let mut chapter = Chapter {
+        start: start as f64 / 1000.0,
+        end: match end {
+            u32::MAX => None,
+            end => Some(end as f64 / 1000.0),
+        },
+        title: String::new(),
+        url: None,
+        image: None,
+    };
+
+    for (id, body) in id3::frames(&rest[16..], major) {
+        match id {
+            b"TIT2" => chapter.title = id3::decode_text(body).unwrap_or_default(),
+            b"WXXX" => chapter.url = decode_user_url(body),
+            _ => {}
+        }
+    }
+
+    Some((element_id.to_vec(), chapter))
+}
+
+/// Returns whether the table of contents is the top-level one and its child element ids.
+fn parse_ctoc(body: &[u8]) -> Option<(bool, Vec<Vec<u8>>)> {
+    let (_element_id, rest) = id3::split_terminated(body, 0)?;
+    let flags = *rest.first()?;
+    let count = *rest.get(1)?;
+    let mut rest = rest.get(2..)?;
+    let mut children = Vec::new();
+
+    for _ in 0..count {
+        let (child, tail) = id3::split_terminated(rest, 0)?;
+
+        children.push(child.to_vec());
+        rest = tail;
+    }
+
+    Some((flags & 0x02 != 0, children))
+}
+
+/// Decodes the URL of a `WXXX` frame (encoding, description, URL).
+fn decode_user_url(body: &[u8]) -> Option<String> {
+    let (encoding, rest) = body.split_first()?;
+    let (_description, url) = id3::split_terminated(rest, *encoding)?;
+    let url = id3::decode(0, url)
+        .trim_end_matches('\0')
+        .trim()
+        .to_string();
+
+    match url.is_empty() {
+        true => None,
+        false => Some(url),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use id3::{encode_frame, encode_tag};
+
+    fn chap(major: u8, element_id: &str, start: u32, end: u32, title: &str) -> Vec<u8> {
+        let title = [&[3][..], title.as_bytes()].concat();
+        let body = [
+            element_id.as_bytes(),
+            &[0],
+            &start.to_be_bytes(),
+            &end.to_be_bytes(),
+            &[0xff; 8],
+            &encode_frame(major, b"TIT2", &title),
+        ]
+        .concat();
+
+        encode_frame(major, b"CHAP", &body)
+    }
+
+    fn ctoc(major: u8, flags: u8, children: &[&str]) -> Vec<u8> {
+        let mut body = [&b"toc\0"[..], &[flags, children.len() as u8]].concat();
+
+        for child in children {
+            body.extend_from_slice(child.as_bytes());
+            body.push(0);
+        }
+
+        encode_frame(major, b"CTOC", &body)
+    }
+
+    fn titles(chapters: &[Chapter]) -> Vec<&str> {
+        chapters
+            .iter()
+            .map(|chapter| chapter.title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn id3_chapters_ctoc_order() {
+        let tag = encode_tag(
+            4,
+            &[
+                chap(4, "ch1", 0, 60000, "first"),
+                chap(4, "ch2", 60000, 120000, "second"),
+                chap(4, "extra", 30000, 40000, "extra"),
+                // not the top-level table of contents
+                ctoc(4, 0x01, &["ch1", "ch2"]),
+                ctoc(4, 0x03, &["ch2", "ch1"]),
+            ],
+        );
+        let chapters = parse_id3_chapters(&tag);
+
+        // chapters not listed follow in the order of their start
+        assert_eq!(titles(&chapters), vec!["second", "first", "extra"]);
+        assert_eq!(
+            chapters[0],
+            Chapter {
+                start: 60.0,
+                end: Some(120.0),
+                title: String::from("second"),
+                url: None,
+                image: None,
+            }
+        );
+    }
+
+    #[test]
+    fn id3_chapters_start_order() {
+        let tag = encode_tag(
+            3,
+            &[
+                chap(3, "ch3", 120000, u32::MAX, "third"),
+                chap(3, "ch1", 0, 60000, "first"),
+                chap(3, "ch2", 60000, 120000, "second"),
+            ],
+        );
+        let chapters = parse_id3_chapters(&tag);
+
+        assert_eq!(titles(&chapters), vec!["first", "second", "third"]);
+        assert_eq!(chapters[2].end, None);
+    }
+
+    #[test]
+    fn id3_chapters_sizes() {
+        // sizes above 127 differ between synchsafe (v2.4) and plain (v2.3) integers
+        let title = "t".repeat(300);
+
+        for major in [3, 4] {
+            let tag = encode_tag(
+                major,
+                &[
+                    chap(major, "ch1", 0, 1000, &title),
+                    chap(major, "ch2", 1000, 2000, "second"),
+                ],
+            );
+
+            assert_eq!(
+                titles(&parse_id3_chapters(&tag)),
+                vec![title.as_str(), "second"]
+            );
+        }
+    }
+
+    #[test]
+    fn id3_chapters_url() {
+        let wxxx = encode_frame(3, b"WXXX", b"\x00link\x00https://example.com/\x00");
+        let body = [
+            &b"ch1\0"[..],
+            &[0; 8],
+            &[0xff; 8],
+            &encode_frame(3, b"TIT2", b"\x00title"),
+            &wxxx,
+        ]
+        .concat();
+        let tag = encode_tag(3, &[encode_frame(3, b"CHAP", &body)]);
+
+        assert_eq!(
+            parse_id3_chapters(&tag)[0].url.as_deref(),
+            Some("https://example.com/")
+        );
+    }
+
+    #[test]
+    fn id3_chapters_malformed() {
+        let tag = encode_tag(
+            4,
+            &[
+                chap(4, "ch1", 0, 60000, "first"),
+                chap(4, "ch2", 60000, 120000, "second"),
+            ],
+        );
+
+        // the truncated chapter is dropped
+        assert_eq!(
+            titles(&parse_id3_chapters(&tag[..tag.len() - 4])),
+            vec!["first"]
+        );
+        // times missing
+        assert!(parse_id3_chapters(&encode_tag(
+            3,
+            &[encode_frame(3, b"CHAP", b"ch1\0\x00\x00")]
+        ))
+        .is_empty());
+        // children missing
+        assert_eq!(
+            titles(&parse_id3_chapters(&encode_tag(
+                3,
+                &[
+                    chap(3, "ch1", 0, 60000, "first"),
+                    encode_frame(3, b"CTOC", b"toc\0\x03\x02ch1\0"),
+                ]
+            ))),
+            vec!["first"]
+        );
+        assert!(parse_id3_chapters(b"no tag").is_empty());
+        assert!(parse_id3_chapters(&[]).is_empty());
+    }
+
+    #[test]
+    fn json_chapters() {
+        let json = r#"{
+            "version": "1.2.0",
+            "chapters": [
+                {"startTime": 90.5, "title": "second", "url": "https://example.com/"},
+                {"startTime": 0, "endTime": 90.5, "title": "first", "img": "https://example.com/image.png"},
+                {"startTime": 30, "title": "hidden", "toc": false},
+                {"startTime": 120, "toc": true}
+            ]
+        }"#;
+        let chapters = parse_json_chapters(json).unwrap();
+
+        assert_eq!(titles(&chapters), vec!["first", "second", ""]);
+        assert_eq!(
+            chapters[0],
+            Chapter {
+                start: 0.0,
+                end: Some(90.5),
+                title: String::from("first"),
+                url: None,
+                image: Some(String::from("https://example.com/image.png")),
+            }
+        );
+        assert_eq!(chapters[1].url.as_deref(), Some("https://example.com/"));
+    }
+
+    #[test]
+    fn json_chapters_malformed() {
+        assert!(parse_json_chapters("").is_err());
+        assert!(parse_json_chapters(r#"{"chapters": [{"startTime": 0"#).is_err());
+        assert!(parse_json_chapters(r#"{"version": "1.2.0"}"#).is_err());
+        assert!(parse_json_chapters(r#"{"chapters": [{"title": "no start"}]}"#).is_err());
+        assert!(parse_json_chapters(r#"{"chapters": []}"#)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,87 @@
+use crate::objects::{Bookmark, ListeningSession};
+use serde::Serialize;
+
+/// User data not synchronized with the server (exported as JSON).
+#[derive(Serialize)]
+pub struct Backup<'a> {
+    pub history: &'a [ListeningSession],
+    pub bookmarks: &'a [Bookmark],
+}
+
+/// Exports the bookmarks as CSV (one bookmark per line, with a header); notes are quoted.
+pub fn bookmarks_csv(bookmarks: &[Bookmark]) -> String {
+    let mut res = String::from("id,item_id,position,created_at,note\n");
+
+    for bookmark in bookmarks {
+        res.push_str(&format!(
+            "{},{},{},{},\"{}\"\n",
+            bookmark.id,
+            bookmark.item_id,
+            bookmark.position,
+            bookmark.created_at.to_rfc3339(),
+            bookmark.note.replace('"', "\"\"")
+        ));
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use uuid::Uuid;
+
+    fn bookmark(note: &str) -> Bookmark {
+        Bookmark {
+            id: Uuid::new_v4(),
+            item_id: Uuid::new_v4(),
+            position: 90.5,
+            note: note.into(),
+            created_at: DateTime::parse_from_rfc3339("2026-10-18T12:00:00+02:00").unwrap(),
+        }
+    }
+
+    #[test]
+    fn csv() {
+        let bookmarks = [bookmark("intro"), bookmark("")];
+        let csv = bookmarks_csv(&bookmarks);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "id,item_id,position,created_at,note".to_string(),
+                format!(
+                    "{},{},90.5,2026-10-18T12:00:00+02:00,\"intro\"",
+                    bookmarks[0].id, bookmarks[0].item_id
+                ),
+                format!(
+                    "{},{},90.5,2026-10-18T12:00:00+02:00,\"\"",
+                    bookmarks[1].id, bookmarks[1].item_id
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_quotes() {
+        let bookmark = bookmark("the \"best\" part,\nagain");
+        let csv = bookmarks_csv(std::slice::from_ref(&bookmark));
+
+        assert!(csv.ends_with(",\"the \"\"best\"\" part,\nagain\"\n"));
+    }
+
+    #[test]
+    fn backup() {
+        let bookmarks = [bookmark("intro")];
+        let backup = serde_json::to_value(Backup {
+            history: &[],
+            bookmarks: &bookmarks,
+        })
+        .unwrap();
+
+        assert_eq!(backup["history"], serde_json::json!([]));
+        assert_eq!(backup["bookmarks"][0]["note"], "intro");
+    }
+}
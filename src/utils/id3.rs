@@ -0,0 +1,324 @@
+/// Length of the ID3v2 header.
+pub const ID3_HEADER_LENGTH: usize = 10;
+const FRAME_HEADER_LENGTH: usize = 10;
+
+/// Frame of an ID3v2 tag (id, body).
+pub type Frame<'a> = (&'a [u8], &'a [u8]);
+
+/// Returns the length of the ID3v2 tag (including header and footer), if the data starts with one.
+pub fn id3_tag_length(header: &[u8]) -> Option<usize> {
+    match header {
+        [b'I', b'D', b'3', major, _, flags, s0, s1, s2, s3, ..] if *major >= 2 => {
+            let footer = match flags & 0x10 {
+                0 => 0,
+                _ => ID3_HEADER_LENGTH,
+            };
+
+            Some(ID3_HEADER_LENGTH + syncsafe(&[*s0, *s1, *s2, *s3]) + footer)
+        }
+        _ => None,
+    }
+}
+
+/// Splits an ID3v2.3 or ID3v2.4 tag into frames (id, body); returns the major version along with the frames.
+/// Malformed frames end the parsing; the frames found until then are returned.
+pub fn tag_frames(tag: &[u8]) -> Option<(u8, Vec<Frame<'_>>)> {
+    let (major, flags) = match tag {
+        [b'I', b'D', b'3', major @ (3 | 4), _, flags, ..] => (*major, *flags),
+        _ => return None,
+    };
+    let end = id3_tag_length(tag)
+        .unwrap_or(0)
+        .min(tag.len())
+        .saturating_sub(match flags & 0x10 {
+            0 => 0,
+            _ => ID3_HEADER_LENGTH,
+        });
+    let mut start = ID3_HEADER_LENGTH;
+
+    // skip the extended header
+    if flags & 0x40 != 0 {
+        let size = match tag.get(start..start + 4) {
+            Some(bytes) if major == 4 => Some(syncsafe(bytes)),
+            Some(bytes) => (be_u32(bytes) as usize).checked_add(4),
+            None => return None,
+        };
+
+        start = size.and_then(|size| start.checked_add(size))?;
+    }
+
+    Some((major, frames(tag.get(start..end).unwrap_or(&[]), major)))
+}
+
+/// Splits the data into frames (id, body); stops at the padding or a malformed frame.
+pub fn frames(data: &[u8], major: u8) -> Vec<Frame<'_>> {
+    let mut res = Vec::new();
+    let mut pos = 0;
+
+    while pos + FRAME_HEADER_LENGTH <= data.len() {
+        let id = &data[pos..pos + 4];
+
+        if !id
+            .iter()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        {
+            break;
+        }
+
+        let size_bytes = &data[pos + 4..pos + 8];
+        let size = match major {
+            4 => syncsafe(size_bytes),
+            _ => be_u32(size_bytes) as usize,
+        };
+        let body_start = pos + FRAME_HEADER_LENGTH;
+        // the size is not trusted (usize has 32 bits on wasm32)
+        let body_end = match body_start.checked_add(size) {
+            Some(body_end) => body_end,
+            None => break,
+        };
+
+        match data.get(body_start..body_end) {
+            Some(body) => res.push((id, body)),
+            None => break,
+        }
+
+        pos = body_end;
+    }
+
+    res
+}
+
+/// Decodes a text frame (encoding byte followed by the text).
+pub fn decode_text(body: &[u8]) -> Option<String> {
+    let (encoding, text) = body.split_first()?;
+
+    Some(
+        decode(*encoding, text)
+            .trim_end_matches('\0')
+            .trim()
+            .to_string(),
+    )
+}
+
+pub fn decode(encoding: u8, data: &[u8]) -> String {
+    match encoding {
+        1 | 2 => {
+            let (big_endian, data) = match data {
+                [0xfe, 0xff, rest @ ..] => (true, rest),
+                [0xff, 0xfe, rest @ ..] => (false, rest),
+                _ => (encoding == 2, data),
+            };
+            let units: Vec<u16> = data
+                .chunks_exact(2)
+                .map(|c| match big_endian {
+                    true => u16::from_be_bytes([c[0], c[1]]),
+                    false => u16::from_le_bytes([c[0], c[1]]),
+                })
+                .collect();
+
+            String::from_utf16_lossy(&units)
+        }
+        3 => String::from_utf8_lossy(data).into_owned(),
+        _ => data.iter().map(|&c| c as char).collect(),
+    }
+}
+
+/// Splits at the terminator of the given encoding (two zero bytes for UTF-16, one otherwise).
+pub fn split_terminated(data: &[u8], encoding: u8) -> Option<(&[u8], &[u8])> {
+    match encoding {
+        1 | 2 => {
+            let pos = data
+                .chunks_exact(2)
+                .position(|c| c == [0, 0])
+                .map(|i| i * 2)?;
+
+            Some((&data[..pos], &data[pos + 2..]))
+        }
+        _ => {
+            let pos = data.iter().position(|&c| c == 0)?;
+
+            Some((&data[..pos], &data[pos + 1..]))
+        }
+    }
+}
+
+pub fn syncsafe(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .take(4)
+        .fold(0, |acc, &b| (acc << 7) | (b & 0x7f) as usize)
+}
+
+pub fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Encodes a frame of an ID3v2.3 or ID3v2.4 tag.
+#[cfg(test)]
+pub fn encode_frame(major: u8, id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let size = match major {
+        4 => encode_syncsafe(body.len()),
+        _ => (body.len() as u32).to_be_bytes(),
+    };
+
+    [&id[..], &size, &[0, 0], body].concat()
+}
+
+/// Encodes an ID3v2.3 or ID3v2.4 tag with the given frames.
+#[cfg(test)]
+pub fn encode_tag(major: u8, frames: &[Vec<u8>]) -> Vec<u8> {
+    let frames = frames.concat();
+
+    [
+        &[b'I', b'D', b'3', major, 0, 0][..],
+        &encode_syncsafe(frames.len()),
+        &frames,
+    ]
+    .concat()
+}
+
+#[cfg(test)]
+fn encode_syncsafe(value: usize) -> [u8; 4] {
+    [21, 14, 7, 0].map(|shift| ((value >> shift) & 0x7f) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tag_length() {
+        assert_eq!(
+            id3_tag_length(&[b'I', b'D', b'3', 4, 0, 0, 0, 0, 0x02, 0x01]),
+            Some(267)
+        );
+        // with footer
+        assert_eq!(
+            id3_tag_length(&[b'I', b'D', b'3', 4, 0, 0x10, 0, 0, 0x02, 0x01]),
+            Some(277)
+        );
+        assert_eq!(id3_tag_length(&[b'I', b'D', b'3', 4, 0, 0]), None);
+        assert_eq!(id3_tag_length(b"RIFF\0\0\0\0\0\0"), None);
+    }
+
+    #[test]
+    fn tag_frames_sizes() {
+        // sizes above 127 differ between synchsafe (v2.4) and plain (v2.3) integers
+        let body = vec![b'x'; 200];
+
+        for major in [3, 4] {
+            let tag = encode_tag(
+                major,
+                &[
+                    encode_frame(major, b"TIT2", &body),
+                    encode_frame(major, b"TLEN", b"\x00100"),
+                ],
+            );
+            let (version, frames) = tag_frames(&tag).unwrap();
+
+            assert_eq!(version, major);
+            assert_eq!(frames.len(), 2);
+            assert_eq!(frames[0], (&b"TIT2"[..], &body[..]));
+            assert_eq!(frames[1], (&b"TLEN"[..], &b"\x00100"[..]));
+        }
+    }
+
+    #[test]
+    fn tag_frames_unsupported_version() {
+        let mut tag = encode_tag(3, &[encode_frame(3, b"TIT2", b"\x00title")]);
+
+        tag[3] = 2;
+        assert!(tag_frames(&tag).is_none());
+        assert!(tag_frames(b"ID3").is_none());
+    }
+
+    #[test]
+    fn tag_frames_padding() {
+        let mut tag = encode_tag(4, &[encode_frame(4, b"TIT2", b"\x00title"), vec![0; 32]]);
+        let length = tag.len();
+
+        tag.extend_from_slice(b"audio data");
+        assert_eq!(id3_tag_length(&tag), Some(length));
+        assert_eq!(tag_frames(&tag).unwrap().1.len(), 1);
+    }
+
+    #[test]
+    fn tag_frames_extended_header() {
+        let frames = encode_frame(3, b"TIT2", b"\x00title");
+        let mut tag = encode_tag(3, &[vec![0, 0, 0, 6, 0, 0, 0, 0, 0, 0], frames]);
+
+        tag[5] = 0x40;
+        assert_eq!(
+            tag_frames(&tag).unwrap().1,
+            vec![(&b"TIT2"[..], &b"\x00title"[..])]
+        );
+    }
+
+    #[test]
+    fn tag_frames_truncated() {
+        let tag = encode_tag(
+            3,
+            &[
+                encode_frame(3, b"TIT2", b"\x00title"),
+                encode_frame(3, b"TLEN", b"\x00100"),
+            ],
+        );
+
+        // the tag announces more data than available
+        assert_eq!(
+            tag_frames(&tag[..tag.len() - 2]).unwrap().1,
+            vec![(&b"TIT2"[..], &b"\x00title"[..])]
+        );
+    }
+
+    #[test]
+    fn frames_oversized() {
+        let mut data = encode_frame(3, b"TIT2", b"\x00title");
+
+        data[4..8].copy_from_slice(&[0xff; 4]);
+        assert!(frames(&data, 3).is_empty());
+        data[4..8].copy_from_slice(&[0x7f; 4]);
+        assert!(frames(&data, 4).is_empty());
+    }
+
+    #[test]
+    fn frames_invalid_id() {
+        let data = [
+            encode_frame(3, b"TIT2", b"\x00title"),
+            encode_frame(3, b"tit2", b"\x00title"),
+            encode_frame(3, b"TLEN", b"\x00100"),
+        ]
+        .concat();
+
+        assert_eq!(frames(&data, 3).len(), 1);
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(decode_text(b"\x03title\0"), Some(String::from("title")));
+        assert_eq!(
+            decode_text(&[1, 0xff, 0xfe, b't', 0, b'i', 0]),
+            Some(String::from("ti"))
+        );
+        assert_eq!(
+            decode_text(&[2, 0, b't', 0, b'i']),
+            Some(String::from("ti"))
+        );
+        assert_eq!(decode_text(&[0, 0xe9]), Some(String::from("é")));
+        assert_eq!(decode_text(&[]), None);
+    }
+
+    #[test]
+    fn terminated() {
+        assert_eq!(
+            split_terminated(b"ab\0cd", 0),
+            Some((&b"ab"[..], &b"cd"[..]))
+        );
+        // UTF-16 terminators are aligned to the code units
+        assert_eq!(
+            split_terminated(&[b'a', 0, 0, b'b', 0, 0, b'c'], 1),
+            Some((&[b'a', 0, 0, b'b'][..], &[b'c'][..]))
+        );
+        assert_eq!(split_terminated(b"ab", 0), None);
+    }
+}
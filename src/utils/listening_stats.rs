@@ -0,0 +1,205 @@
+use crate::objects::ListeningSession;
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+};
+use uuid::Uuid;
+
+/// Statistics of the listening history (times in seconds).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListeningStats {
+    pub listened: f64,
+    pub saved: f64,
+    /// time listened by day (most recent first)
+    pub per_day: Vec<(NaiveDate, f64)>,
+    /// time listened by ISO week (year, week; most recent first)
+    pub per_week: Vec<((i32, u32), f64)>,
+    /// time listened by channel id (longest first)
+    pub per_channel: Vec<(Uuid, f64)>,
+    /// item ids with the end of their last session (most recent first)
+    pub recent: Vec<(Uuid, DateTime<FixedOffset>)>,
+}
+
+impl ListeningStats {
+    /// Sessions are attributed to the day (in their time zone) they started.
+    pub fn new(sessions: &[ListeningSession]) -> Self {
+        let mut per_day = BTreeMap::new();
+        let mut per_week = BTreeMap::new();
+        let mut per_channel = HashMap::new();
+        let mut recent = HashMap::new();
+        let mut res = Self::default();
+
+        for session in sessions {
+            let listened = session.listened();
+            let date = session.started_at.date_naive();
+            let week = date.iso_week();
+
+            res.listened += listened;
+            res.saved += session.saved();
+            *per_day.entry(date).or_insert(0.0) += listened;
+            *per_week.entry((week.year(), week.week())).or_insert(0.0) += listened;
+            *per_channel.entry(session.channel_id).or_insert(0.0) += listened;
+
+            let last = recent.entry(session.item_id).or_insert(session.ended_at);
+
+            if session.ended_at > *last {
+                *last = session.ended_at;
+            }
+        }
+
+        res.per_day = per_day.into_iter().rev().collect();
+        res.per_week = per_week.into_iter().rev().collect();
+        res.per_channel = per_channel.into_iter().collect();
+        res.per_channel.sort_by(|a, b| b.1.total_cmp(&a.1));
+        res.recent = recent.into_iter().collect();
+        res.recent.sort_by_key(|recent| Reverse(recent.1));
+        res
+    }
+}
+
+/// Exports the sessions as CSV (one session per line, with a header).
+pub fn history_csv(sessions: &[ListeningSession]) -> String {
+    let mut res = String::from(
+        "id,item_id,channel_id,start_position,end_position,started_at,ended_at,playback_rate\n",
+    );
+
+    for session in sessions {
+        res.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            session.id,
+            session.item_id,
+            session.channel_id,
+            session.start_position,
+            session.end_position,
+            session.started_at.to_rfc3339(),
+            session.ended_at.to_rfc3339(),
+            session.playback_rate
+        ));
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(
+        item_id: Uuid,
+        channel_id: Uuid,
+        started_at: &str,
+        listened: i64,
+    ) -> ListeningSession {
+        let started_at = DateTime::parse_from_rfc3339(started_at).unwrap();
+
+        ListeningSession {
+            id: Uuid::new_v4(),
+            item_id,
+            channel_id,
+            start_position: 0.0,
+            end_position: 2.0 * listened as f64,
+            started_at,
+            ended_at: started_at + chrono::Duration::seconds(listened),
+            playback_rate: 2.0,
+        }
+    }
+
+    #[test]
+    fn totals() {
+        let (item_id, channel_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let stats = ListeningStats::new(&[
+            session(item_id, channel_id, "2026-10-18T12:00:00+02:00", 60),
+            session(item_id, channel_id, "2026-10-18T13:00:00+02:00", 30),
+        ]);
+
+        assert_eq!(stats.listened, 90.0);
+        assert_eq!(stats.saved, 90.0);
+        assert_eq!(ListeningStats::new(&[]), ListeningStats::default());
+    }
+
+    #[test]
+    fn per_day_and_week() {
+        let (item_id, channel_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let stats = ListeningStats::new(&[
+            // Sunday; the session started before midnight in its time zone
+            session(item_id, channel_id, "2026-10-18T23:30:00+02:00", 60),
+            // Monday of the next ISO week
+            session(item_id, channel_id, "2026-10-19T08:00:00+02:00", 30),
+            session(item_id, channel_id, "2026-10-18T08:00:00+02:00", 10),
+        ]);
+
+        assert_eq!(
+            stats.per_day,
+            vec![
+                (NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(), 30.0),
+                (NaiveDate::from_ymd_opt(2026, 10, 18).unwrap(), 70.0),
+            ]
+        );
+        assert_eq!(stats.per_week, vec![((2026, 43), 30.0), ((2026, 42), 70.0)]);
+    }
+
+    #[test]
+    fn per_channel() {
+        let (channel_a, channel_b) = (Uuid::new_v4(), Uuid::new_v4());
+        let stats = ListeningStats::new(&[
+            session(Uuid::new_v4(), channel_a, "2026-10-18T12:00:00Z", 10),
+            session(Uuid::new_v4(), channel_b, "2026-10-18T13:00:00Z", 30),
+            session(Uuid::new_v4(), channel_a, "2026-10-18T14:00:00Z", 10),
+        ]);
+
+        assert_eq!(
+            stats.per_channel,
+            vec![(channel_b, 30.0), (channel_a, 20.0)]
+        );
+    }
+
+    #[test]
+    fn recent() {
+        let (item_a, item_b, channel_id) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let stats = ListeningStats::new(&[
+            session(item_a, channel_id, "2026-10-18T14:00:00Z", 60),
+            session(item_b, channel_id, "2026-10-18T13:00:00Z", 60),
+            session(item_a, channel_id, "2026-10-18T12:00:00Z", 60),
+        ]);
+
+        assert_eq!(
+            stats.recent,
+            vec![
+                (
+                    item_a,
+                    DateTime::parse_from_rfc3339("2026-10-18T14:01:00Z").unwrap()
+                ),
+                (
+                    item_b,
+                    DateTime::parse_from_rfc3339("2026-10-18T13:01:00Z").unwrap()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn csv() {
+        let session = session(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            "2026-10-18T12:00:00+02:00",
+            60,
+        );
+        let csv = history_csv(std::slice::from_ref(&session));
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "id,item_id,channel_id,start_position,end_position,started_at,ended_at,playback_rate"
+        );
+        assert_eq!(
+            lines[1],
+            format!(
+                "{},{},{},0,120,2026-10-18T12:00:00+02:00,2026-10-18T12:01:00+02:00,2",
+                session.id, session.item_id, session.channel_id
+            )
+        );
+    }
+}
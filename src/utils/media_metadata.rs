@@ -0,0 +1,543 @@
+use super::id3;
+
+/// Metadata embedded in an enclosure.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaMetadata {
+    /// duration in seconds
+    pub duration: Option<f64>,
+    pub title: Option<String>,
+    pub image: Option<EmbeddedImage>,
+}
+
+/// Cover image embedded in an enclosure.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedImage {
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Number of bytes searched for the first frame of MPEG audio.
+pub const MPEG_SEARCH_LENGTH: usize = 4096;
+
+/// Parses the title (`TIT2`), the length (`TLEN`) and the front cover (`APIC`) of an ID3v2.3 or ID3v2.4 tag.
+///
+/// If there are several pictures, the front cover is preferred over the first one.
+pub fn parse_id3_metadata(tag: &[u8]) -> MediaMetadata {
+    let mut res = MediaMetadata::default();
+    let mut cover = None;
+    let frames = match id3::tag_frames(tag) {
+        Some((_major, frames)) => frames,
+        None => return res,
+    };
+
+    for (id, body) in frames {
+        match id {
+            b"TIT2" => res.title = id3::decode_text(body).filter(|title| !title.is_empty()),
+            b"TLEN" => {
+                res.duration = id3::decode_text(body)
+                    .and_then(|length| length.trim().parse::<f64>().ok())
+                    .filter(|length| *length > 0.0)
+                    .map(|length| length / 1000.0)
+            }
+            b"APIC" => {
+                if let Some((picture_type, image)) = parse_apic(body) {
+                    if picture_type == 3 {
+                        cover = Some(image);
+                    } else if res.image.is_none() {
+                        res.image = Some(image);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    res.image = cover.or(res.image);
+    res
+}
+
+/// Parses a picture frame (encoding, MIME type, picture type, description, data).
+fn parse_apic(body: &[u8]) -> Option<(u8, EmbeddedImage)> {
+    let (encoding, rest) = body.split_first()?;
+    let (mime_type, rest) = id3::split_terminated(rest, 0)?;
+    let (picture_type, rest) = rest.split_first()?;
+    let (_description, data) = id3::split_terminated(rest, *encoding)?;
+    let mime_type = match id3::decode(0, mime_type).to_lowercase().as_str() {
+        // ID3v2.2 style image formats
+        "" | "jpg" => String::from("image/jpeg"),
+        "png" => String::from("image/png"),
+        mime_type if mime_type.contains('/') => mime_type.to_string(),
+        mime_type => format!("image/{}", mime_type),
+    };
+
+    match data.is_empty() {
+        true => None,
+        false => Some((
+            *picture_type,
+            EmbeddedImage {
+                mime_type,
+                data: data.to_vec(),
+            },
+        )),
+    }
+}
+
+/// Estimates the duration of MPEG audio from the header of the first frame found in the data.
+///
+/// The number of frames is taken from a Xing/Info or VBRI header; without one, the constant bit rate of the first frame is assumed.
+/// `audio_length` is the number of bytes of audio data (i.e., without tags).
+pub fn mpeg_duration(data: &[u8], audio_length: f64) -> Option<f64> {
+    let (header, data) = (0..data.len())
+        .find_map(|pos| MpegHeader::parse(&data[pos..]).map(|header| (header, &data[pos..])))?;
+
+    match header.vbr_frames(data) {
+        Some(frames) => Some(frames as f64 * header.samples as f64 / header.sample_rate as f64),
+        None => Some(audio_length * 8.0 / (header.bit_rate as f64 * 1000.0)),
+    }
+}
+
+#[derive(Debug)]
+struct MpegHeader {
+    mpeg1: bool,
+    mono: bool,
+    layer: u8,
+    bit_rate: u32,
+    sample_rate: u32,
+    samples: u32,
+}
+
+impl MpegHeader {
+    fn parse(data: &[u8]) -> Option<Self> {
+        let (b1, b2, b3) = match data {
+            [0xFF, b1, b2, b3, ..] if b1 & 0xE0 == 0xE0 => (*b1, *b2, *b3),
+            _ => return None,
+        };
+        let version = (b1 >> 3) & 0x03;
+        let layer = match (b1 >> 1) & 0x03 {
+            0 => return None,
+            bits => 4 - bits,
+        };
+        let mpeg1 = match version {
+            1 => return None,
+            version => version == 3,
+        };
+        let bit_rates: [u32; 15] = match (mpeg1, layer) {
+            (true, 1) => [
+                0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+            ],
+            (true, 2) => [
+                0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+            ],
+            (true, _) => [
+                0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+            ],
+            (false, 1) => [
+                0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+            ],
+            (false, _) => [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+        };
+        let bit_rate = *bit_rates.get((b2 >> 4) as usize)?;
+        let sample_rate = match ((b2 >> 2) & 0x03, version) {
+            (3, _) => return None,
+            (index, 3) => [44100, 48000, 32000][index as usize],
+            (index, 2) => [22050, 24000, 16000][index as usize],
+            (index, _) => [11025, 12000, 8000][index as usize],
+        };
+        let samples = match (layer, mpeg1) {
+            (1, _) => 384,
+            (2, _) | (_, true) => 1152,
+            (_, false) => 576,
+        };
+
+        match bit_rate {
+            0 => None,
+            bit_rate => Some(Self {
+                mpeg1,
+                mono: b3 >> 6 == 3,
+                layer,
+                bit_rate,
+                sample_rate,
+                samples,
+            }),
+        }
+    }
+
+    /// Number of frames announced by a Xing/Info or VBRI header in the first frame.
+    fn vbr_frames(&self, data: &[u8]) -> Option<u32> {
+        if self.layer == 3 {
+            // the Xing header follows the side information
+            let offset = 4 + match (self.mpeg1, self.mono) {
+                (true, false) => 32,
+                (true, true) | (false, false) => 17,
+                (false, true) => 9,
+            };
+
+            if let Some(b"Xing" | b"Info") = data.get(offset..offset + 4) {
+                let flags = id3::be_u32(data.get(offset + 4..offset + 8)?);
+
+                return match flags & 0x01 {
+                    0 => None,
+                    _ => Some(id3::be_u32(data.get(offset + 8..offset + 12)?)),
+                };
+            }
+        }
+
+        match data.get(36..40) {
+            Some(b"VBRI") => Some(id3::be_u32(data.get(50..54)?)),
+            _ => None,
+        }
+    }
+}
+
+/// Length of the header of an MP4 box (including the 64-bit size).
+pub const MP4_BOX_HEADER_LENGTH: usize = 16;
+
+/// Parses the header of an MP4 box; returns the type, the size of the box (`None`: up to the end of the file) and the length of the header.
+pub fn mp4_box_header(data: &[u8]) -> Option<([u8; 4], Option<u64>, usize)> {
+    let size = id3::be_u32(data.get(0..4)?);
+    let box_type = match data.get(4..8)? {
+        [b0, b1, b2, b3] => [*b0, *b1, *b2, *b3],
+        _ => return None,
+    };
+
+    match size {
+        0 => Some((box_type, None, 8)),
+        1 => {
+            let size = data
+                .get(8..16)?
+                .iter()
+                .fold(0u64, |acc, byte| (acc << 8) | *byte as u64);
+
+            Some((box_type, Some(size), 16))
+        }
+        size if size >= 8 => Some((box_type, Some(size as u64), 8)),
+        _ => None,
+    }
+}
+
+/// Parses the duration (`mvhd`), the title and the cover (`udta/meta/ilst`) of the body of an MP4 `moov` box.
+pub fn parse_mp4_moov(moov: &[u8]) -> MediaMetadata {
+    let mut res = MediaMetadata::default();
+
+    for (box_type, body) in mp4_boxes(moov) {
+        match &box_type {
+            b"mvhd" => res.duration = parse_mvhd(body),
+            b"udta" => {
+                for (box_type, body) in mp4_boxes(body) {
+                    if &box_type == b"meta" {
+                        // the meta box is a full box (version and flags), except in QuickTime files
+                        let body = match body.get(4..8) {
+                            Some(b"hdlr") => body,
+                            _ => body.get(4..).unwrap_or(&[]),
+                        };
+
+                        for (box_type, body) in mp4_boxes(body) {
+                            if &box_type == b"ilst" {
+                                parse_ilst(body, &mut res);
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    res
+}
+
+/// Splits the data into boxes (type, body); stops at a malformed box.
+fn mp4_boxes(data: &[u8]) -> Vec<([u8; 4], &[u8])> {
+    let mut res = Vec::new();
+    let mut pos = 0;
+
+    while let Some((box_type, size, header_length)) = mp4_box_header(&data[pos..]) {
+        // the size is not trusted (usize has 32 bits on wasm32)
+        let end = match size {
+            Some(size) => match usize::try_from(size)
+                .ok()
+                .and_then(|size| pos.checked_add(size))
+            {
+                Some(end) => end,
+                None => break,
+            },
+            None => data.len(),
+        };
+
+        match data.get(pos + header_length..end) {
+            Some(body) => res.push((box_type, body)),
+            None => break,
+        }
+
+        pos = end;
+    }
+
+    res
+}
+
+fn parse_mvhd(body: &[u8]) -> Option<f64> {
+    let (time_scale, duration) = match body.first()? {
+        1 => (
+            id3::be_u32(body.get(20..24)?),
+            body.get(24..32)?
+                .iter()
+                .fold(0u64, |acc, byte| (acc << 8) | *byte as u64),
+        ),
+        _ => (
+            id3::be_u32(body.get(12..16)?),
+            id3::be_u32(body.get(16..20)?) as u64,
+        ),
+    };
+
+    match time_scale {
+        0 => None,
+        time_scale => Some(duration as f64 / time_scale as f64),
+    }
+}
+
+/// Parses the title (`©nam`) and the cover (`covr`) of an iTunes metadata list.
+fn parse_ilst(ilst: &[u8], res: &mut MediaMetadata) {
+    for (box_type, body) in mp4_boxes(ilst) {
+        // the value is in a data box (type indicator, locale, value)
+        let (data_type, value) = match mp4_boxes(body)
+            .into_iter()
+            .find(|(box_type, _)| box_type == b"data")
+            .and_then(|(_, data)| Some((id3::be_u32(data.get(0..4)?), data.get(8..)?)))
+        {
+            Some(data) => data,
+            None => continue,
+        };
+
+        match &box_type {
+            [0xA9, b'n', b'a', b'm'] => {
+                res.title = Some(id3::decode(3, value)).filter(|title| !title.is_empty())
+            }
+            b"covr" if res.image.is_none() && !value.is_empty() => {
+                res.image = Some(EmbeddedImage {
+                    mime_type: match data_type & 0x00FF_FFFF {
+                        14 => String::from("image/png"),
+                        27 => String::from("image/bmp"),
+                        _ => String::from("image/jpeg"),
+                    },
+                    data: value.to_vec(),
+                })
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use id3::{encode_frame, encode_tag};
+
+    fn mp4_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        [
+            &((body.len() + 8) as u32).to_be_bytes(),
+            &box_type[..],
+            body,
+        ]
+        .concat()
+    }
+
+    fn apic(mime_type: &str, picture_type: u8, data: &[u8]) -> Vec<u8> {
+        [
+            &[0],
+            mime_type.as_bytes(),
+            &[0, picture_type],
+            b"description\0",
+            data,
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn id3_metadata() {
+        for major in [3, 4] {
+            let tag = encode_tag(
+                major,
+                &[
+                    encode_frame(major, b"TIT2", b"\x03title"),
+                    encode_frame(major, b"TLEN", b"\x00125000"),
+                    encode_frame(major, b"APIC", &apic("image/png", 0, b"other")),
+                    encode_frame(major, b"APIC", &apic("jpg", 3, b"cover")),
+                ],
+            );
+
+            assert_eq!(
+                parse_id3_metadata(&tag),
+                MediaMetadata {
+                    duration: Some(125.0),
+                    title: Some(String::from("title")),
+                    image: Some(EmbeddedImage {
+                        mime_type: String::from("image/jpeg"),
+                        data: b"cover".to_vec(),
+                    }),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn id3_metadata_first_picture() {
+        let tag = encode_tag(
+            3,
+            &[
+                encode_frame(3, b"APIC", &apic("PNG", 0, b"first")),
+                encode_frame(3, b"APIC", &apic("image/gif", 4, b"second")),
+            ],
+        );
+
+        assert_eq!(
+            parse_id3_metadata(&tag).image,
+            Some(EmbeddedImage {
+                mime_type: String::from("image/png"),
+                data: b"first".to_vec(),
+            })
+        );
+    }
+
+    #[test]
+    fn id3_metadata_invalid() {
+        let tag = encode_tag(
+            3,
+            &[
+                encode_frame(3, b"TIT2", b"\x00"),
+                encode_frame(3, b"TLEN", b"\x00unknown"),
+                encode_frame(3, b"APIC", &apic("image/png", 3, b"")),
+            ],
+        );
+
+        assert_eq!(parse_id3_metadata(&tag), MediaMetadata::default());
+        assert_eq!(parse_id3_metadata(b"no tag"), MediaMetadata::default());
+    }
+
+    #[test]
+    fn apic_truncated() {
+        assert!(parse_apic(&[]).is_none());
+        assert!(parse_apic(b"\x00image/png").is_none());
+        assert!(parse_apic(b"\x00image/png\x00\x03description").is_none());
+    }
+
+    /// Header of an MPEG-1 layer III frame (128 kbit/s, 44.1 kHz, stereo).
+    const MPEG_HEADER: [u8; 4] = [0xff, 0xfb, 0x90, 0x00];
+
+    #[test]
+    fn mpeg_duration_cbr() {
+        let data = [&[0, 1, 2][..], &MPEG_HEADER, &[0; 100]].concat();
+
+        assert_eq!(mpeg_duration(&data, 1_600_000.0), Some(100.0));
+    }
+
+    #[test]
+    fn mpeg_duration_xing() {
+        let mut data = [&MPEG_HEADER[..], &[0; 100]].concat();
+
+        data[36..48].copy_from_slice(b"Xing\x00\x00\x00\x01\x00\x00\x03\xe8");
+        assert_eq!(
+            mpeg_duration(&data, 1_600_000.0),
+            Some(1000.0 * 1152.0 / 44100.0)
+        );
+    }
+
+    #[test]
+    fn mpeg_duration_vbri() {
+        let mut data = [&MPEG_HEADER[..], &[0; 100]].concat();
+
+        data[36..40].copy_from_slice(b"VBRI");
+        data[50..54].copy_from_slice(&[0, 0, 0x07, 0xd0]);
+        assert_eq!(
+            mpeg_duration(&data, 1_600_000.0),
+            Some(2000.0 * 1152.0 / 44100.0)
+        );
+    }
+
+    #[test]
+    fn mpeg_duration_invalid() {
+        assert_eq!(mpeg_duration(&[0; 100], 1000.0), None);
+        // free format (bit rate index 0) and reserved sample rate
+        assert_eq!(mpeg_duration(&[0xff, 0xfb, 0x00, 0x00], 1000.0), None);
+        assert_eq!(mpeg_duration(&[0xff, 0xfb, 0x9c, 0x00], 1000.0), None);
+        assert_eq!(mpeg_duration(&[0xff, 0xfb], 1000.0), None);
+    }
+
+    #[test]
+    fn mp4_moov() {
+        let mvhd = [
+            &[0; 12][..],
+            &1000u32.to_be_bytes(),
+            &60000u32.to_be_bytes(),
+        ]
+        .concat();
+        let title = mp4_box(b"data", &[&[0, 0, 0, 1, 0, 0, 0, 0][..], b"title"].concat());
+        let cover = mp4_box(
+            b"data",
+            &[&[0, 0, 0, 14, 0, 0, 0, 0][..], b"cover"].concat(),
+        );
+        let ilst = [
+            mp4_box(&[0xa9, b'n', b'a', b'm'], &title),
+            mp4_box(b"covr", &cover),
+        ]
+        .concat();
+        let meta = [&[0; 4][..], &mp4_box(b"ilst", &ilst)].concat();
+        let moov = [
+            mp4_box(b"mvhd", &mvhd),
+            mp4_box(b"udta", &mp4_box(b"meta", &meta)),
+        ]
+        .concat();
+
+        assert_eq!(
+            parse_mp4_moov(&moov),
+            MediaMetadata {
+                duration: Some(60.0),
+                title: Some(String::from("title")),
+                image: Some(EmbeddedImage {
+                    mime_type: String::from("image/png"),
+                    data: b"cover".to_vec(),
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn mp4_mvhd_version_1() {
+        let mvhd = [
+            &[1][..],
+            &[0; 19],
+            &600u32.to_be_bytes(),
+            &36000u64.to_be_bytes(),
+        ]
+        .concat();
+
+        assert_eq!(
+            parse_mp4_moov(&mp4_box(b"mvhd", &mvhd)).duration,
+            Some(60.0)
+        );
+    }
+
+    #[test]
+    fn mp4_moov_malformed() {
+        let mvhd = mp4_box(
+            b"mvhd",
+            &[
+                &[0; 12][..],
+                &1000u32.to_be_bytes(),
+                &60000u32.to_be_bytes(),
+            ]
+            .concat(),
+        );
+        // 64-bit size beyond the data
+        let oversized = [&1u32.to_be_bytes()[..], b"udta", &[0xff; 8]].concat();
+
+        assert_eq!(
+            parse_mp4_moov(&[&mvhd[..], &oversized].concat()).duration,
+            Some(60.0)
+        );
+        assert_eq!(
+            parse_mp4_moov(&mvhd[..mvhd.len() - 1]),
+            MediaMetadata::default()
+        );
+        assert_eq!(mp4_box_header(&[0, 0, 0, 4, b'f', b'r', b'e', b'e']), None);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::objects::Item;
+use crate::objects::JsError;
+use js_sys::{Array, ArrayBuffer, Uint8Array};
+use serde::Deserialize;
+use wasm_bindgen::JsValue;
+use web_sys::{Blob, BlobPropertyBag};
+
+/// Number of bytes required for sniffing the type.
+pub const SNIFF_LENGTH: u32 = 512;
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AnnouncedEnclosure {
+    val: AnnouncedEnclosureVal,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct AnnouncedEnclosureVal {
+    enclosure_type: Option<String>,
+}
+
+/// Determines the MIME type of an enclosure.
+///
+/// The content type of the download response takes precedence over the type announced in the feed.
+/// If neither is a usable audio type, the type is sniffed from the data.
+pub fn enclosure_mime_type(content_type: Option<&str>, item: &Item, data: &[u8]) -> Option<String> {
+    content_type
+        .and_then(normalize_mime_type)
+        .or_else(|| {
+            announced_enclosure_type(item)
+                .as_deref()
+                .and_then(normalize_mime_type)
+        })
+        .or_else(|| sniff_mime_type(data).map(String::from))
+}
+
+/// Returns the enclosure type announced in the feed.
+fn announced_enclosure_type(item: &Item) -> Option<String> {
+    serde_json::to_value(item)
+        .ok()
+        .and_then(|value| serde_json::from_value::<AnnouncedEnclosure>(value).ok())
+        .and_then(|announced| announced.val.enclosure_type)
+}
+
+/// Maps the many aliases used for audio types to the types understood by browsers.
+/// Generic types (e.g., "application/octet-stream") are not usable and result in `None`.
+pub fn normalize_mime_type(mime_type: &str) -> Option<String> {
+    let mime_type = mime_type.trim().to_lowercase();
+    let essence = mime_type.split(';').next().unwrap_or("").trim();
+
+    match essence {
+        "audio/mpeg" | "audio/mp3" | "audio/mpeg3" | "audio/x-mpeg" | "audio/x-mp3" => {
+            Some("audio/mpeg".into())
+        }
+        "audio/mp4" | "audio/m4a" | "audio/x-m4a" | "audio/mp4a-latm" | "video/mp4" => {
+            Some("audio/mp4".into())
+        }
+        "audio/aac" | "audio/aacp" | "audio/x-aac" => Some("audio/aac".into()),
+        "audio/opus" => Some("audio/ogg; codecs=opus".into()),
+        "audio/ogg" | "application/ogg" | "audio/vorbis" | "audio/x-vorbis+ogg" => {
+            Some(mime_type.replace("application/ogg", "audio/ogg"))
+        }
+        "audio/flac" | "audio/x-flac" => Some("audio/flac".into()),
+        "audio/wav" | "audio/x-wav" | "audio/wave" => Some("audio/wav".into()),
+        "audio/webm" | "video/webm" => Some("audio/webm".into()),
+        _ => None,
+    }
+}
+
+/// Guesses the MIME type from the first bytes of the data.
+pub fn sniff_mime_type(data: &[u8]) -> Option<&'static str> {
+    match data {
+        [b'I', b'D', b'3', ..] => Some("audio/mpeg"),
+        [b'f', b'L', b'a', b'C', ..] => Some("audio/flac"),
+        [b'O', b'g', b'g', b'S', ..] => match find(data, b"OpusHead") {
+            true => Some("audio/ogg; codecs=opus"),
+            false => Some("audio/ogg"),
+        },
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some("audio/wav"),
+        [0x1a, 0x45, 0xdf, 0xa3, ..] => Some("audio/webm"),
+        [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some("audio/mp4"),
+        // ADTS frames (AAC) have the layer bits set to zero
+        [0xff, b1, ..] if b1 & 0xf6 == 0xf0 => Some("audio/aac"),
+        // MPEG audio frames
+        [0xff, b1, ..] if b1 & 0xe0 == 0xe0 => Some("audio/mpeg"),
+        _ => None,
+    }
+}
+
+/// Returns the first bytes of the data, which are sufficient for sniffing the type.
+pub fn sniff_bytes(data: &ArrayBuffer) -> Vec<u8> {
+    Uint8Array::new_with_byte_offset_and_length(data, 0, data.byte_length().min(SNIFF_LENGTH))
+        .to_vec()
+}
+
+/// Wraps the data (a blob or a buffer) into a blob of the given type.
+/// Wrapping a blob does not copy its data.
+pub fn blob_with_type(data: &JsValue, mime_type: &str) -> Result<Blob, JsError> {
+    let mut options = BlobPropertyBag::new();
+
+    options.type_(mime_type);
+    Ok(Blob::new_with_blob_sequence_and_options(
+        &Array::of1(data),
+        &options,
+    )?)
+}
+
+fn find(data: &[u8], needle: &[u8]) -> bool {
+    data.windows(needle.len()).any(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        assert_eq!(
+            normalize_mime_type("audio/mpeg").as_deref(),
+            Some("audio/mpeg")
+        );
+        assert_eq!(
+            normalize_mime_type(" Audio/MP3 ").as_deref(),
+            Some("audio/mpeg")
+        );
+        assert_eq!(
+            normalize_mime_type("audio/x-m4a").as_deref(),
+            Some("audio/mp4")
+        );
+        assert_eq!(
+            normalize_mime_type("audio/opus").as_deref(),
+            Some("audio/ogg; codecs=opus")
+        );
+        assert_eq!(
+            normalize_mime_type("application/ogg; codecs=vorbis").as_deref(),
+            Some("audio/ogg; codecs=vorbis")
+        );
+        assert_eq!(normalize_mime_type("application/octet-stream"), None);
+        assert_eq!(normalize_mime_type(""), None);
+    }
+
+    #[test]
+    fn sniff() {
+        assert_eq!(sniff_mime_type(b"ID3\x04\x00"), Some("audio/mpeg"));
+        assert_eq!(sniff_mime_type(b"fLaC\x00"), Some("audio/flac"));
+        assert_eq!(
+            sniff_mime_type(b"OggS\x00\x02OpusHead"),
+            Some("audio/ogg; codecs=opus")
+        );
+        assert_eq!(
+            sniff_mime_type(b"OggS\x00\x02\x01vorbis"),
+            Some("audio/ogg")
+        );
+        assert_eq!(
+            sniff_mime_type(b"RIFF\x24\x00\x00\x00WAVEfmt "),
+            Some("audio/wav")
+        );
+        assert_eq!(
+            sniff_mime_type(&[0x1a, 0x45, 0xdf, 0xa3, 0x01]),
+            Some("audio/webm")
+        );
+        assert_eq!(
+            sniff_mime_type(b"\x00\x00\x00\x20ftypM4A "),
+            Some("audio/mp4")
+        );
+        assert_eq!(sniff_mime_type(&[0xff, 0xf1, 0x50]), Some("audio/aac"));
+        assert_eq!(sniff_mime_type(&[0xff, 0xfb, 0x90]), Some("audio/mpeg"));
+        assert_eq!(sniff_mime_type(b"<html>"), None);
+        assert_eq!(sniff_mime_type(&[]), None);
+    }
+}